@@ -2,19 +2,39 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use leagus::models::SeasonError;
 
 /// All errors exposed via external interfaces
 pub enum LeagusError {
     /// Something unexpected went wrong
     Internal,
+    /// The requested resource does not exist
+    NotFound,
+    /// The request was malformed, e.g. a form with an invalid date
+    BadRequest,
+    /// The change isn't allowed in the current state, e.g. writing to a
+    /// finished season
+    Conflict,
 }
 
 impl IntoResponse for LeagusError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             LeagusError::Internal => (StatusCode::INTERNAL_SERVER_ERROR, "Something went wrong"),
+            LeagusError::NotFound => (StatusCode::NOT_FOUND, "Not found"),
+            LeagusError::BadRequest => (StatusCode::BAD_REQUEST, "Bad request"),
+            LeagusError::Conflict => (StatusCode::CONFLICT, "Not allowed in the season's status"),
         };
 
         (status, message).into_response()
     }
 }
+
+impl From<SeasonError> for LeagusError {
+    fn from(error: SeasonError) -> Self {
+        match error {
+            SeasonError::Storage(_) => LeagusError::Internal,
+            _ => LeagusError::Conflict,
+        }
+    }
+}
@@ -1 +1,5 @@
+pub mod brackets;
+pub mod calendars;
+pub mod ladders;
 pub mod root;
+pub mod seasons;
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use askama::Template;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use axum::{routing::get, Router};
+use bson::Uuid;
+
+use leagus::models::BracketId;
+use leagus::persistence::WriteableStore;
+
+use crate::errors::LeagusError;
+use crate::models::bracket_view::BracketView;
+use crate::state::AppState;
+
+/// Routes available for '/brackets' path.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/:bracket_id", get(show))
+}
+
+async fn show(
+    State(state): State<AppState>,
+    Path(bracket_id): Path<String>,
+) -> Result<Html<String>, LeagusError> {
+    let bracket_id = Uuid::parse_str(bracket_id).map_err(|_| LeagusError::NotFound)?;
+    let bracket = state
+        .store
+        .get_bracket(&BracketId::from(bracket_id))
+        .await
+        .ok_or(LeagusError::NotFound)?;
+
+    let mut names = HashMap::new();
+    for participant_id in &bracket.seeds {
+        if let Some(participant) = state.store.get_participant(participant_id).await {
+            names.insert(participant.id, participant.name);
+        }
+    }
+
+    BracketTemplate {
+        bracket: BracketView::new(&bracket, &names),
+    }
+    .render()
+    .map(Html)
+    .map_err(|_| LeagusError::Internal)
+}
+
+#[derive(Template)]
+#[template(path = "bracket.html")]
+struct BracketTemplate {
+    bracket: BracketView,
+}
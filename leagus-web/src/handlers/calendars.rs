@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::{routing::get, Router};
+use bson::Uuid;
+use chrono::Utc;
+
+use leagus::calendar::{Calendar, SeasonSchedule};
+use leagus::models::{League, LeagueId, ParticipantId, Season, SeasonId, SeasonStatus};
+use leagus::persistence::WriteableStore;
+
+use crate::errors::LeagusError;
+use crate::state::AppState;
+
+/// Routes available for '/calendars' path.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/leagues/:league_id", get(league))
+        .route("/seasons/:season_id", get(season))
+        .route("/participants/:participant_id", get(participant))
+}
+
+/// Every session and match of each of a league's seasons.
+async fn league(
+    State(state): State<AppState>,
+    Path(league_id): Path<String>,
+) -> Result<impl IntoResponse, LeagusError> {
+    let league_id = Uuid::parse_str(league_id).map_err(|_| LeagusError::NotFound)?;
+    let league = state
+        .store
+        .get_league(&LeagueId::from(league_id))
+        .await
+        .ok_or(LeagusError::NotFound)?;
+
+    let mut calendar = Calendar::new(&league.name, Utc::now());
+    for season in state.store.list_seasons_for_league(&league.id).await {
+        add_season(&state, &mut calendar, &league, &season, None).await;
+    }
+    Ok(ics(&calendar))
+}
+
+/// Every session and match of a season.
+async fn season(
+    State(state): State<AppState>,
+    Path(season_id): Path<String>,
+) -> Result<impl IntoResponse, LeagusError> {
+    let season_id = Uuid::parse_str(season_id).map_err(|_| LeagusError::NotFound)?;
+    let season = state
+        .store
+        .get_season(&SeasonId::from(season_id))
+        .await
+        .filter(|season| season.status != SeasonStatus::Draft)
+        .ok_or(LeagusError::NotFound)?;
+    let league = state
+        .store
+        .get_league(&season.league_id)
+        .await
+        .ok_or(LeagusError::NotFound)?;
+
+    let name = format!("{} {}", league.name, season.name);
+    let mut calendar = Calendar::new(name.trim(), Utc::now());
+    add_season(&state, &mut calendar, &league, &season, None).await;
+    Ok(ics(&calendar))
+}
+
+/// The sessions a participant is playing in, across all leagues, and their
+/// matches.
+async fn participant(
+    State(state): State<AppState>,
+    Path(participant_id): Path<String>,
+) -> Result<impl IntoResponse, LeagusError> {
+    let participant_id = Uuid::parse_str(participant_id).map_err(|_| LeagusError::NotFound)?;
+    let participant = state
+        .store
+        .get_participant(&ParticipantId::from(participant_id))
+        .await
+        .ok_or(LeagusError::NotFound)?;
+
+    let mut calendar = Calendar::new(&participant.name, Utc::now());
+    for league in state.store.list_leagues().await {
+        for season in state.store.list_seasons_for_league(&league.id).await {
+            add_season(
+                &state,
+                &mut calendar,
+                &league,
+                &season,
+                Some(&participant.id),
+            )
+            .await;
+        }
+    }
+    Ok(ics(&calendar))
+}
+
+/// Load a season's schedule and add it to the calendar, only including a
+/// participant's sessions if one is given. Draft seasons are left out, they
+/// aren't shown to participants yet.
+async fn add_season(
+    state: &AppState,
+    calendar: &mut Calendar,
+    league: &League,
+    season: &Season,
+    participant_id: Option<&ParticipantId>,
+) {
+    if season.status == SeasonStatus::Draft {
+        return;
+    }
+    let sessions = state.store.list_sessions_for_season(&season.id).await;
+    let rounds = state.store.list_rounds_for_season(&season.id).await;
+    let matches = state.store.list_matches_for_season(&season.id).await;
+    let venues = state.store.list_venues().await;
+    let names: HashMap<ParticipantId, String> = state
+        .store
+        .list_participants(None)
+        .await
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+
+    let schedule = SeasonSchedule {
+        league,
+        season,
+        sessions: &sessions,
+        rounds: &rounds,
+        matches: &matches,
+    };
+    match participant_id {
+        Some(participant_id) => calendar.add_season_for(&schedule, &names, &venues, participant_id),
+        None => calendar.add_season(&schedule, &names, &venues),
+    }
+}
+
+/// Serve the calendar as an iCalendar feed.
+fn ics(calendar: &Calendar) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        calendar.to_ics(),
+    )
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use askama::Template;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use axum::{routing::get, Router};
+use bson::Uuid;
+
+use leagus::models::LeagueId;
+use leagus::persistence::WriteableStore;
+
+use crate::errors::LeagusError;
+use crate::models::ladder_view::LadderView;
+use crate::state::AppState;
+
+/// Routes available for '/ladders' path.
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/:league_id", get(show))
+}
+
+async fn show(
+    State(state): State<AppState>,
+    Path(league_id): Path<String>,
+) -> Result<Html<String>, LeagusError> {
+    let league_id = Uuid::parse_str(league_id).map_err(|_| LeagusError::NotFound)?;
+    let league = state
+        .store
+        .get_league(&LeagueId::from(league_id))
+        .await
+        .ok_or(LeagusError::NotFound)?;
+    let ladder = league.ladder().ok_or(LeagusError::NotFound)?;
+    let challenges = state.store.list_challenges_for_league(&league.id).await;
+
+    let mut names = HashMap::new();
+    for rung in &ladder.rungs {
+        if let Some(participant) = state.store.get_participant(&rung.participant_id).await {
+            names.insert(participant.id, participant.name);
+        }
+    }
+
+    LadderTemplate {
+        ladder: LadderView::new(&league.name, ladder, &challenges, &names),
+    }
+    .render()
+    .map(Html)
+    .map_err(|_| LeagusError::Internal)
+}
+
+#[derive(Template)]
+#[template(path = "ladder.html")]
+struct LadderTemplate {
+    ladder: LadderView,
+}
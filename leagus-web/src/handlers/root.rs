@@ -2,37 +2,47 @@ use askama::Template;
 use axum::response::Html;
 use axum::{routing::get, Router};
 
+use leagus::models::{Participant, PointsTable, PointsTableEntry, ScoringSystem, TieBreaker};
+
+use crate::errors::LeagusError;
 use crate::models::league_entry::LeagueEntry;
+use crate::state::AppState;
 
 /// Routes available for '/leagues' path.
-pub fn routes() -> Router {
+pub fn routes() -> Router<AppState> {
     // TODO: Error handling
     Router::new().route("/", get(index))
 }
 
-async fn index() -> Html<String> {
-    let mut entries = vec![
-        LeagueEntry {
-            player: "Lionel".into(),
-            score: 21,
-        },
-        LeagueEntry {
-            player: "Robert".into(),
-            score: 19,
-        },
-        LeagueEntry {
-            player: "Caleb".into(),
-            score: 35,
-        },
-        LeagueEntry {
-            player: "Noah".into(),
-            score: 35,
-        },
-    ];
-
-    entries.sort_by_key(|x| -x.score);
-
-    Html(IndexFullTemplate { entries }.to_string())
+async fn index() -> Result<Html<String>, LeagusError> {
+    let table = PointsTable {
+        entries: vec![
+            fake_entry("Lionel", 21, 9),
+            fake_entry("Robert", 19, 9),
+            fake_entry("Caleb", 35, 14),
+            fake_entry("Noah", 35, 12),
+        ],
+    };
+
+    let tie_breakers = [TieBreaker::MatchesPlayed, TieBreaker::Random { seed: 0 }];
+    let entries = table
+        .rank(&tie_breakers, &ScoringSystem::default(), &[])
+        .into_iter()
+        .map(LeagueEntry::from)
+        .collect();
+
+    IndexFullTemplate { entries }
+        .render()
+        .map(Html)
+        .map_err(|_| LeagusError::Internal)
+}
+
+/// A stand-in entry until the dashboard reads the active season's table.
+fn fake_entry(name: &str, points: u32, wins: u32) -> PointsTableEntry {
+    let mut entry = PointsTableEntry::new(&Participant::new(name.into()));
+    entry.points = points;
+    entry.wins = wins;
+    entry
 }
 
 #[derive(Template)]
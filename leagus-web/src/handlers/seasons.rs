@@ -0,0 +1,199 @@
+use askama::Template;
+use axum::extract::{Path, State};
+use axum::response::Html;
+use axum::{
+    routing::{get, post},
+    Form, Router,
+};
+use bson::Uuid;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+
+use leagus::models::{Season, SeasonId, SeasonStatus};
+use leagus::persistence::WriteableStore;
+
+use crate::errors::LeagusError;
+use crate::models::league_entry::LeagueEntry;
+use crate::state::AppState;
+
+/// Routes available for '/seasons' path.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/:season_id", get(show))
+        .route("/:season_id/clone", post(clone))
+}
+
+/// A season's standings. Draft seasons aren't shown to participants yet.
+async fn show(
+    State(state): State<AppState>,
+    Path(season_id): Path<String>,
+) -> Result<Html<String>, LeagusError> {
+    let season = get_season(&state, season_id)
+        .await?
+        .filter(|season| season.status != SeasonStatus::Draft)
+        .ok_or(LeagusError::NotFound)?;
+    // Only confirmed results count towards the standings.
+    let mut matches = state.store.list_matches_for_season(&season.id).await;
+    matches.retain(|a_match| a_match.confirmed);
+
+    SeasonTemplate {
+        id: season.id.to_string(),
+        name: season.name.clone(),
+        status: season.status.to_string(),
+        frozen: season.status >= SeasonStatus::Finished,
+        entries: season
+            .standings(&matches)
+            .into_iter()
+            .map(LeagueEntry::from)
+            .collect(),
+    }
+    .render()
+    .map(Html)
+    .map_err(|_| LeagusError::Internal)
+}
+
+/// The new season to create from a previous one.
+#[derive(Deserialize)]
+struct CloneForm {
+    name: String,
+    start: NaiveDate,
+    end: NaiveDate,
+    /// Present when the participants are seeded from the final table.
+    seed: Option<String>,
+}
+
+/// Create a new draft season from a previous one, scheduling its sessions if
+/// they recur.
+async fn clone(
+    State(state): State<AppState>,
+    Path(season_id): Path<String>,
+    Form(form): Form<CloneForm>,
+) -> Result<Html<String>, LeagusError> {
+    let season = get_season(&state, season_id)
+        .await?
+        .ok_or(LeagusError::NotFound)?;
+    let league = state
+        .store
+        .get_league(&season.league_id)
+        .await
+        .ok_or(LeagusError::NotFound)?;
+    if form.end < form.start {
+        return Err(LeagusError::BadRequest);
+    }
+    let start = midnight(form.start);
+    let end = midnight(form.end);
+
+    // Participants are seeded by their confirmed results.
+    let mut matches = state.store.list_matches_for_season(&season.id).await;
+    matches.retain(|a_match| a_match.confirmed);
+    let next = season.from_previous(
+        &league,
+        &matches,
+        &start,
+        &end,
+        form.name.trim(),
+        form.seed.is_some(),
+    );
+    state.store.create_season(&next, false).await;
+    let sessions = next
+        .recurrence
+        .as_ref()
+        .map(|recurrence| recurrence.sessions(&next, &[]))
+        .unwrap_or_default();
+    // The new season is a draft, so none of its sessions are active yet.
+    if let Err(error) = state.store.create_sessions(&sessions).await {
+        state.store.delete_season(&next.id).await;
+        return Err(error.into());
+    }
+
+    ClonedSeasonTemplate {
+        id: next.id.to_string(),
+        name: next.name,
+        participants: next.roster.len(),
+        sessions: sessions.len(),
+    }
+    .render()
+    .map(Html)
+    .map_err(|_| LeagusError::Internal)
+}
+
+/// Get the season with the id given.
+async fn get_season(state: &AppState, season_id: String) -> Result<Option<Season>, LeagusError> {
+    let season_id = Uuid::parse_str(season_id).map_err(|_| LeagusError::NotFound)?;
+    Ok(state.store.get_season(&SeasonId::from(season_id)).await)
+}
+
+/// The start of a day, in UTC.
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0).expect("a valid time").and_utc()
+}
+
+#[derive(Template)]
+#[template(path = "season.html")]
+struct SeasonTemplate {
+    id: String,
+    name: String,
+    status: String,
+    /// Whether the table is final.
+    frozen: bool,
+    entries: Vec<LeagueEntry>,
+}
+
+#[derive(Template)]
+#[template(path = "partials/cloned_season.html")]
+struct ClonedSeasonTemplate {
+    id: String,
+    name: String,
+    participants: usize,
+    sessions: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone, Weekday};
+    use leagus::models::{League, Recurrence, Sport};
+
+    #[tokio::test]
+    #[ignore = "needs the MongoDB from docker-compose.yml"]
+    async fn cloned_sessions_are_not_made_active() {
+        let state = AppState::new().await;
+        let league = League::new(&Uuid::new().to_string(), "", Sport::Tennis);
+        state.store.create_league(league.clone()).await;
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap();
+        let mut season = Season::new(&league.id, &start, &end, "2024");
+        season.recurrence = Some(Recurrence::weekly(
+            Weekday::Tue,
+            NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+        ));
+        state.store.create_season(&season, false).await;
+
+        let form = CloneForm {
+            name: "2025".to_string(),
+            start: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(),
+            seed: None,
+        };
+        let cloned = clone(
+            State(state.clone()),
+            Path(season.id.to_string()),
+            Form(form),
+        )
+        .await;
+        assert!(cloned.is_ok());
+
+        let next = state
+            .store
+            .list_seasons_for_league(&league.id)
+            .await
+            .into_iter()
+            .find(|next| next.name == "2025")
+            .unwrap();
+        assert_eq!(
+            state.store.list_sessions_for_season(&next.id).await.len(),
+            4
+        );
+        assert_eq!(next.active_session, None);
+    }
+}
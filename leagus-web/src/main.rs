@@ -1,5 +1,5 @@
 use axum::{extract::MatchedPath, http::Request, response::Response, Router};
-use handlers::root;
+use handlers::{brackets, calendars, ladders, root, seasons};
 use state::AppState;
 use std::time::Duration;
 use tower_http::services::ServeDir;
@@ -20,6 +20,10 @@ async fn main() {
 
     let app = Router::new()
         .nest("/", root::routes())
+        .nest("/brackets", brackets::routes())
+        .nest("/calendars", calendars::routes())
+        .nest("/ladders", ladders::routes())
+        .nest("/seasons", seasons::routes())
         .nest_service("/assets", ServeDir::new("assets"))
         // `TraceLayer` is provided by tower-http so you have to add that as a dependency.
         // It provides good defaults but is also very customizable.
@@ -60,7 +64,8 @@ async fn main() {
                         // ...
                     },
                 ),
-        );
+        )
+        .with_state(state);
 
     // run our app with hyper, listening globally on port 3000
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
@@ -1 +1,3 @@
+pub mod bracket_view;
+pub mod ladder_view;
 pub mod league_entry;
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use leagus::models::{Bracket, BracketFormat, BracketSection, Occupant, ParticipantId, Side};
+
+/// A knockout bracket laid out for display, section by section and round by
+/// round.
+pub struct BracketView {
+    pub name: String,
+    pub champion: Option<String>,
+    pub sections: Vec<SectionView>,
+}
+
+pub struct SectionView {
+    pub title: String,
+    pub rounds: Vec<RoundView>,
+}
+
+pub struct RoundView {
+    pub title: String,
+    pub matches: Vec<MatchView>,
+}
+
+pub struct MatchView {
+    pub home: SideView,
+    pub away: SideView,
+}
+
+pub struct SideView {
+    pub name: String,
+    pub won: bool,
+}
+
+/// The order sections are shown in.
+const SECTIONS: [BracketSection; 5] = [
+    BracketSection::Main,
+    BracketSection::Losers,
+    BracketSection::GrandFinal,
+    BracketSection::ThirdPlace,
+    BracketSection::Plate,
+];
+
+impl BracketView {
+    /// Lay out a bracket using the names of its participants.
+    pub fn new(bracket: &Bracket, names: &HashMap<ParticipantId, String>) -> BracketView {
+        let name_of = |occupant: Occupant| match occupant {
+            Occupant::Participant(id) => names
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| "Unknown".to_string()),
+            Occupant::Bye => "Bye".to_string(),
+            Occupant::Pending => "TBD".to_string(),
+        };
+
+        let sections = SECTIONS
+            .iter()
+            .filter_map(|section| {
+                let indices: Vec<usize> = (0..bracket.matches.len())
+                    .filter(|index| bracket.matches[*index].section == *section)
+                    .collect();
+                let round_count = indices
+                    .iter()
+                    .map(|index| bracket.matches[*index].round)
+                    .max()?;
+
+                let rounds = (1..=round_count)
+                    .map(|round| RoundView {
+                        title: round_title(*section, round, round_count),
+                        matches: indices
+                            .iter()
+                            .filter(|index| bracket.matches[**index].round == round)
+                            .map(|index| {
+                                let (home, away) = bracket.occupants(*index);
+                                let winner = bracket.matches[*index].winner;
+                                MatchView {
+                                    home: SideView {
+                                        name: name_of(home),
+                                        won: winner == Some(Side::Home),
+                                    },
+                                    away: SideView {
+                                        name: name_of(away),
+                                        won: winner == Some(Side::Away),
+                                    },
+                                }
+                            })
+                            .collect(),
+                    })
+                    .collect();
+
+                Some(SectionView {
+                    title: section_title(*section, bracket.format),
+                    rounds,
+                })
+            })
+            .collect();
+
+        BracketView {
+            name: bracket.name.clone(),
+            champion: bracket
+                .champion()
+                .map(|id| name_of(Occupant::Participant(id))),
+            sections,
+        }
+    }
+}
+
+fn section_title(section: BracketSection, format: BracketFormat) -> String {
+    match (section, format) {
+        (BracketSection::Main, BracketFormat::DoubleElimination) => "Winners bracket",
+        (BracketSection::Main, _) => "Main draw",
+        (BracketSection::Losers, _) => "Losers bracket",
+        (BracketSection::GrandFinal, _) => "Grand final",
+        (BracketSection::ThirdPlace, _) => "Third-place play-off",
+        (BracketSection::Plate, _) => "Plate",
+    }
+    .to_string()
+}
+
+/// Name the rounds of a knockout by how far they are from its final.
+fn round_title(section: BracketSection, round: usize, round_count: usize) -> String {
+    match (section, round_count - round) {
+        (BracketSection::Main | BracketSection::Plate, 0) => "Final".to_string(),
+        (BracketSection::Main | BracketSection::Plate, 1) => "Semi-finals".to_string(),
+        (BracketSection::Main | BracketSection::Plate, 2) => "Quarter-finals".to_string(),
+        (BracketSection::GrandFinal | BracketSection::ThirdPlace, _) => String::new(),
+        _ => format!("Round {round}"),
+    }
+}
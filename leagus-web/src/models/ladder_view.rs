@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use leagus::models::{Challenge, ChallengeStatus, Ladder, MatchOutcome, ParticipantId, Side};
+
+/// How many of the most recent results to show.
+const RECENT_RESULTS: usize = 10;
+
+/// A ladder laid out for display.
+pub struct LadderView {
+    pub name: String,
+    pub rungs: Vec<RungView>,
+    pub open: Vec<ChallengeView>,
+    pub recent: Vec<ChallengeView>,
+}
+
+pub struct RungView {
+    pub position: usize,
+    pub player: String,
+    /// The date the player last played, or joined.
+    pub active: String,
+    /// Whether the player is in an open challenge.
+    pub challenged: bool,
+}
+
+pub struct ChallengeView {
+    pub challenger: String,
+    pub defender: String,
+    /// Where the challenge is, or how it ended.
+    pub status: String,
+    pub deadline: String,
+}
+
+impl LadderView {
+    /// Lay out a ladder and its challenges using the names of its players.
+    pub fn new(
+        name: &str,
+        ladder: &Ladder,
+        challenges: &[Challenge],
+        names: &HashMap<ParticipantId, String>,
+    ) -> LadderView {
+        let name_of = |id: &ParticipantId| {
+            names
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| "Unknown".to_string())
+        };
+        let view = |challenge: &Challenge| ChallengeView {
+            challenger: name_of(&challenge.challenger),
+            defender: name_of(&challenge.defender),
+            status: status(challenge),
+            deadline: challenge.deadline.format("%d %b %Y").to_string(),
+        };
+
+        let rungs = ladder
+            .rungs
+            .iter()
+            .enumerate()
+            .map(|(index, rung)| RungView {
+                position: index + 1,
+                player: name_of(&rung.participant_id),
+                active: rung.active.format("%d %b %Y").to_string(),
+                challenged: challenges.iter().any(|challenge| {
+                    challenge.is_open() && challenge.involves(&rung.participant_id)
+                }),
+            })
+            .collect();
+
+        LadderView {
+            name: name.to_string(),
+            rungs,
+            open: challenges
+                .iter()
+                .filter(|challenge| challenge.is_open())
+                .map(view)
+                .collect(),
+            recent: challenges
+                .iter()
+                .rev()
+                .filter(|challenge| !challenge.is_open())
+                .take(RECENT_RESULTS)
+                .map(view)
+                .collect(),
+        }
+    }
+}
+
+/// Describe where a challenge is, or how it ended.
+fn status(challenge: &Challenge) -> String {
+    match (challenge.status, challenge.result) {
+        (ChallengeStatus::Issued, _) => "Awaiting a reply",
+        (ChallengeStatus::Accepted, _) => "To be played",
+        (ChallengeStatus::Declined, _) => "Declined",
+        (ChallengeStatus::Expired, _) => "Expired",
+        (ChallengeStatus::Played, Some(MatchOutcome::Win(Side::Home))) => "Challenger won",
+        (ChallengeStatus::Played, Some(MatchOutcome::Win(Side::Away))) => "Defender won",
+        (ChallengeStatus::Played, _) => "Drawn",
+    }
+    .to_string()
+}
@@ -1,4 +1,22 @@
+use leagus::models::RankedEntry;
+
 pub struct LeagueEntry {
+    pub position: usize,
     pub player: String,
-    pub score: i32,
+    pub played: u32,
+    pub score: u32,
+    /// Describes which tie-breaker decided the position, if any.
+    pub tie_break: Option<String>,
+}
+
+impl From<RankedEntry> for LeagueEntry {
+    fn from(ranked: RankedEntry) -> Self {
+        LeagueEntry {
+            position: ranked.position,
+            tie_break: ranked.explanation(),
+            played: ranked.entry.played(),
+            score: ranked.entry.points,
+            player: ranked.entry.participant_name,
+        }
+    }
 }
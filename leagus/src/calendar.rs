@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::models::{
+    League, Match, ParticipantId, Round, RoundId, Season, Session, SessionStatus, Venue,
+};
+
+/// How long a session, and each of its matches, is expected to last.
+pub fn default_duration() -> TimeDelta {
+    TimeDelta::hours(2)
+}
+
+/// A feed of sessions and matches which can be written as iCalendar.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Calendar {
+    pub name: String,
+    /// When the calendar was generated.
+    pub stamp: DateTime<Utc>,
+    pub events: Vec<Event>,
+}
+
+/// A session or match in a calendar.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Event {
+    /// Stays the same as the event changes, so calendar apps update rather
+    /// than duplicate it.
+    pub uid: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub cancelled: bool,
+}
+
+/// A season with its sessions, rounds and matches.
+#[derive(Debug, Clone, Copy)]
+pub struct SeasonSchedule<'a> {
+    pub league: &'a League,
+    pub season: &'a Season,
+    pub sessions: &'a [Session],
+    pub rounds: &'a [Round],
+    pub matches: &'a [Match],
+}
+
+impl Calendar {
+    /// Create a calendar with no events (yet).
+    pub fn new(name: &str, stamp: DateTime<Utc>) -> Calendar {
+        Calendar {
+            name: name.to_string(),
+            stamp,
+            events: Vec::new(),
+        }
+    }
+
+    /// Add every session and match of a season. Participants and venues are
+    /// named from those given.
+    pub fn add_season(
+        &mut self,
+        schedule: &SeasonSchedule,
+        names: &HashMap<ParticipantId, String>,
+        venues: &[Venue],
+    ) {
+        self.add(schedule, names, venues, None);
+    }
+
+    /// Add the sessions of a season a participant is playing in, and their
+    /// matches.
+    pub fn add_season_for(
+        &mut self,
+        schedule: &SeasonSchedule,
+        names: &HashMap<ParticipantId, String>,
+        venues: &[Venue],
+        participant_id: &ParticipantId,
+    ) {
+        self.add(schedule, names, venues, Some(participant_id));
+    }
+
+    fn add(
+        &mut self,
+        schedule: &SeasonSchedule,
+        names: &HashMap<ParticipantId, String>,
+        venues: &[Venue],
+        participant_id: Option<&ParticipantId>,
+    ) {
+        let title = format!("{} {}", schedule.league.name, schedule.season.name);
+        let title = title.trim();
+        let sessions: HashMap<RoundId, &Session> = schedule
+            .rounds
+            .iter()
+            .filter_map(|round| {
+                let session = schedule
+                    .sessions
+                    .iter()
+                    .find(|session| session.id == round.session_id)?;
+                Some((round.id, session))
+            })
+            .collect();
+        let matches: Vec<&Match> = schedule
+            .matches
+            .iter()
+            .filter(|a_match| participant_id.is_none_or(|id| a_match.details.side_of(id).is_some()))
+            .collect();
+
+        for session in schedule.sessions {
+            let taking_part = participant_id.is_none_or(|id| {
+                schedule
+                    .rounds
+                    .iter()
+                    .filter(|round| round.session_id == session.id)
+                    .any(|round| {
+                        round.participants.contains(id)
+                            || matches.iter().any(|a_match| a_match.round_id == round.id)
+                    })
+            });
+            if !taking_part {
+                continue;
+            }
+
+            self.events.push(Event {
+                uid: format!("{}@leagus", session.id),
+                start: session.date,
+                end: session.date + default_duration(),
+                summary: format!("{title} session"),
+                location: None,
+                description: None,
+                cancelled: session.status == SessionStatus::Cancelled,
+            });
+        }
+
+        for a_match in matches {
+            let Some(session) = sessions.get(&a_match.round_id) else {
+                continue;
+            };
+            let Some((home, away)) = a_match.details.sides() else {
+                continue;
+            };
+            let side = |ids: &[ParticipantId]| {
+                ids.iter()
+                    .map(|id| names.get(id).map(String::as_str).unwrap_or("TBC"))
+                    .collect::<Vec<_>>()
+                    .join(" & ")
+            };
+            let location = venues
+                .iter()
+                .find(|venue| venue.id == a_match.venue_id)
+                .map(|venue| match &a_match.court {
+                    Some(court) => format!("{}, {}", venue.name, court),
+                    None => venue.name.clone(),
+                });
+
+            self.events.push(Event {
+                uid: format!("{}@leagus", a_match.id),
+                start: session.date,
+                end: session.date + default_duration(),
+                summary: format!("{} v {}", side(&home), side(&away)),
+                location,
+                description: Some(title.to_string()),
+                cancelled: session.status == SessionStatus::Cancelled,
+            });
+        }
+
+        self.events.sort_by_key(|event| event.start);
+    }
+
+    /// Write the calendar in the iCalendar format (RFC 5545).
+    pub fn to_ics(&self) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//leagus//leagus//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            format!("X-WR-CALNAME:{}", escape(&self.name)),
+        ];
+        for event in &self.events {
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}", event.uid));
+            lines.push(format!("DTSTAMP:{}", timestamp(&self.stamp)));
+            lines.push(format!("DTSTART:{}", timestamp(&event.start)));
+            lines.push(format!("DTEND:{}", timestamp(&event.end)));
+            lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+            if let Some(location) = &event.location {
+                lines.push(format!("LOCATION:{}", escape(location)));
+            }
+            if let Some(description) = &event.description {
+                lines.push(format!("DESCRIPTION:{}", escape(description)));
+            }
+            if event.cancelled {
+                lines.push("STATUS:CANCELLED".to_string());
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        lines.iter().fold(String::new(), |mut ics, line| {
+            let _ = write!(ics, "{}\r\n", fold(line));
+            ics
+        })
+    }
+}
+
+/// A UTC date-time as iCalendar writes them.
+fn timestamp(at: &DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape the characters iCalendar gives a meaning to in text.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a line longer than 75 bytes onto continuation lines, which start with
+/// a space, without splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{MatchDetails, Participant, SessionId, Sport};
+
+    struct Fixture {
+        league: League,
+        season: Season,
+        sessions: Vec<Session>,
+        rounds: Vec<Round>,
+        matches: Vec<Match>,
+        venues: Vec<Venue>,
+        names: HashMap<ParticipantId, String>,
+        players: Vec<Participant>,
+    }
+
+    impl Fixture {
+        fn schedule(&self) -> SeasonSchedule<'_> {
+            SeasonSchedule {
+                league: &self.league,
+                season: &self.season,
+                sessions: &self.sessions,
+                rounds: &self.rounds,
+                matches: &self.matches,
+            }
+        }
+    }
+
+    /// Two Tuesday sessions, the second cancelled, with Ann playing Bob and
+    /// then Cat.
+    fn fixture() -> Fixture {
+        let league = League::new("Club Ladder", "", Sport::Chess);
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 19, 00, 00).unwrap();
+        let season = Season::new(&league.id, &start, &start, "Winter");
+        let players: Vec<Participant> = ["Ann", "Bob", "Cat"]
+            .iter()
+            .map(|name| Participant::new(name.to_string()))
+            .collect();
+        let venues = vec![Venue::new("Hall, upstairs".to_string())];
+
+        let mut sessions = vec![
+            Session::new(&season.id, &start),
+            Session::new(&season.id, &(start + TimeDelta::days(7))),
+        ];
+        sessions[0].id =
+            SessionId::from(bson::Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap());
+        sessions[1].id =
+            SessionId::from(bson::Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap());
+        sessions[1].cancel();
+        let rounds: Vec<Round> = sessions
+            .iter()
+            .map(|session| Round::new(session.id))
+            .collect();
+
+        let mut matches = Vec::new();
+        for (round, opponent) in rounds.iter().zip(&players[1..]) {
+            let mut a_match = Match::new(round.id, venues[0].id);
+            a_match.details = MatchDetails::Chess {
+                players: (players[0].id, opponent.id),
+                result: None,
+            };
+            matches.push(a_match);
+        }
+        matches[0].id = bson::Uuid::parse_str("22222222-2248-4345-80ec-b88499f9ff1e")
+            .unwrap()
+            .into();
+        matches[0].court = Some("Board 1".to_string());
+
+        let names = players
+            .iter()
+            .map(|player| (player.id, player.name.clone()))
+            .collect();
+
+        Fixture {
+            league,
+            season,
+            sessions,
+            rounds,
+            matches,
+            venues,
+            names,
+            players,
+        }
+    }
+
+    #[test]
+    fn write_sessions_and_matches_as_ics() {
+        let fixture = fixture();
+        let stamp = Utc.with_ymd_and_hms(2024, 1, 1, 9, 00, 00).unwrap();
+        let mut calendar = Calendar::new("Club Ladder", stamp);
+
+        calendar.add_season(&fixture.schedule(), &fixture.names, &fixture.venues);
+
+        assert_eq!(calendar.events.len(), 4);
+        let ics = calendar.to_ics();
+        let first_week = [
+            "BEGIN:VEVENT",
+            "UID:00000000-2248-4345-80ec-b88499f9ff1e@leagus",
+            "DTSTAMP:20240101T090000Z",
+            "DTSTART:20240102T190000Z",
+            "DTEND:20240102T210000Z",
+            "SUMMARY:Club Ladder Winter session",
+            "END:VEVENT",
+            "BEGIN:VEVENT",
+            "UID:22222222-2248-4345-80ec-b88499f9ff1e@leagus",
+            "DTSTAMP:20240101T090000Z",
+            "DTSTART:20240102T190000Z",
+            "DTEND:20240102T210000Z",
+            "SUMMARY:Ann v Bob",
+            "LOCATION:Hall\\, upstairs\\, Board 1",
+            "DESCRIPTION:Club Ladder Winter",
+            "END:VEVENT",
+        ]
+        .join("\r\n");
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.contains(&first_week), "{ics}");
+        assert_eq!(ics.matches("STATUS:CANCELLED").count(), 2);
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn only_include_a_participants_own_matches() {
+        let fixture = fixture();
+        let mut calendar = Calendar::new("Bob", Utc::now());
+
+        calendar.add_season_for(
+            &fixture.schedule(),
+            &fixture.names,
+            &fixture.venues,
+            &fixture.players[1].id,
+        );
+
+        let summaries: Vec<&str> = calendar
+            .events
+            .iter()
+            .map(|event| event.summary.as_str())
+            .collect();
+        assert_eq!(summaries, ["Club Ladder Winter session", "Ann v Bob"]);
+    }
+
+    #[test]
+    fn fold_long_lines() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold(&line);
+
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -1,5 +1,15 @@
+pub mod brackets;
+pub mod calendars;
 pub mod database;
+pub mod groups;
+pub mod handicaps;
+pub mod ladders;
 pub mod leagues;
+pub mod matches;
 pub mod participants;
+pub mod ratings;
 pub mod seasons;
 pub mod sessions;
+pub mod teams;
+pub mod ties;
+pub mod venues;
@@ -0,0 +1,162 @@
+use bson::Uuid;
+use chrono::{DateTime, Utc};
+use clap::{arg, value_parser, ArgMatches, Command};
+use leagus::fixtures::knockout::{seeds_from_ratings, seeds_from_standings, Knockout};
+use leagus::models::{BracketFormat, Match, ParticipantId, RatingSystem, SeasonId, VenueId};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+use leagus::ratings;
+
+pub const CMD_NAME: &str = "brackets";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing knockout brackets")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create")
+                .about("Draw a knockout bracket for a season and schedule its first matches")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season the bracket belongs to"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -v --venue <ID> "Id of the venue the matches are played at"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    -n --name <NAME> "Name of the bracket"
+                ))
+                .arg(arg!(
+                    -d --date <DATE> "Date the bracket is played"
+                ))
+                .arg(arg!(
+                    --double "Use double elimination rather than single"
+                ))
+                .arg(arg!(
+                    --"third-place" "Add a third-place play-off"
+                ))
+                .arg(arg!(
+                    --plate "Add a plate for those beaten in their first match"
+                ))
+                .arg(
+                    arg!(
+                        --"seed-by" <SOURCE> "Seed from the standings or a rating system"
+                    )
+                    .value_parser(["standings", "elo", "glicko2", "trueskill"])
+                    .default_value("standings"),
+                )
+                .arg(
+                    arg!(
+                        --top <COUNT> "Only include this many of the top seeds"
+                    )
+                    .value_parser(value_parser!(usize)),
+                ),
+        )
+}
+
+/// Delegate subcommands of the brackets command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("create", sub_matches)) => create(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Draw a bracket from the participants of a season
+fn create(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+    let venue_id = matches.get_one::<String>("venue").expect("required");
+    let venue_id = Uuid::parse_str(venue_id).expect("Invalid venue id");
+    let top = matches.get_one::<usize>("top").copied();
+
+    let mut store = MongoStore::new();
+    let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find league of season \"{}\".", season.name);
+        return;
+    };
+
+    // Brackets are drawn as singles, which not every sport is played as.
+    if !league.sport.has_singles() {
+        println!(
+            "Cannot draw bracket, {} isn't played as singles.",
+            league.sport
+        );
+        return;
+    }
+
+    // TODO: handle bad dates with more grace
+    let date = matches
+        .get_one::<String>("date")
+        .map(|date| date.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or_else(Utc::now);
+
+    let confirmed: Vec<Match> = store
+        .list_matches_for_season(&season.id)
+        .into_iter()
+        .filter(|a_match| a_match.confirmed)
+        .collect();
+    let standings = season.standings(&confirmed);
+    let rating_system = match matches.get_one::<String>("seed-by").map(String::as_str) {
+        Some("elo") => Some(RatingSystem::Elo),
+        Some("glicko2") => Some(RatingSystem::Glicko2),
+        Some("trueskill") => Some(RatingSystem::TrueSkill),
+        _ => None,
+    };
+    let seeds = match rating_system {
+        Some(system) => {
+            let participants: Vec<ParticipantId> = standings
+                .iter()
+                .map(|ranked| ranked.entry.participant_id)
+                .collect();
+            let skills = ratings::skills(&store.list_ratings(), system);
+            let mut seeds = seeds_from_ratings(&participants, &skills);
+            seeds.truncate(top.unwrap_or(seeds.len()));
+            seeds
+        }
+        None => seeds_from_standings(&standings, top),
+    };
+
+    let format = match matches.get_flag("double") {
+        true => BracketFormat::DoubleElimination,
+        false => BracketFormat::SingleElimination {
+            third_place: matches.get_flag("third-place"),
+            plate: matches.get_flag("plate"),
+        },
+    };
+    let knockout = Knockout {
+        name: matches
+            .get_one::<String>("name")
+            .cloned()
+            .unwrap_or_else(|| format!("{} Finals", season.name)),
+        format,
+        date,
+        venue_id: VenueId::from(venue_id),
+    };
+
+    match knockout.generate(&season, league.sport, seeds) {
+        Ok((bracket, fixtures)) => {
+            if let Err(error) = store.create_fixtures(&fixtures) {
+                println!("Cannot draw bracket, {error}.");
+                return;
+            }
+            store.create_bracket(&bracket);
+            println!(
+                "Created bracket \"{}\" ({}) with {} seeds and {} matches ready to play",
+                bracket.name,
+                bracket.id,
+                bracket.seeds.len(),
+                fixtures.matches.len()
+            );
+        }
+        Err(error) => println!("Cannot draw bracket, {error}."),
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use bson::Uuid;
+use chrono::Utc;
+use clap::{arg, ArgGroup, ArgMatches, Command};
+use leagus::calendar::{Calendar, SeasonSchedule};
+use leagus::models::{League, ParticipantId, Season, SeasonId};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+
+pub const CMD_NAME: &str = "calendars";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for exporting sessions and matches as iCalendar")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("export")
+                .about("Export a league, season or participant's calendar to a file")
+                .arg(arg!(
+                    -l --league <NAME> "Name of the league"
+                ))
+                .arg(arg!(
+                    -s --season <ID> "Id of the season"
+                ))
+                .arg(arg!(
+                    -p --participant <ID> "Id of the participant"
+                ))
+                .group(
+                    ArgGroup::new("feed")
+                        .args(["league", "season", "participant"])
+                        .required(true),
+                )
+                .arg(
+                    arg!(
+                        -o --output <FILE> "File to write the calendar to, e.g. \"league.ics\""
+                    )
+                    .required(true),
+                ),
+        )
+}
+
+/// Delegate subcommands of the calendars command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("export", sub_matches)) => export(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Write a calendar to a file
+fn export(matches: &ArgMatches) {
+    let output = matches.get_one::<String>("output").expect("required");
+    let store = MongoStore::new();
+
+    let calendar = if let Some(league_name) = matches.get_one::<String>("league") {
+        let Some(league) = store.get_league_by_name(league_name) else {
+            println!("Cannot find league with name \"{}\".", league_name);
+            return;
+        };
+        let mut calendar = Calendar::new(&league.name, Utc::now());
+        for season in store.list_seasons_for_league(&league.id) {
+            add_season(&store, &mut calendar, &league, &season, None);
+        }
+        calendar
+    } else if let Some(season_id) = matches.get_one::<String>("season") {
+        let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+        let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+            println!("Cannot find season with id \"{}\".", season_id);
+            return;
+        };
+        let Some(league) = store.get_league(&season.league_id) else {
+            println!("Cannot find league with id \"{}\".", season.league_id);
+            return;
+        };
+        let name = format!("{} {}", league.name, season.name);
+        let mut calendar = Calendar::new(name.trim(), Utc::now());
+        add_season(&store, &mut calendar, &league, &season, None);
+        calendar
+    } else {
+        let participant_id = matches.get_one::<String>("participant").expect("required");
+        let participant_id = Uuid::parse_str(participant_id).expect("Invalid participant id");
+        let participant_id = ParticipantId::from(participant_id);
+        let Some(participant) = store.get_participant(&participant_id) else {
+            println!("Cannot find participant with id \"{}\".", participant_id);
+            return;
+        };
+        let mut calendar = Calendar::new(&participant.name, Utc::now());
+        for league in store.list_leagues() {
+            for season in store.list_seasons_for_league(&league.id) {
+                add_season(
+                    &store,
+                    &mut calendar,
+                    &league,
+                    &season,
+                    Some(&participant.id),
+                );
+            }
+        }
+        calendar
+    };
+
+    match std::fs::write(output, calendar.to_ics()) {
+        Ok(()) => println!(
+            "Exported {} events to \"{}\"",
+            calendar.events.len(),
+            output
+        ),
+        Err(error) => println!("Cannot write to \"{}\": {}", output, error),
+    }
+}
+
+/// Load a season's schedule and add it to the calendar, only including a
+/// participant's sessions if one is given.
+fn add_season(
+    store: &MongoStore,
+    calendar: &mut Calendar,
+    league: &League,
+    season: &Season,
+    participant_id: Option<&ParticipantId>,
+) {
+    let sessions = store.list_sessions_for_season(&season.id);
+    let rounds = store.list_rounds_for_season(&season.id);
+    let matches = store.list_matches_for_season(&season.id);
+    let venues = store.list_venues();
+    let names: HashMap<ParticipantId, String> = store
+        .list_participants()
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+
+    let schedule = SeasonSchedule {
+        league,
+        season,
+        sessions: &sessions,
+        rounds: &rounds,
+        matches: &matches,
+    };
+    match participant_id {
+        Some(participant_id) => calendar.add_season_for(&schedule, &names, &venues, participant_id),
+        None => calendar.add_season(&schedule, &names, &venues),
+    }
+}
@@ -0,0 +1,296 @@
+use bson::Uuid;
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::{arg, value_parser, ArgMatches, Command};
+use leagus::fixtures::groups::Groups;
+use leagus::fixtures::knockout::Knockout;
+use leagus::fixtures::round_robin::{Legs, RoundRobin};
+use leagus::models::{
+    BracketFormat, GroupStageId, Match, Participant, Qualification, SeasonId, TieBreaker, VenueId,
+};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+
+pub const CMD_NAME: &str = "groups";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing group stages ahead of a knockout")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create")
+                .about("Draw the participants of a season into groups and schedule their matches")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season the groups belong to"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -v --venue <ID> "Id of the venue the matches are played at"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -g --groups <COUNT> "Number of groups"
+                    )
+                    .value_parser(value_parser!(usize))
+                    .required(true),
+                )
+                .arg(arg!(
+                    -n --name <NAME> "Name of the group stage"
+                ))
+                .arg(arg!(
+                    --start <DATE> "Date of the first session, defaults to the season start"
+                ))
+                .arg(
+                    arg!(
+                        --every <DAYS> "Days between sessions"
+                    )
+                    .value_parser(value_parser!(i64))
+                    .default_value("7"),
+                )
+                .arg(arg!(
+                    --double "Play everyone in the group twice, home and away"
+                ))
+                .arg(
+                    arg!(
+                        --qualify <COUNT> "Places in each group which go through"
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("2"),
+                )
+                .arg(
+                    arg!(
+                        --"best-of-next" <COUNT> "The best of the next placed who also go through"
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("0"),
+                ),
+        )
+        .subcommand(
+            Command::new("standings")
+                .about("Show the table of each group")
+                .arg(arg!(<ID> "Id of the group stage").required(true)),
+        )
+        .subcommand(
+            Command::new("knockout")
+                .about("Draw the qualifiers from the groups into a knockout bracket")
+                .arg(arg!(<ID> "Id of the group stage").required(true))
+                .arg(
+                    arg!(
+                        -v --venue <ID> "Id of the venue the matches are played at"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    -n --name <NAME> "Name of the bracket"
+                ))
+                .arg(arg!(
+                    -d --date <DATE> "Date the bracket is played"
+                ))
+                .arg(arg!(
+                    --"third-place" "Add a third-place play-off"
+                ))
+                .arg(arg!(
+                    --plate "Add a plate for those beaten in their first match"
+                )),
+        )
+}
+
+/// Delegate subcommands of the groups command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("create", sub_matches)) => create(sub_matches),
+        Some(("standings", sub_matches)) => standings(sub_matches),
+        Some(("knockout", sub_matches)) => knockout(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Draw the participants of a season into groups, seeded by the season's
+/// standings
+fn create(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+    let venue_id = matches.get_one::<String>("venue").expect("required");
+    let venue_id = Uuid::parse_str(venue_id).expect("Invalid venue id");
+    let every = *matches.get_one::<i64>("every").expect("defaulted");
+
+    let mut store = MongoStore::new();
+    let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find league of season \"{}\".", season.name);
+        return;
+    };
+
+    // TODO: handle bad dates with more grace
+    let start = matches
+        .get_one::<String>("start")
+        .map(|start| start.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(season.start);
+
+    let generator = Groups {
+        name: matches
+            .get_one::<String>("name")
+            .cloned()
+            .unwrap_or_else(|| format!("{} Groups", season.name)),
+        count: *matches.get_one::<usize>("groups").expect("required"),
+        round_robin: RoundRobin {
+            legs: match matches.get_flag("double") {
+                true => Legs::Double,
+                false => Legs::Single,
+            },
+            start,
+            interval: TimeDelta::days(every),
+            venue_id: VenueId::from(venue_id),
+        },
+        tie_breakers: match season.tie_breakers.is_empty() {
+            true => vec![TieBreaker::HeadToHead],
+            false => season.tie_breakers.clone(),
+        },
+        qualification: Qualification {
+            per_group: *matches.get_one::<usize>("qualify").expect("defaulted"),
+            best_of_next: *matches.get_one::<usize>("best-of-next").expect("defaulted"),
+        },
+    };
+    let confirmed: Vec<Match> = store
+        .list_matches_for_season(&season.id)
+        .into_iter()
+        .filter(|a_match| a_match.confirmed)
+        .collect();
+    let seeds: Vec<Participant> = season
+        .standings(&confirmed)
+        .into_iter()
+        .map(|ranked| Participant {
+            id: ranked.entry.participant_id,
+            name: ranked.entry.participant_name,
+            team: None,
+        })
+        .collect();
+
+    match generator.generate(&season, league.sport, &seeds) {
+        Ok((group_stage, fixtures)) => {
+            if let Err(error) = store.create_fixtures(&fixtures) {
+                println!("Cannot draw groups, {error}.");
+                return;
+            }
+            store.create_group_stage(&group_stage);
+            println!(
+                "Created \"{}\" ({}) with {} groups and {} matches over {} sessions",
+                group_stage.name,
+                group_stage.id,
+                group_stage.groups.len(),
+                fixtures.matches.len(),
+                fixtures.sessions.len()
+            );
+        }
+        Err(error) => println!("Cannot draw groups, {error}."),
+    }
+}
+
+/// Print the ranked table of each group
+fn standings(matches: &ArgMatches) {
+    let group_stage_id = matches.get_one::<String>("ID").expect("required");
+    let group_stage_id = Uuid::parse_str(group_stage_id).expect("Invalid group stage id");
+
+    let store = MongoStore::new();
+    let Some(group_stage) = store.get_group_stage(&GroupStageId::from(group_stage_id)) else {
+        println!("Cannot find group stage with id \"{}\".", group_stage_id);
+        return;
+    };
+    let Some(season) = store.get_season(&group_stage.season_id) else {
+        println!("Cannot find season of \"{}\".", group_stage.name);
+        return;
+    };
+    let season_matches = store.list_matches_for_season(&season.id);
+
+    for group in &group_stage.groups {
+        println!("{}", group.name);
+        for ranked in group.standings(&season.scoring, &season_matches) {
+            let entry = &ranked.entry;
+            println!(
+                "  {}. {} - {} pts (P{} W{} D{} L{})",
+                ranked.position,
+                entry.participant_name,
+                entry.points,
+                entry.played(),
+                entry.wins,
+                entry.draws,
+                entry.losses
+            );
+        }
+    }
+}
+
+/// Draw the knockout the group qualifiers go through to
+fn knockout(matches: &ArgMatches) {
+    let group_stage_id = matches.get_one::<String>("ID").expect("required");
+    let group_stage_id = Uuid::parse_str(group_stage_id).expect("Invalid group stage id");
+    let venue_id = matches.get_one::<String>("venue").expect("required");
+    let venue_id = Uuid::parse_str(venue_id).expect("Invalid venue id");
+
+    let mut store = MongoStore::new();
+    let Some(mut group_stage) = store.get_group_stage(&GroupStageId::from(group_stage_id)) else {
+        println!("Cannot find group stage with id \"{}\".", group_stage_id);
+        return;
+    };
+    if group_stage.bracket_id.is_some() {
+        println!(
+            "The knockout of \"{}\" has already been drawn.",
+            group_stage.name
+        );
+        return;
+    }
+    let Some(season) = store.get_season(&group_stage.season_id) else {
+        println!("Cannot find season of \"{}\".", group_stage.name);
+        return;
+    };
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find league of season \"{}\".", season.name);
+        return;
+    };
+
+    // TODO: handle bad dates with more grace
+    let date = matches
+        .get_one::<String>("date")
+        .map(|date| date.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or_else(Utc::now);
+
+    let knockout = Knockout {
+        name: matches
+            .get_one::<String>("name")
+            .cloned()
+            .unwrap_or_else(|| format!("{} Knockout", season.name)),
+        format: BracketFormat::SingleElimination {
+            third_place: matches.get_flag("third-place"),
+            plate: matches.get_flag("plate"),
+        },
+        date,
+        venue_id: VenueId::from(venue_id),
+    };
+    let seeds = group_stage.qualifiers(&season.scoring, &store.list_matches_for_season(&season.id));
+
+    match knockout.generate(&season, league.sport, seeds) {
+        Ok((bracket, fixtures)) => {
+            if let Err(error) = store.create_fixtures(&fixtures) {
+                println!("Cannot draw knockout, {error}.");
+                return;
+            }
+            store.create_bracket(&bracket);
+            group_stage.bracket_id = Some(bracket.id);
+            store.update_group_stage(&group_stage);
+            println!(
+                "Created bracket \"{}\" ({}) with {} qualifiers and {} matches ready to play",
+                bracket.name,
+                bracket.id,
+                bracket.seeds.len(),
+                fixtures.matches.len()
+            );
+        }
+        Err(error) => println!("Cannot draw knockout, {error}."),
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+
+use bson::Uuid;
+use clap::{arg, value_parser, ArgMatches, Command};
+use leagus::models::{
+    HandicapRules, Handicaps, Match, ParticipantId, RatingSystem, Season, SeasonId, Session,
+    SessionId,
+};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+use leagus::ratings;
+
+pub const CMD_NAME: &str = "handicaps";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing the handicap starts of a season")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("enable")
+                .about("Give a season handicaps, deriving everyone's start from their ratings")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --"rating-per-start" <POINTS> "Rating points one game, or point, of start is worth"
+                    )
+                    .value_parser(value_parser!(f64))
+                    .default_value("50"),
+                )
+                .arg(
+                    arg!(
+                        --"max-start" <START> "The largest start anyone is given"
+                    )
+                    .value_parser(value_parser!(u8))
+                    .default_value("10"),
+                )
+                .arg(
+                    arg!(
+                        --system <SYSTEM> "The rating system starts are derived from"
+                    )
+                    .value_parser(["elo", "glicko2", "trueskill"])
+                    .default_value("elo"),
+                ),
+        )
+        .subcommand(
+            Command::new("derive")
+                .about("Derive the starts again from the current ratings")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Set a participant's start by hand")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --start <START> "Games, or points, they start each set with"
+                    )
+                    .value_parser(value_parser!(u8))
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("clear")
+                .about("Clear a participant's start so it is derived again")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("scoresheet")
+                .about("Print a session's scoresheet, fixing the starts of its matches")
+                .arg(arg!(<ID> "Id of the session").required(true)),
+        )
+        .subcommand(
+            Command::new("adjust")
+                .about("Adjust the starts from the handicapped results of a session")
+                .arg(arg!(<ID> "Id of the session").required(true)),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List the starts of a season")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                ),
+        )
+}
+
+/// Delegate subcommands of the handicaps command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("enable", sub_matches)) => enable(sub_matches),
+        Some(("derive", sub_matches)) => derive(sub_matches),
+        Some(("set", sub_matches)) => set(sub_matches),
+        Some(("clear", sub_matches)) => clear(sub_matches),
+        Some(("scoresheet", sub_matches)) => scoresheet(sub_matches),
+        Some(("adjust", sub_matches)) => adjust(sub_matches),
+        Some(("list", sub_matches)) => list(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Get the season with the id given
+fn get_season(store: &MongoStore, matches: &ArgMatches) -> Option<Season> {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+
+    let season = store.get_season(&SeasonId::from(season_id));
+    if season.is_none() {
+        println!("Cannot find season with id \"{}\".", season_id);
+    }
+    season
+}
+
+/// Get the participant id given
+fn get_participant(matches: &ArgMatches) -> ParticipantId {
+    let participant_id = matches.get_one::<String>("participant").expect("required");
+    let participant_id = Uuid::parse_str(participant_id).expect("Invalid participant id");
+    ParticipantId::from(participant_id)
+}
+
+/// Get the handicaps of a season, if it has them
+fn handicaps_of(season: &mut Season) -> Option<&mut Handicaps> {
+    if season.handicaps.is_none() {
+        println!("Season {} doesn't have handicaps.", season.name);
+    }
+    season.handicaps.as_mut()
+}
+
+/// Derive the starts of the season's participants from their ratings
+fn derive_starts(store: &MongoStore, season: &mut Season) {
+    let participants = season.participants();
+    let Some(handicaps) = season.handicaps.as_mut() else {
+        return;
+    };
+    let skills = ratings::skills(&store.list_ratings(), handicaps.rules.system);
+    handicaps.derive(&participants, &skills);
+}
+
+/// Give a season handicaps
+fn enable(matches: &ArgMatches) {
+    let system = match matches.get_one::<String>("system").map(String::as_str) {
+        Some("glicko2") => RatingSystem::Glicko2,
+        Some("trueskill") => RatingSystem::TrueSkill,
+        _ => RatingSystem::Elo,
+    };
+    let rules = HandicapRules {
+        rating_per_start: *matches.get_one::<f64>("rating-per-start").expect("default"),
+        max_start: *matches.get_one::<u8>("max-start").expect("default"),
+        system,
+    };
+
+    let mut store = MongoStore::new();
+    let Some(mut season) = get_season(&store, matches) else {
+        return;
+    };
+    season.handicaps = Some(Handicaps::new(rules));
+    derive_starts(&store, &mut season);
+    match store.update_season(&season) {
+        Ok(()) => println!("Enabled handicaps for {}", season.name),
+        Err(error) => println!("Cannot enable handicaps, {error}."),
+    }
+}
+
+/// Derive the starts of a season again
+fn derive(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some(mut season) = get_season(&store, matches) else {
+        return;
+    };
+    if handicaps_of(&mut season).is_none() {
+        return;
+    }
+    derive_starts(&store, &mut season);
+    match store.update_season(&season) {
+        Ok(()) => println!("Derived the starts of {}", season.name),
+        Err(error) => println!("Cannot derive the starts, {error}."),
+    }
+}
+
+/// Set a participant's start by hand
+fn set(matches: &ArgMatches) {
+    let participant_id = get_participant(matches);
+    let start = *matches.get_one::<u8>("start").expect("required");
+
+    let mut store = MongoStore::new();
+    let Some(mut season) = get_season(&store, matches) else {
+        return;
+    };
+    let Some(handicaps) = handicaps_of(&mut season) else {
+        return;
+    };
+    handicaps.set(&participant_id, start);
+    match store.update_season(&season) {
+        Ok(()) => println!("Set the start of {} to {}", participant_id, start),
+        Err(error) => println!("Cannot set the start, {error}."),
+    }
+}
+
+/// Clear a participant's start
+fn clear(matches: &ArgMatches) {
+    let participant_id = get_participant(matches);
+
+    let mut store = MongoStore::new();
+    let Some(mut season) = get_season(&store, matches) else {
+        return;
+    };
+    let Some(handicaps) = handicaps_of(&mut season) else {
+        return;
+    };
+    if !handicaps.clear(&participant_id) {
+        println!("Participant \"{}\" doesn't have a start.", participant_id);
+        return;
+    }
+    match store.update_season(&season) {
+        Ok(()) => println!("Cleared the start of {}", participant_id),
+        Err(error) => println!("Cannot clear the start, {error}."),
+    }
+}
+
+/// Get the session with the id given, along with its matches
+fn get_session(store: &MongoStore, matches: &ArgMatches) -> Option<(Session, Vec<Match>)> {
+    let session_id = matches.get_one::<String>("ID").expect("required");
+    let session_id = Uuid::parse_str(session_id).expect("Invalid session id");
+
+    let Some(session) = store.get_session(&SessionId::from(session_id)) else {
+        println!("Cannot find session with id \"{}\".", session_id);
+        return None;
+    };
+    let rounds: Vec<_> = store
+        .list_rounds_for_season(&session.season_id)
+        .into_iter()
+        .filter(|round| round.session_id == session.id)
+        .map(|round| round.id)
+        .collect();
+    let session_matches = store
+        .list_matches_for_season(&session.season_id)
+        .into_iter()
+        .filter(|a_match| rounds.contains(&a_match.round_id))
+        .collect();
+    Some((session, session_matches))
+}
+
+/// Print the scoresheet of a session, fixing each match's starts
+fn scoresheet(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some((session, mut session_matches)) = get_session(&store, matches) else {
+        return;
+    };
+    let Some(season) = store.get_season(&session.season_id) else {
+        println!("Cannot find season with id \"{}\".", session.season_id);
+        return;
+    };
+    let names: HashMap<ParticipantId, String> = store
+        .list_participants()
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+    let side_names = |side: &[ParticipantId]| {
+        side.iter()
+            .map(|id| names.get(id).cloned().unwrap_or_else(|| id.to_string()))
+            .collect::<Vec<_>>()
+            .join(" & ")
+    };
+
+    println!("Scoresheet for {} on {}:", season.name, session.date);
+    for a_match in session_matches.iter_mut() {
+        let Some((home, away)) = a_match.details.sides() else {
+            continue;
+        };
+        if a_match.starts.is_none() {
+            if let Some(handicaps) = &season.handicaps {
+                a_match.starts = handicaps.starts(&a_match.details);
+                if let Err(error) = store.update_match(a_match) {
+                    println!("Cannot fix the starts, {error}.");
+                    return;
+                }
+            }
+        }
+        let (home_start, away_start) = a_match.starts.unwrap_or_default();
+        let score = a_match
+            .scored_details()
+            .score_totals()
+            .map(|(home, away)| format!(" {}-{}", home, away))
+            .unwrap_or_default();
+        println!(
+            "\t- {} (+{}) v {} (+{}){}",
+            side_names(&home),
+            home_start,
+            side_names(&away),
+            away_start,
+            score
+        );
+    }
+}
+
+/// Adjust the starts of a season after one of its sessions
+fn adjust(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some((session, session_matches)) = get_session(&store, matches) else {
+        return;
+    };
+    let Some(mut season) = store.get_season(&session.season_id) else {
+        println!("Cannot find season with id \"{}\".", session.season_id);
+        return;
+    };
+    let Some(handicaps) = handicaps_of(&mut season) else {
+        return;
+    };
+    if handicaps.adjusted.contains(&session.id) {
+        println!(
+            "The starts were already adjusted after session {}.",
+            session.id
+        );
+        return;
+    }
+
+    let changes = handicaps.adjust(&session.id, &session_matches);
+    if let Err(error) = store.update_season(&season) {
+        println!("Cannot adjust the starts, {error}.");
+        return;
+    }
+    println!(
+        "Adjusted {} starts after session {}",
+        changes.len(),
+        session.id
+    );
+    for change in changes {
+        println!(
+            "\t- {}: {} -> {}",
+            change.participant_id, change.before, change.after
+        );
+    }
+}
+
+/// List the starts of a season
+fn list(matches: &ArgMatches) {
+    let store = MongoStore::new();
+    let Some(mut season) = get_season(&store, matches) else {
+        return;
+    };
+    let names: HashMap<ParticipantId, String> = store
+        .list_participants()
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+    let Some(handicaps) = handicaps_of(&mut season) else {
+        return;
+    };
+
+    handicaps.starts.sort_by_key(|handicap| handicap.start);
+    for handicap in &handicaps.starts {
+        let name = names
+            .get(&handicap.participant_id)
+            .cloned()
+            .unwrap_or_else(|| handicap.participant_id.to_string());
+        let manual = if handicap.manual {
+            " (set by hand)"
+        } else {
+            ""
+        };
+        println!("\t- {}: +{}{}", name, handicap.start, manual);
+    }
+}
@@ -0,0 +1,384 @@
+use bson::Uuid;
+use chrono::Utc;
+use clap::{arg, value_parser, ArgMatches, Command};
+use leagus::models::{
+    ChallengeId, Ladder, LadderRules, League, LeagueFormat, MatchOutcome, ParticipantId, Side,
+};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+
+pub const CMD_NAME: &str = "ladders";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing ladder leagues")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("start")
+                .about("Play a league as a ladder of challenges")
+                .arg(
+                    arg!(
+                        -l --league <NAME> "Name of the league"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --reach <RUNGS> "How many rungs above themselves a player may challenge"
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("3"),
+                )
+                .arg(
+                    arg!(
+                        --"response-days" <DAYS> "Days to accept or decline a challenge"
+                    )
+                    .value_parser(value_parser!(u32))
+                    .default_value("7"),
+                )
+                .arg(
+                    arg!(
+                        --"play-days" <DAYS> "Days to play an accepted challenge"
+                    )
+                    .value_parser(value_parser!(u32))
+                    .default_value("14"),
+                )
+                .arg(
+                    arg!(
+                        --"inactivity-days" <DAYS> "Days without playing before dropping down"
+                    )
+                    .value_parser(value_parser!(u32))
+                    .default_value("30"),
+                )
+                .arg(
+                    arg!(
+                        --"inactivity-drop" <RUNGS> "Rungs dropped for being inactive"
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("1"),
+                ),
+        )
+        .subcommand(
+            Command::new("join")
+                .about("Add a participant to the bottom of a ladder")
+                .arg(
+                    arg!(
+                        -l --league <NAME> "Name of the league"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("challenge")
+                .about("Challenge a player further up the ladder")
+                .arg(
+                    arg!(
+                        -l --league <NAME> "Name of the league"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -c --challenger <ID> "Id of the participant issuing the challenge"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -d --defender <ID> "Id of the participant being challenged"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("accept")
+                .about("Accept a challenge")
+                .arg(arg!(<ID> "Id of the challenge").required(true)),
+        )
+        .subcommand(
+            Command::new("decline")
+                .about("Decline a challenge")
+                .arg(arg!(<ID> "Id of the challenge").required(true)),
+        )
+        .subcommand(
+            Command::new("result")
+                .about("Record the result of an accepted challenge")
+                .arg(arg!(<ID> "Id of the challenge").required(true))
+                .arg(
+                    arg!(
+                        -w --winner <WINNER> "Who won the challenge"
+                    )
+                    .value_parser(["challenger", "defender", "draw"])
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("tidy")
+                .about("Expire challenges past their deadline and drop inactive players")
+                .arg(
+                    arg!(
+                        -l --league <NAME> "Name of the league"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show the rungs and open challenges of a ladder")
+                .arg(
+                    arg!(
+                        -l --league <NAME> "Name of the league"
+                    )
+                    .required(true),
+                ),
+        )
+}
+
+/// Delegate subcommands of the ladders command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("start", sub_matches)) => start(sub_matches),
+        Some(("join", sub_matches)) => join(sub_matches),
+        Some(("challenge", sub_matches)) => challenge(sub_matches),
+        Some(("accept", sub_matches)) => respond(sub_matches, true),
+        Some(("decline", sub_matches)) => respond(sub_matches, false),
+        Some(("result", sub_matches)) => result(sub_matches),
+        Some(("tidy", sub_matches)) => tidy(sub_matches),
+        Some(("show", sub_matches)) => show(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Get the league played as a ladder with the given name
+fn get_ladder_league(store: &MongoStore, matches: &ArgMatches) -> Option<League> {
+    let league_name = matches.get_one::<String>("league").expect("required");
+    let Some(league) = store.get_league_by_name(league_name) else {
+        println!("Cannot find league with name \"{}\".", league_name);
+        return None;
+    };
+    if league.ladder().is_none() {
+        println!("League \"{}\" is not played as a ladder.", league.name);
+        return None;
+    }
+    Some(league)
+}
+
+/// Parse a participant id argument
+fn participant_id(matches: &ArgMatches, name: &str) -> ParticipantId {
+    let id = matches.get_one::<String>(name).expect("required");
+    ParticipantId::from(Uuid::parse_str(id).expect("Invalid participant id"))
+}
+
+/// Switch a league to a ladder
+fn start(matches: &ArgMatches) {
+    let league_name = matches.get_one::<String>("league").expect("required");
+
+    let mut store = MongoStore::new();
+    let Some(mut league) = store.get_league_by_name(league_name) else {
+        println!("Cannot find league with name \"{}\".", league_name);
+        return;
+    };
+    if league.ladder().is_some() {
+        println!("League \"{}\" is already a ladder.", league.name);
+        return;
+    }
+
+    let rules = LadderRules {
+        reach: *matches.get_one::<usize>("reach").expect("defaulted"),
+        response_days: *matches.get_one::<u32>("response-days").expect("defaulted"),
+        play_days: *matches.get_one::<u32>("play-days").expect("defaulted"),
+        inactivity_days: *matches
+            .get_one::<u32>("inactivity-days")
+            .expect("defaulted"),
+        inactivity_drop: *matches
+            .get_one::<usize>("inactivity-drop")
+            .expect("defaulted"),
+    };
+    league.format = LeagueFormat::Ladder(Ladder::new(rules));
+    store.update_league(&league);
+    println!("League \"{}\" is now played as a ladder", league.name);
+}
+
+/// Add a participant to a ladder
+fn join(matches: &ArgMatches) {
+    let participant_id = participant_id(matches, "participant");
+
+    let mut store = MongoStore::new();
+    let Some(mut league) = get_ladder_league(&store, matches) else {
+        return;
+    };
+    let ladder = league.ladder_mut().expect("checked");
+
+    match ladder.join(&participant_id, Utc::now()) {
+        Ok(()) => {
+            let rung = ladder.rungs.len();
+            store.update_league(&league);
+            println!("Participant {} joined on rung {}", participant_id, rung);
+        }
+        Err(error) => println!("Cannot join the ladder, {error}."),
+    }
+}
+
+/// Issue a challenge
+fn challenge(matches: &ArgMatches) {
+    let challenger = participant_id(matches, "challenger");
+    let defender = participant_id(matches, "defender");
+
+    let mut store = MongoStore::new();
+    let Some(league) = get_ladder_league(&store, matches) else {
+        return;
+    };
+    let ladder = league.ladder().expect("checked");
+    let open = store.list_challenges_for_league(&league.id);
+
+    match ladder.challenge(&league.id, &challenger, &defender, &open, Utc::now()) {
+        Ok(challenge) => {
+            store.create_challenge(&challenge);
+            println!(
+                "Issued challenge {}, to be answered by {}",
+                challenge.id, challenge.deadline
+            );
+        }
+        Err(error) => println!("Cannot issue the challenge, {error}."),
+    }
+}
+
+/// Accept or decline a challenge
+fn respond(matches: &ArgMatches, accept: bool) {
+    let challenge_id = matches.get_one::<String>("ID").expect("required");
+    let challenge_id = Uuid::parse_str(challenge_id).expect("Invalid challenge id");
+
+    let mut store = MongoStore::new();
+    let Some(mut challenge) = store.get_challenge(&ChallengeId::from(challenge_id)) else {
+        println!("Cannot find challenge with id \"{}\".", challenge_id);
+        return;
+    };
+    let Some(ladder) = store
+        .get_league(&challenge.league_id)
+        .and_then(|league| league.ladder().cloned())
+    else {
+        println!("Cannot find the ladder of challenge \"{}\".", challenge_id);
+        return;
+    };
+
+    let now = Utc::now();
+    let result = match accept {
+        true => challenge.accept(&ladder.rules, now),
+        false => challenge.decline(now),
+    };
+    match result {
+        Ok(()) => {
+            store.update_challenge(&challenge);
+            match accept {
+                true => println!("Accepted, to be played by {}", challenge.deadline),
+                false => println!("Declined"),
+            }
+        }
+        Err(error) => println!("Cannot answer the challenge, {error}."),
+    }
+}
+
+/// Record the result of a challenge
+fn result(matches: &ArgMatches) {
+    let challenge_id = matches.get_one::<String>("ID").expect("required");
+    let challenge_id = Uuid::parse_str(challenge_id).expect("Invalid challenge id");
+    let outcome = match matches.get_one::<String>("winner").map(String::as_str) {
+        Some("challenger") => MatchOutcome::Win(Side::Home),
+        Some("defender") => MatchOutcome::Win(Side::Away),
+        _ => MatchOutcome::Draw,
+    };
+
+    let mut store = MongoStore::new();
+    let Some(mut challenge) = store.get_challenge(&ChallengeId::from(challenge_id)) else {
+        println!("Cannot find challenge with id \"{}\".", challenge_id);
+        return;
+    };
+    let Some(mut league) = store.get_league(&challenge.league_id) else {
+        println!("Cannot find the league of challenge \"{}\".", challenge_id);
+        return;
+    };
+    let Some(ladder) = league.ladder_mut() else {
+        println!("League \"{}\" is not played as a ladder.", league.name);
+        return;
+    };
+
+    match ladder.settle(&mut challenge, outcome, Utc::now()) {
+        Ok(()) => {
+            let rung = ladder.position(&challenge.challenger);
+            store.update_challenge(&challenge);
+            store.update_league(&league);
+            if let Some(rung) = rung {
+                println!("Recorded, the challenger is on rung {}", rung);
+            }
+        }
+        Err(error) => println!("Cannot record the result, {error}."),
+    }
+}
+
+/// Expire challenges past their deadline and drop inactive players
+fn tidy(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some(mut league) = get_ladder_league(&store, matches) else {
+        return;
+    };
+    let mut challenges = store.list_challenges_for_league(&league.id);
+    let ladder = league.ladder_mut().expect("checked");
+    let now = Utc::now();
+
+    let expired: Vec<_> = ladder
+        .expire(&mut challenges, now)
+        .into_iter()
+        .cloned()
+        .collect();
+    let dropped = ladder.penalise_inactive(now);
+
+    for challenge in &expired {
+        store.update_challenge(challenge);
+    }
+    store.update_league(&league);
+    println!(
+        "Expired {} challenges and dropped {} inactive players",
+        expired.len(),
+        dropped.len()
+    );
+}
+
+/// Print the rungs and open challenges of a ladder
+fn show(matches: &ArgMatches) {
+    let store = MongoStore::new();
+    let Some(league) = get_ladder_league(&store, matches) else {
+        return;
+    };
+    let ladder = league.ladder().expect("checked");
+
+    println!("{}:", league.name);
+    for (index, rung) in ladder.rungs.iter().enumerate() {
+        println!(
+            "  {}. {} (active {})",
+            index + 1,
+            rung.participant_id,
+            rung.active
+        );
+    }
+
+    println!("Open challenges:");
+    for challenge in store
+        .list_challenges_for_league(&league.id)
+        .iter()
+        .filter(|challenge| challenge.is_open())
+    {
+        println!(
+            "- {} challenges {}, {:?} until {}\n\tid: {}",
+            challenge.challenger,
+            challenge.defender,
+            challenge.status,
+            challenge.deadline,
+            challenge.id
+        );
+    }
+}
@@ -1,5 +1,5 @@
-use clap::{arg, ArgMatches, Command};
-use leagus::models::League;
+use clap::{arg, value_parser, ArgMatches, Command};
+use leagus::models::{Division, League, Sport};
 use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
 
 pub const CMD_NAME: &str = "leagues";
@@ -19,7 +19,37 @@ pub fn commands() -> Command {
                 )
                 .arg(arg!(
                     -d --description <DESCRIPTION> "Description of the new league"
-                )),
+                ))
+                .arg(
+                    arg!(
+                        -s --sport <SPORT> "Sport played in the new league"
+                    )
+                    .value_parser(clap::value_parser!(Sport))
+                    .default_value("tennis"),
+                ),
+        )
+        .subcommand(
+            Command::new("add-division")
+                .about("Add a division below the existing divisions of a league")
+                .arg(
+                    arg!(
+                        -l --league <NAME> "Name of the league"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -d --division <NAME> "Name of the new division"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --exchange <COUNT> "Number relegated to, and promoted from, the division below"
+                    )
+                    .value_parser(value_parser!(usize))
+                    .default_value("2"),
+                ),
         )
         .subcommand(
             Command::new("list")
@@ -34,6 +64,7 @@ pub fn commands() -> Command {
 pub fn handle_subcommands(matches: &ArgMatches) {
     match matches.subcommand() {
         Some(("create", sub_matches)) => create(sub_matches),
+        Some(("add-division", sub_matches)) => add_division(sub_matches),
         Some(("list", sub_matches)) => list(sub_matches),
         _ => unreachable!("Must specify a subcommand"),
     }
@@ -48,11 +79,48 @@ fn create(matches: &ArgMatches) {
         .get_one::<String>("description")
         .unwrap_or(&default_description);
 
-    let league = League::new(name, description);
+    let sport = matches.get_one::<Sport>("sport").expect("defaulted");
+
+    let league = League::new(name, description, *sport);
 
     let mut store = MongoStore::new();
     store.create_league(league);
-    println!("Created new league: \"{}\"", name);
+    println!("Created new {} league: \"{}\"", sport, name);
+}
+
+/// Add a division to the bottom of a league
+fn add_division(matches: &ArgMatches) {
+    let league_name = matches.get_one::<String>("league").expect("required");
+    let division_name = matches.get_one::<String>("division").expect("required");
+    let exchange = *matches.get_one::<usize>("exchange").expect("defaulted");
+
+    let mut store = MongoStore::new();
+    let Some(mut league) = store.get_league_by_name(league_name) else {
+        println!("Cannot find league with name \"{}\".", league_name);
+        return;
+    };
+    if league
+        .divisions
+        .iter()
+        .any(|division| division.name == *division_name)
+    {
+        println!(
+            "League \"{}\" already has a division named \"{}\".",
+            league.name, division_name
+        );
+        return;
+    }
+
+    league
+        .divisions
+        .push(Division::new(division_name, exchange));
+    store.update_league(&league);
+    println!(
+        "Added division \"{}\" to {}, tier {}",
+        division_name,
+        league.name,
+        league.divisions.len()
+    );
 }
 
 /// List all leagues
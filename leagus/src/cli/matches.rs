@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+use bson::Uuid;
+use clap::{arg, ArgGroup, ArgMatches, Command};
+use leagus::models::{Match, MatchDetails, MatchId, MatchOutcome, Side, Sport};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+
+pub const CMD_NAME: &str = "matches";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing the results of matches")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("result")
+                .about("Record the result of a match, checked against the league's sport")
+                .arg(arg!(<ID> "Id of the match").required(true))
+                .arg(arg!(
+                    --sets <SCORES> "Score of each set from the home side, e.g. \"6-4,3-6,7-5\""
+                ))
+                .arg(arg!(
+                    --score <SCORE> "Score from the home side, e.g. \"2-1\""
+                ))
+                .arg(
+                    arg!(
+                        --result <RESULT> "The result of a match without scores"
+                    )
+                    .value_parser(["home", "away", "draw"]),
+                )
+                .group(
+                    ArgGroup::new("outcome")
+                        .args(["sets", "score", "result"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("confirm")
+                .about("Confirm the result of a match, so it counts towards ratings")
+                .arg(arg!(<ID> "Id of the match").required(true)),
+        )
+}
+
+/// Delegate subcommands of the matches command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("result", sub_matches)) => result(sub_matches),
+        Some(("confirm", sub_matches)) => confirm(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Get the match with the id given
+fn get_match(store: &MongoStore, matches: &ArgMatches) -> Option<Match> {
+    let match_id = matches.get_one::<String>("ID").expect("required");
+    let match_id = Uuid::parse_str(match_id).expect("Invalid match id");
+
+    let a_match = store.get_match(&MatchId::from(match_id));
+    if a_match.is_none() {
+        println!("Cannot find match with id \"{}\".", match_id);
+    }
+    a_match
+}
+
+/// Find the sport of the league a match is played in
+fn get_sport(store: &MongoStore, a_match: &Match) -> Option<Sport> {
+    store
+        .get_round(&a_match.round_id)
+        .and_then(|round| store.get_session(&round.session_id))
+        .and_then(|session| store.get_season(&session.season_id))
+        .and_then(|season| store.get_league(&season.league_id))
+        .map(|league| league.sport)
+}
+
+/// Parse a score from the home side such as "6-4"
+fn parse_score<T: FromStr>(score: &str) -> Option<(T, T)> {
+    let (home, away) = score.trim().split_once('-')?;
+    Some((home.trim().parse().ok()?, away.trim().parse().ok()?))
+}
+
+/// Fill in the scores or result given into the details of a match
+fn fill_details(matches: &ArgMatches, details: &mut MatchDetails) -> Result<(), String> {
+    if let Some(sets) = matches.get_one::<String>("sets") {
+        let scores = sets
+            .split(',')
+            .map(parse_score)
+            .collect::<Option<Vec<(u8, u8)>>>()
+            .ok_or(format!("cannot read the set scores \"{sets}\""))?;
+        let set_scores = details
+            .set_scores_mut()
+            .ok_or("the match isn't scored in sets")?;
+        *set_scores = scores;
+    } else if let Some(score) = matches.get_one::<String>("score") {
+        let parsed = parse_score(score).ok_or(format!("cannot read the score \"{score}\""))?;
+        match details {
+            MatchDetails::HeadToHeadScore { score, .. } => *score = Some(parsed),
+            _ => return Err("the match isn't decided by a single score".to_string()),
+        }
+    } else if let Some(result) = matches.get_one::<String>("result") {
+        let outcome = match result.as_str() {
+            "home" => MatchOutcome::Win(Side::Home),
+            "away" => MatchOutcome::Win(Side::Away),
+            _ => MatchOutcome::Draw,
+        };
+        match details {
+            MatchDetails::Chess { result, .. } | MatchDetails::HeadToHeadResult { result, .. } => {
+                *result = Some(outcome)
+            }
+            _ => return Err("the match isn't decided by its result alone".to_string()),
+        }
+    }
+    Ok(())
+}
+
+/// Record the result of a match
+fn result(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some(mut a_match) = get_match(&store, matches) else {
+        return;
+    };
+    if a_match.confirmed {
+        println!(
+            "Cannot record the result, match {} is already confirmed.",
+            a_match.id
+        );
+        return;
+    }
+    let Some(sport) = get_sport(&store, &a_match) else {
+        println!("Cannot find the league match {} is played in.", a_match.id);
+        return;
+    };
+
+    let mut details = a_match.details.clone();
+    if let Err(error) = fill_details(matches, &mut details) {
+        println!("Cannot record the result, {error}.");
+        return;
+    }
+    if let Err(error) = a_match.set_details(&sport, details) {
+        println!("Cannot record the result, {error}.");
+        return;
+    }
+
+    match store.update_match(&a_match) {
+        Ok(()) => println!("Recorded the result of match {}", a_match.id),
+        Err(error) => println!("Cannot record the result, {error}."),
+    }
+}
+
+/// Confirm the result of a match
+fn confirm(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some(a_match) = get_match(&store, matches) else {
+        return;
+    };
+    if a_match.confirmed {
+        println!("Match {} is already confirmed.", a_match.id);
+        return;
+    }
+    if a_match.scored_details().outcome().is_none() {
+        println!("Match {} doesn't have a result yet.", a_match.id);
+        return;
+    }
+
+    match store.confirm_match(&a_match.id) {
+        Ok(()) => println!("Confirmed the result of match {}", a_match.id),
+        Err(error) => println!("Cannot confirm the result, {error}."),
+    }
+}
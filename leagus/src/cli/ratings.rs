@@ -0,0 +1,58 @@
+use clap::{ArgMatches, Command};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+use leagus::ratings;
+
+pub const CMD_NAME: &str = "ratings";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing participant ratings")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("recalculate")
+                .about("Recalculate all ratings from scratch from every confirmed match"),
+        )
+        .subcommand(Command::new("list").about("List the current ratings"))
+}
+
+/// Delegate subcommands of the ratings command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("recalculate", sub_matches)) => recalculate(sub_matches),
+        Some(("list", sub_matches)) => list(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Recalculate the ratings of every participant across all leagues
+fn recalculate(_matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let history = store.list_match_history();
+    let ratings = ratings::recalculate(&history);
+    store.replace_ratings(&ratings);
+    println!(
+        "Recalculated ratings for {} participants from {} matches",
+        ratings.len(),
+        history.len()
+    );
+}
+
+/// List the ratings of every rated participant
+fn list(_matches: &ArgMatches) {
+    let store = MongoStore::new();
+    let mut ratings = store.list_ratings();
+    ratings.sort_by(|a, b| b.elo.rating.total_cmp(&a.elo.rating));
+    for rating in ratings {
+        println!(
+            "- {} \n\telo: {:.0} ({} matches)\n\tglicko-2: {:.0} ±{:.0} (volatility {:.4})\n\ttrueskill: {:.2} ±{:.2}",
+            rating.participant_id,
+            rating.elo.rating,
+            rating.elo.history.len(),
+            rating.glicko.rating,
+            rating.glicko.deviation,
+            rating.glicko.volatility,
+            rating.trueskill.mu,
+            rating.trueskill.sigma
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use std::collections::HashMap;
+
+use bson::Uuid;
 use chrono::{DateTime, TimeDelta, Utc};
-use clap::{arg, ArgMatches, Command};
-use leagus::models::Season;
+use clap::{arg, value_parser, ArgMatches, Command};
+use leagus::fixtures::round_robin::{Legs, RoundRobin};
+use leagus::models::{Match, ParticipantId, Season, SeasonId, SeasonStatus, TieBreaker, VenueId};
 use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
 
 pub const CMD_NAME: &str = "seasons";
@@ -28,6 +32,184 @@ pub fn commands() -> Command {
                     .required(true),
                 ),
         )
+        .subcommand(
+            Command::new("fixtures")
+                .about("Generate round-robin fixtures for the participants of a season")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season to generate fixtures for"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -v --venue <ID> "Id of the venue the matches are played at"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    --start <DATE> "Date of the first session, defaults to the season start"
+                ))
+                .arg(
+                    arg!(
+                        --every <DAYS> "Days between sessions"
+                    )
+                    .value_parser(value_parser!(i64))
+                    .default_value("7"),
+                )
+                .arg(arg!(
+                    --double "Play everyone twice, once at home and once away"
+                )),
+        )
+        .subcommand(
+            Command::new("promote")
+                .about("End a season, promoting and relegating between divisions into the next")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season that has finished"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    --start <DATE> "Start date of the next season, defaults to the end of this one"
+                ))
+                .arg(arg!(
+                    -e --end <DATE> "End date of the next season"
+                ))
+                .arg(arg!(
+                    -n --name <NAME> "Name of the next season"
+                )),
+        )
+        .subcommand(
+            Command::new("clone")
+                .about("Create a new season from a previous one, with its settings, schedule and roster")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season to copy"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    --start <DATE> "Start date of the new season, defaults to the end of this one"
+                ))
+                .arg(arg!(
+                    -e --end <DATE> "End date of the new season"
+                ))
+                .arg(arg!(
+                    -n --name <NAME> "Name of the new season"
+                ))
+                .arg(arg!(
+                    --seed "List participants in the order they finished the previous season"
+                )),
+        )
+        .subcommand(
+            Command::new("register")
+                .about("Register a participant to play in a season")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    -j --joined <DATE> "When they joined, defaults to now"
+                ))
+                .arg(arg!(
+                    -d --division <NAME> "Name of the division they play in"
+                )),
+        )
+        .subcommand(
+            Command::new("withdraw")
+                .about("Withdraw a participant from the rest of a season, keeping their results")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("unregister")
+                .about("Remove a participant registered for a season by mistake")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("roster")
+                .about("List the participants registered for a season")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("status")
+                .about("Move a season on to another status")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(<STATUS> "The season's new status")
+                        .value_parser(["draft", "open", "running", "finished", "archived"])
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("tie-breakers")
+                .about("Set the chain of tie-breakers ranking those level on points, in order")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!([BREAKER]... "The tie-breakers, none to leave ties as they are")
+                        .value_parser([
+                            "head-to-head",
+                            "set-difference",
+                            "game-difference",
+                            "sets-won",
+                            "matches-played",
+                            "buchholz",
+                            "sonneborn-berger",
+                            "random",
+                        ]),
+                )
+                .arg(
+                    arg!(
+                        --seed <SEED> "Seed of the random tie-breaker, defaults to the time"
+                    )
+                    .value_parser(value_parser!(u64)),
+                ),
+        )
         .subcommand(
             Command::new("list")
                 .about("List existing seasons")
@@ -41,6 +223,15 @@ pub fn commands() -> Command {
 pub fn handle_subcommands(matches: &ArgMatches) {
     match matches.subcommand() {
         Some(("create", sub_matches)) => create(sub_matches),
+        Some(("fixtures", sub_matches)) => fixtures(sub_matches),
+        Some(("promote", sub_matches)) => promote(sub_matches),
+        Some(("clone", sub_matches)) => clone(sub_matches),
+        Some(("register", sub_matches)) => register(sub_matches),
+        Some(("withdraw", sub_matches)) => withdraw(sub_matches),
+        Some(("unregister", sub_matches)) => unregister(sub_matches),
+        Some(("roster", sub_matches)) => roster(sub_matches),
+        Some(("status", sub_matches)) => status(sub_matches),
+        Some(("tie-breakers", sub_matches)) => tie_breakers(sub_matches),
         Some(("list", sub_matches)) => list(sub_matches),
         _ => unreachable!("Must specify a subcommand"),
     }
@@ -73,13 +264,359 @@ fn create(matches: &ArgMatches) {
     match league {
         Some(league) => {
             println!("Adding new season to {:?}", league);
-            let season = Season::new(&league.id, &start, &end, name);
+            let season = Season::for_league(&league, &start, &end, name);
             store.create_season(&season);
         }
         None => println!("Cannot find league with name \"{}\".", league_name),
     }
 }
 
+/// Generate and store a round-robin between the participants of a season
+fn fixtures(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+    let venue_id = matches.get_one::<String>("venue").expect("required");
+    let venue_id = Uuid::parse_str(venue_id).expect("Invalid venue id");
+    let every = *matches.get_one::<i64>("every").expect("defaulted");
+
+    let mut store = MongoStore::new();
+    let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    if season.status >= SeasonStatus::Finished {
+        println!("Cannot add fixtures to a {} season.", season.status);
+        return;
+    }
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find league of season \"{}\".", season.name);
+        return;
+    };
+
+    // TODO: handle bad dates with more grace
+    let start = matches
+        .get_one::<String>("start")
+        .map(|start| start.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(season.start);
+
+    let generator = RoundRobin {
+        legs: match matches.get_flag("double") {
+            true => Legs::Double,
+            false => Legs::Single,
+        },
+        start,
+        interval: TimeDelta::days(every),
+        venue_id: VenueId::from(venue_id),
+    };
+    match generator.generate(&season, league.sport, &season.participants()) {
+        Ok(fixtures) => match store.create_fixtures(&fixtures) {
+            Ok(()) => println!(
+                "Created {} sessions with {} matches for {}",
+                fixtures.sessions.len(),
+                fixtures.matches.len(),
+                season.name
+            ),
+            Err(error) => println!("Cannot generate fixtures, {error}."),
+        },
+        Err(error) => println!("Cannot generate fixtures, {error}."),
+    }
+}
+
+/// Create the next season of a league, moving participants between its
+/// divisions on the standings of the season just finished
+fn promote(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+
+    let mut store = MongoStore::new();
+    let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    if season.status < SeasonStatus::Finished {
+        println!("Season {} hasn't finished yet.", season.name);
+        return;
+    }
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find league of season \"{}\".", season.name);
+        return;
+    };
+    if league.divisions.is_empty() {
+        println!("League \"{}\" has no divisions.", league.name);
+        return;
+    }
+
+    // TODO: handle bad dates with more grace
+    let start = matches
+        .get_one::<String>("start")
+        .map(|start| start.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(season.end);
+    let end = matches
+        .get_one::<String>("end")
+        .map(|end| end.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(start + (season.end - season.start));
+    let default_name = "".to_string();
+    let name = matches.get_one::<String>("name").unwrap_or(&default_name);
+
+    let confirmed: Vec<Match> = store
+        .list_matches_for_season(&season.id)
+        .into_iter()
+        .filter(|a_match| a_match.confirmed)
+        .collect();
+    let next = season.next_season(&league, &confirmed, &start, &end, name);
+    store.create_season(&next);
+
+    println!("Created season {} for {}", next.id, league.name);
+    for division in &next.divisions {
+        println!("{}", division.name);
+        for entry in &division.table.entries {
+            println!("  - {}", entry.participant_name);
+        }
+    }
+}
+
+/// Create a new season from a previous one, scheduling its sessions if
+/// they recur
+fn clone(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+
+    let mut store = MongoStore::new();
+    let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find league of season \"{}\".", season.name);
+        return;
+    };
+
+    // TODO: handle bad dates with more grace
+    let start = matches
+        .get_one::<String>("start")
+        .map(|start| start.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(season.end);
+    let end = matches
+        .get_one::<String>("end")
+        .map(|end| end.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(start + (season.end - season.start));
+    let default_name = "".to_string();
+    let name = matches.get_one::<String>("name").unwrap_or(&default_name);
+
+    let confirmed: Vec<Match> = store
+        .list_matches_for_season(&season.id)
+        .into_iter()
+        .filter(|a_match| a_match.confirmed)
+        .collect();
+    let next = season.from_previous(
+        &league,
+        &confirmed,
+        &start,
+        &end,
+        name,
+        matches.get_flag("seed"),
+    );
+    store.create_season(&next);
+    let sessions = next
+        .recurrence
+        .as_ref()
+        .map(|recurrence| recurrence.sessions(&next, &[]))
+        .unwrap_or_default();
+    if let Err(error) = store.create_sessions(&sessions) {
+        store.delete_season(&next.id);
+        println!("Cannot create the season's sessions, {error}.");
+        return;
+    }
+
+    println!(
+        "Created season {} for {} with {} participants and {} sessions",
+        next.id,
+        league.name,
+        next.roster.len(),
+        sessions.len()
+    );
+}
+
+/// Get the season and participant ids given
+fn get_season(store: &MongoStore, matches: &ArgMatches) -> Option<(Season, ParticipantId)> {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+    let participant_id = matches.get_one::<String>("participant").expect("required");
+    let participant_id = Uuid::parse_str(participant_id).expect("Invalid participant id");
+
+    match store.get_season(&SeasonId::from(season_id)) {
+        Some(season) => Some((season, ParticipantId::from(participant_id))),
+        None => {
+            println!("Cannot find season with id \"{}\".", season_id);
+            None
+        }
+    }
+}
+
+/// Register a participant for a season
+fn register(matches: &ArgMatches) {
+    // TODO: handle bad dates with more grace
+    let joined = matches
+        .get_one::<String>("joined")
+        .map(|joined| joined.parse::<DateTime<Utc>>().unwrap())
+        .unwrap_or(Utc::now());
+
+    let mut store = MongoStore::new();
+    let Some((mut season, participant_id)) = get_season(&store, matches) else {
+        return;
+    };
+    let Some(participant) = store.get_participant(&participant_id) else {
+        println!("Cannot find participant with id \"{}\".", participant_id);
+        return;
+    };
+
+    let registered = match matches.get_one::<String>("division") {
+        Some(division) => season.register_in_division(&participant, division, &joined),
+        None => season.register(&participant, &joined),
+    }
+    .and_then(|()| store.update_season(&season));
+    match registered {
+        Ok(()) => println!("Registered {} for {}", participant.name, season.name),
+        Err(error) => println!("Cannot register {}, {error}.", participant.name),
+    }
+}
+
+/// Withdraw a participant from a season
+fn withdraw(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some((mut season, participant_id)) = get_season(&store, matches) else {
+        return;
+    };
+
+    let withdrawn = season
+        .withdraw(&participant_id, &Utc::now())
+        .and_then(|()| store.update_season(&season));
+    match withdrawn {
+        Ok(()) => println!("Withdrew {} from {}", participant_id, season.name),
+        Err(error) => println!("Cannot withdraw {}, {error}.", participant_id),
+    }
+}
+
+/// Remove a participant from a season's roster
+fn unregister(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some((mut season, participant_id)) = get_season(&store, matches) else {
+        return;
+    };
+
+    let removed = season
+        .unregister(&participant_id)
+        .and_then(|()| store.update_season(&season));
+    match removed {
+        Ok(()) => println!("Removed {} from {}", participant_id, season.name),
+        Err(error) => println!("Cannot remove {}, {error}.", participant_id),
+    }
+}
+
+/// List the participants registered for a season
+fn roster(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+
+    let store = MongoStore::new();
+    let Some(season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    let names: HashMap<ParticipantId, String> = store
+        .list_participants()
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+
+    println!("Roster of {}:", season.name);
+    for registration in &season.roster {
+        let name = names
+            .get(&registration.participant_id)
+            .cloned()
+            .unwrap_or_else(|| registration.participant_id.to_string());
+        let withdrawn = registration
+            .withdrawn
+            .map(|withdrawn| format!(", withdrew {}", withdrawn))
+            .unwrap_or_default();
+        println!("\t- {} (joined {}{})", name, registration.joined, withdrawn);
+    }
+}
+
+/// Move a season on to another status
+fn status(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+    let status = match matches.get_one::<String>("STATUS").map(String::as_str) {
+        Some("draft") => SeasonStatus::Draft,
+        Some("open") => SeasonStatus::Open,
+        Some("running") => SeasonStatus::Running,
+        Some("finished") => SeasonStatus::Finished,
+        _ => SeasonStatus::Archived,
+    };
+
+    let mut store = MongoStore::new();
+    let Some(mut season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+
+    let updated = season
+        .set_status(status)
+        .and_then(|()| store.update_season(&season));
+    match updated {
+        Ok(()) => println!("Season {} is now {}", season.name, season.status),
+        Err(error) => println!("Cannot update season {}, {error}.", season.name),
+    }
+}
+
+/// Set the tie-breakers of a season
+fn tie_breakers(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+    let seed = matches
+        .get_one::<u64>("seed")
+        .copied()
+        .unwrap_or_else(|| Utc::now().timestamp().unsigned_abs());
+    let tie_breakers: Vec<TieBreaker> = matches
+        .get_many::<String>("BREAKER")
+        .unwrap_or_default()
+        .map(|breaker| match breaker.as_str() {
+            "head-to-head" => TieBreaker::HeadToHead,
+            "set-difference" => TieBreaker::SetDifference,
+            "game-difference" => TieBreaker::GameDifference,
+            "sets-won" => TieBreaker::SetsWon,
+            "matches-played" => TieBreaker::MatchesPlayed,
+            "buchholz" => TieBreaker::Buchholz,
+            "sonneborn-berger" => TieBreaker::SonnebornBerger,
+            _ => TieBreaker::Random { seed },
+        })
+        .collect();
+
+    let mut store = MongoStore::new();
+    let Some(mut season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+
+    season.tie_breakers = tie_breakers;
+    match store.update_season(&season) {
+        Ok(()) => {
+            let chain: Vec<String> = season
+                .tie_breakers
+                .iter()
+                .map(|tie_breaker| tie_breaker.to_string())
+                .collect();
+            println!(
+                "Season {} breaks ties by: {}",
+                season.name,
+                chain.join(", ")
+            );
+        }
+        Err(error) => println!("Cannot update season {}, {error}.", season.name),
+    }
+}
+
 /// List all leagues
 fn list(_matches: &ArgMatches) {
     let store = MongoStore::new();
@@ -88,7 +625,7 @@ fn list(_matches: &ArgMatches) {
         println!("League: {}", league.name);
         let seasons = store.list_seasons_for_league(&league.id);
         for season in seasons {
-            println!("\t- {:?}", season);
+            println!("\t- {} ({}), {}", season.name, season.id, season.status);
         }
     }
 }
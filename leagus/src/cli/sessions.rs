@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+
 use bson::Uuid;
-use chrono::{DateTime, Utc};
-use clap::{arg, ArgMatches, Command};
-use leagus::models::{SeasonId, Session};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc, Weekday};
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use leagus::match_making::{assign_courts, SeasonHistory};
+use leagus::models::{
+    Match, MatchMakingStrategy, Participant, ParticipantId, PointsTable, Recurrence, Round,
+    RoundId, RsvpAnswer, SeasonId, Session, SessionId, VenueId,
+};
 use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
 
 pub const CMD_NAME: &str = "sessions";
@@ -23,6 +29,130 @@ pub fn commands() -> Command {
                     .required(true),
                 ),
         )
+        .subcommand(
+            Command::new("schedule")
+                .about("Schedule a season's sessions to recur, creating any not yet created")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -w --weekday <DAY> "Day of the week the sessions are on, e.g. \"Tue\""
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -t --time <TIME> "Time the sessions start (UTC), e.g. \"19:00\""
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --every <WEEKS> "Weeks between sessions"
+                    )
+                    .value_parser(value_parser!(u32))
+                    .default_value("1"),
+                )
+                .arg(
+                    arg!(
+                        -x --except <DATE> "A date without a session, e.g. \"2024-12-24\""
+                    )
+                    .action(ArgAction::Append),
+                ),
+        )
+        .subcommand(
+            Command::new("reschedule")
+                .about("Move a session to another date")
+                .arg(arg!(<ID> "Id of the session").required(true))
+                .arg(
+                    arg!(
+                        -d --date <DATE> "New date of the session"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("cancel")
+                .about("Call off a session")
+                .arg(arg!(<ID> "Id of the session").required(true)),
+        )
+        .subcommand(
+            Command::new("rsvp")
+                .about("Reply to whether a participant is coming to a session")
+                .arg(arg!(<ID> "Id of the session").required(true))
+                .arg(
+                    arg!(
+                        -p --participant <ID> "Id of the participant"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -a --answer <ANSWER> "Whether they're coming"
+                    )
+                    .value_parser(["yes", "no", "maybe"])
+                    .required(true),
+                )
+                .arg(arg!(
+                    --arrival <TIME> "When they expect to arrive (UTC), e.g. \"19:30\""
+                )),
+        )
+        .subcommand(
+            Command::new("capacity")
+                .about("Limit how many participants can attend a session")
+                .arg(arg!(<ID> "Id of the session").required(true))
+                .arg(
+                    arg!(
+                        -m --max <COUNT> "Most participants who can attend, no limit if left out"
+                    )
+                    .value_parser(value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("attendance")
+                .about("Show who is coming to a session")
+                .arg(arg!(<ID> "Id of the session").required(true)),
+        )
+        .subcommand(
+            Command::new("add-round")
+                .about("Add a round to a session with everyone confirmed as coming")
+                .arg(arg!(<ID> "Id of the session").required(true))
+                .arg(arg!(
+                    --by <TIME> "Only include those arriving by this time (UTC), e.g. \"20:00\""
+                )),
+        )
+        .subcommand(
+            Command::new("round-capacity")
+                .about("Limit how many matches can be played in a round")
+                .arg(arg!(<ID> "Id of the round").required(true))
+                .arg(
+                    arg!(
+                        -m --max <COUNT> "Most matches in the round, no limit if left out"
+                    )
+                    .value_parser(value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("pair-round")
+                .about("Pair a round's participants into matches with its strategy")
+                .arg(arg!(<ID> "Id of the round").required(true))
+                .arg(
+                    arg!(
+                        -v --venue <ID> "Id of the venue the matches are played at"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --strategy <STRATEGY> "How to pair the round, instead of its own strategy"
+                    )
+                    .value_parser(["manual", "swiss", "doubles"]),
+                ),
+        )
         .subcommand(
             Command::new("list")
                 .about("List existing sessions")
@@ -36,6 +166,15 @@ pub fn commands() -> Command {
 pub fn handle_subcommands(matches: &ArgMatches) {
     match matches.subcommand() {
         Some(("create", sub_matches)) => create(sub_matches),
+        Some(("schedule", sub_matches)) => schedule(sub_matches),
+        Some(("reschedule", sub_matches)) => reschedule(sub_matches),
+        Some(("cancel", sub_matches)) => cancel(sub_matches),
+        Some(("rsvp", sub_matches)) => rsvp(sub_matches),
+        Some(("capacity", sub_matches)) => capacity(sub_matches),
+        Some(("attendance", sub_matches)) => attendance(sub_matches),
+        Some(("add-round", sub_matches)) => add_round(sub_matches),
+        Some(("round-capacity", sub_matches)) => round_capacity(sub_matches),
+        Some(("pair-round", sub_matches)) => pair_round(sub_matches),
         Some(("list", sub_matches)) => list(sub_matches),
         _ => unreachable!("Must specify a subcommand"),
     }
@@ -65,12 +204,437 @@ fn create(matches: &ArgMatches) {
                 serde_json::to_string_pretty(&session).unwrap(),
                 serde_json::to_string_pretty(&season).unwrap()
             );
-            store.create_session(&session);
+            if let Err(error) = store.create_session(&session) {
+                println!("Cannot add the session, {error}.");
+            }
         }
         None => println!("Cannot find league with name \"{}\".", season_id),
     }
 }
 
+/// Set the recurrence of a season and create its sessions
+fn schedule(matches: &ArgMatches) {
+    let season_id = matches.get_one::<String>("season").expect("required");
+    let season_id = Uuid::parse_str(season_id).expect("Invalid season id");
+
+    let weekday = matches.get_one::<String>("weekday").expect("required");
+    let Ok(weekday) = weekday.parse::<Weekday>() else {
+        println!("Cannot read the day \"{}\", e.g. \"Tue\".", weekday);
+        return;
+    };
+    let time = matches.get_one::<String>("time").expect("required");
+    let Ok(time) = NaiveTime::parse_from_str(time, "%H:%M") else {
+        println!("Cannot read the time \"{}\", e.g. \"19:00\".", time);
+        return;
+    };
+    let mut exclusions = Vec::new();
+    for date in matches.get_many::<String>("except").unwrap_or_default() {
+        match date.parse::<NaiveDate>() {
+            Ok(date) => exclusions.push(date),
+            Err(_) => {
+                println!("Cannot read the date \"{}\", e.g. \"2024-12-24\".", date);
+                return;
+            }
+        }
+    }
+    let recurrence = Recurrence {
+        weekday,
+        time,
+        every: *matches.get_one::<u32>("every").expect("defaulted"),
+        exclusions,
+    };
+
+    let mut store = MongoStore::new();
+    let Some(mut season) = store.get_season(&SeasonId::from(season_id)) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+
+    let sessions = recurrence.sessions(&season, &store.list_sessions_for_season(&season.id));
+    season.recurrence = Some(recurrence);
+    if let Err(error) = store.update_season(&season) {
+        println!("Cannot schedule sessions, {error}.");
+        return;
+    }
+    for session in &sessions {
+        if let Err(error) = store.create_session(session) {
+            println!("Cannot schedule sessions, {error}.");
+            return;
+        }
+    }
+    println!("Created {} sessions for {}", sessions.len(), season.name);
+}
+
+/// Get the session with the id given
+fn get_session(store: &MongoStore, matches: &ArgMatches) -> Option<Session> {
+    let session_id = matches.get_one::<String>("ID").expect("required");
+    let session_id = Uuid::parse_str(session_id).expect("Invalid session id");
+
+    let session = store.get_session(&SessionId::from(session_id));
+    if session.is_none() {
+        println!("Cannot find session with id \"{}\".", session_id);
+    }
+    session
+}
+
+/// Move a session to another date
+fn reschedule(matches: &ArgMatches) {
+    // TODO: handle bad dates with more grace
+    let date = matches.get_one::<String>("date").expect("required");
+    let date = date.parse::<DateTime<Utc>>().unwrap();
+
+    let mut store = MongoStore::new();
+    let Some(mut session) = get_session(&store, matches) else {
+        return;
+    };
+    session.reschedule(&date);
+    match store.update_session(&session) {
+        Ok(()) => println!("Session {} is now on {}", session.id, session.date),
+        Err(error) => println!("Cannot reschedule the session, {error}."),
+    }
+}
+
+/// Call off a session
+fn cancel(matches: &ArgMatches) {
+    let mut store = MongoStore::new();
+    let Some(mut session) = get_session(&store, matches) else {
+        return;
+    };
+    session.cancel();
+    match store.update_session(&session) {
+        Ok(()) => println!("Cancelled session {} on {}", session.id, session.date),
+        Err(error) => println!("Cannot cancel the session, {error}."),
+    }
+}
+
+/// Read an optional time argument such as "19:30"
+fn get_time(matches: &ArgMatches, id: &str) -> Result<Option<NaiveTime>, ()> {
+    match matches.get_one::<String>(id) {
+        Some(time) => match NaiveTime::parse_from_str(time, "%H:%M") {
+            Ok(time) => Ok(Some(time)),
+            Err(_) => {
+                println!("Cannot read the time \"{}\", e.g. \"19:30\".", time);
+                Err(())
+            }
+        },
+        None => Ok(None),
+    }
+}
+
+/// Record whether a participant is coming to a session
+fn rsvp(matches: &ArgMatches) {
+    let participant_id = matches.get_one::<String>("participant").expect("required");
+    let participant_id = Uuid::parse_str(participant_id).expect("Invalid participant id");
+    let answer = match matches
+        .get_one::<String>("answer")
+        .expect("required")
+        .as_str()
+    {
+        "yes" => RsvpAnswer::Yes,
+        "no" => RsvpAnswer::No,
+        _ => RsvpAnswer::Maybe,
+    };
+    let Ok(arrival) = get_time(matches, "arrival") else {
+        return;
+    };
+
+    let mut store = MongoStore::new();
+    let Some(mut session) = get_session(&store, matches) else {
+        return;
+    };
+    let participant_id = ParticipantId::from(participant_id);
+    let promoted = session.rsvp(&participant_id, answer, arrival, &Utc::now());
+    if let Err(error) = store.update_session(&session) {
+        println!("Cannot record the reply, {error}.");
+        return;
+    }
+
+    let attendance = session.attendance();
+    println!(
+        "Recorded {:?} for session {}: {} coming, {} not, {} maybe, {} waiting",
+        answer, session.id, attendance.yes, attendance.no, attendance.maybe, attendance.waitlisted
+    );
+    if session.waitlist.contains(&participant_id) {
+        println!("The session is full, so they're on the waitlist.");
+    }
+    print_promoted(&promoted);
+}
+
+/// Change the most participants who can attend a session
+fn capacity(matches: &ArgMatches) {
+    let capacity = matches.get_one::<usize>("max").copied();
+
+    let mut store = MongoStore::new();
+    let Some(mut session) = get_session(&store, matches) else {
+        return;
+    };
+    let promoted = session.set_capacity(capacity);
+    if let Err(error) = store.update_session(&session) {
+        println!("Cannot change the capacity, {error}.");
+        return;
+    }
+
+    match capacity {
+        Some(capacity) => println!("Session {} is limited to {}", session.id, capacity),
+        None => println!("Session {} has no limit", session.id),
+    }
+    print_promoted(&promoted);
+}
+
+/// Tell the organiser who has been given a place from the waitlist
+fn print_promoted(promoted: &[ParticipantId]) {
+    for participant_id in promoted {
+        println!("Promoted {} from the waitlist", participant_id);
+    }
+}
+
+/// Show the replies to a session
+fn attendance(matches: &ArgMatches) {
+    let store = MongoStore::new();
+    let Some(session) = get_session(&store, matches) else {
+        return;
+    };
+    let names: HashMap<ParticipantId, String> = store
+        .list_participants()
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+
+    let attendance = session.attendance();
+    println!(
+        "Session {} on {}: {} coming, {} not, {} maybe, {} waiting",
+        session.id,
+        session.date,
+        attendance.yes,
+        attendance.no,
+        attendance.maybe,
+        attendance.waitlisted
+    );
+    let name = |participant_id: &ParticipantId| {
+        names
+            .get(participant_id)
+            .cloned()
+            .unwrap_or_else(|| participant_id.to_string())
+    };
+    for rsvp in &session.rsvps {
+        if session.waitlist.contains(&rsvp.participant_id) {
+            continue;
+        }
+        let arrival = rsvp
+            .arrival
+            .map(|arrival| format!(" from {}", arrival.format("%H:%M")))
+            .unwrap_or_default();
+        println!(
+            "\t- {}: {:?}{}",
+            name(&rsvp.participant_id),
+            rsvp.answer,
+            arrival
+        );
+    }
+    if !session.waitlist.is_empty() {
+        println!("Waitlist:");
+        for (position, participant_id) in session.waitlist.iter().enumerate() {
+            println!("\t{}. {}", position + 1, name(participant_id));
+        }
+    }
+    if !session.withdrawals.is_empty() {
+        println!("Withdrawals:");
+        for withdrawal in &session.withdrawals {
+            let late = if withdrawal.late { " (late)" } else { "" };
+            println!(
+                "\t- {} on {}{}",
+                name(&withdrawal.participant_id),
+                withdrawal.at,
+                late
+            );
+        }
+    }
+}
+
+/// Add a round with the session's confirmed attendees
+fn add_round(matches: &ArgMatches) {
+    let Ok(by) = get_time(matches, "by") else {
+        return;
+    };
+
+    let mut store = MongoStore::new();
+    let Some(session) = get_session(&store, matches) else {
+        return;
+    };
+    let mut round = Round::new(session.id);
+    let added = round.add_attendees(&session, by);
+    if let Err(error) = store.create_round(&round) {
+        println!("Cannot add the round, {error}.");
+        return;
+    }
+    println!(
+        "Added round {} to session {} with {} participants",
+        round.id, session.id, added
+    );
+}
+
+/// Limit how many matches can be played in a round
+fn round_capacity(matches: &ArgMatches) {
+    let round_id = matches.get_one::<String>("ID").expect("required");
+    let round_id = Uuid::parse_str(round_id).expect("Invalid round id");
+    let capacity = matches.get_one::<usize>("max").copied();
+
+    let mut store = MongoStore::new();
+    let Some(mut round) = store.get_round(&RoundId::from(round_id)) else {
+        println!("Cannot find round with id \"{}\".", round_id);
+        return;
+    };
+    round.capacity = capacity;
+    if let Err(error) = store.update_round(&round) {
+        println!("Cannot change the capacity, {error}.");
+        return;
+    }
+
+    match capacity {
+        Some(capacity) => println!("Round {} is limited to {} matches", round.id, capacity),
+        None => println!("Round {} has no limit", round.id),
+    }
+}
+
+/// Pair a round with its strategy, creating its matches and recording who
+/// sat out
+fn pair_round(matches: &ArgMatches) {
+    let round_id = matches.get_one::<String>("ID").expect("required");
+    let round_id = Uuid::parse_str(round_id).expect("Invalid round id");
+    let venue_id = matches.get_one::<String>("venue").expect("required");
+    let venue_id = Uuid::parse_str(venue_id).expect("Invalid venue id");
+
+    let mut store = MongoStore::new();
+    let Some(mut round) = store.get_round(&RoundId::from(round_id)) else {
+        println!("Cannot find round with id \"{}\".", round_id);
+        return;
+    };
+    let Some(venue) = store.get_venue(&VenueId::from(venue_id)) else {
+        println!("Cannot find venue with id \"{}\".", venue_id);
+        return;
+    };
+    let Some(session) = store.get_session(&round.session_id) else {
+        println!("Cannot find the session of round {}.", round.id);
+        return;
+    };
+    let Some(season) = store.get_season(&session.season_id) else {
+        println!("Cannot find the season of round {}.", round.id);
+        return;
+    };
+    let Some(league) = store.get_league(&season.league_id) else {
+        println!("Cannot find the league of round {}.", round.id);
+        return;
+    };
+    if let Some(strategy) = matches.get_one::<String>("strategy") {
+        round.strategy = match strategy.as_str() {
+            "swiss" => MatchMakingStrategy::Swiss,
+            "doubles" => MatchMakingStrategy::RotatingDoubles,
+            _ => MatchMakingStrategy::Manual,
+        };
+    }
+
+    let season_matches = store.list_matches_for_season(&season.id);
+    if season_matches
+        .iter()
+        .any(|a_match| a_match.round_id == round.id)
+    {
+        println!("Round {} already has matches.", round.id);
+        return;
+    }
+    let mut rounds = store.list_rounds_for_season(&season.id);
+    rounds.retain(|earlier| earlier.id != round.id);
+
+    // Only confirmed results count towards the standings pairings are made by.
+    let roster = season.participants();
+    let participants: Vec<Participant> = roster
+        .iter()
+        .filter_map(|participant_id| store.get_participant(participant_id))
+        .collect();
+    let confirmed: Vec<Match> = season_matches
+        .iter()
+        .filter(|a_match| a_match.confirmed)
+        .cloned()
+        .collect();
+    let table = PointsTable::from_matches(&participants, &confirmed, &season.scoring);
+    // Doubles teams are balanced by each participant's Elo rating.
+    let skills: HashMap<ParticipantId, f64> = store
+        .list_ratings()
+        .into_iter()
+        .map(|rating| (rating.participant_id, rating.elo.rating))
+        .collect();
+    let history = SeasonHistory {
+        table: &table,
+        scoring: &season.scoring,
+        rounds: &rounds,
+        matches: &season_matches,
+        skills: &skills,
+        roster: Some(&roster),
+    };
+
+    // Those who don't fit on the courts free sit out.
+    round.fit_courts(&venue, &session.date);
+    let Some(pairings) = round.pair(&history) else {
+        println!(
+            "Round {} is paired by hand, give a strategy to pair it with.",
+            round.id
+        );
+        return;
+    };
+    let Some(mut round_matches) = pairings.matches(&round.id, league.sport, &venue.id) else {
+        println!(
+            "Cannot pair the round, {} isn't played the way it was paired.",
+            league.sport
+        );
+        return;
+    };
+    if let Err(error) = assign_courts(&mut round_matches, &venue, &session.date, &[]) {
+        println!("Cannot put the round's matches on courts, {error}.");
+        return;
+    }
+
+    for a_match in &round_matches {
+        if let Err(error) = store.create_match(a_match) {
+            println!("Cannot create the round's matches, {error}.");
+            return;
+        }
+    }
+    if let Err(error) = store.update_round(&round) {
+        println!("Cannot update the round, {error}.");
+        return;
+    }
+
+    let name = |participant_id: &ParticipantId| {
+        store
+            .get_participant(participant_id)
+            .map_or(participant_id.to_string(), |participant| participant.name)
+    };
+    println!(
+        "Paired round {} into {} matches",
+        round.id,
+        round_matches.len()
+    );
+    for a_match in &round_matches {
+        if let Some((home, away)) = a_match.details.sides() {
+            let side =
+                |ids: Vec<ParticipantId>| ids.iter().map(name).collect::<Vec<String>>().join(" & ");
+            let court = a_match
+                .court
+                .as_ref()
+                .map_or(String::new(), |court| format!(" on {court}"));
+            println!(
+                "\t- {} v {}{} ({})",
+                side(home),
+                side(away),
+                court,
+                a_match.id
+            );
+        }
+    }
+    for participant_id in &round.sit_outs {
+        println!("\t- {} sits out", name(participant_id));
+    }
+}
+
 /// List all leagues
 fn list(_matches: &ArgMatches) {
     let store = MongoStore::new();
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use bson::Uuid;
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use leagus::models::{MatchId, Participant, ParticipantId};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+
+pub const CMD_NAME: &str = "teams";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing teams and their members")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create")
+                .about("Create a new team")
+                .arg(
+                    arg!(
+                        -n --name <NAME> "Name of the new team"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --"max-members" <COUNT> "Most members the team may have"
+                    )
+                    .value_parser(value_parser!(usize)),
+                )
+                .arg(
+                    arg!(
+                        --"min-players" <COUNT> "Fewest members who must play in a match"
+                    )
+                    .value_parser(value_parser!(usize)),
+                )
+                .arg(
+                    arg!(
+                        --"max-players" <COUNT> "Most members who may play in a match"
+                    )
+                    .value_parser(value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("add-member")
+                .about("Add a member to a team")
+                .arg(arg!(<ID> "Id of the team").required(true))
+                .arg(
+                    arg!(
+                        -m --member <ID> "Id of the participant joining"
+                    )
+                    .required(true),
+                )
+                .arg(arg!(
+                    --captain "Make the member a captain of the team"
+                )),
+        )
+        .subcommand(
+            Command::new("remove-member")
+                .about("Remove a member from a team")
+                .arg(arg!(<ID> "Id of the team").required(true))
+                .arg(
+                    arg!(
+                        -m --member <ID> "Id of the participant leaving"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("lineup")
+                .about("Record the members who played for a team in a match")
+                .arg(
+                    arg!(
+                        --match <ID> "Id of the match"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -t --team <ID> "Id of the team"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -m --member <ID> "Id of a member who played"
+                    )
+                    .action(ArgAction::Append)
+                    .required(true),
+                ),
+        )
+        .subcommand(Command::new("list").about("List existing teams and their members"))
+}
+
+/// Delegate subcommands of the teams command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("create", sub_matches)) => create(sub_matches),
+        Some(("add-member", sub_matches)) => add_member(sub_matches),
+        Some(("remove-member", sub_matches)) => remove_member(sub_matches),
+        Some(("lineup", sub_matches)) => lineup(sub_matches),
+        Some(("list", sub_matches)) => list(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Create a new team
+fn create(matches: &ArgMatches) {
+    let name = matches.get_one::<String>("name").expect("required");
+
+    let mut team = Participant::new_team(name.clone());
+    let eligibility = &mut team.team.as_mut().expect("a team").eligibility;
+    eligibility.max_members = matches.get_one::<usize>("max-members").copied();
+    eligibility.min_players = matches.get_one::<usize>("min-players").copied();
+    eligibility.max_players = matches.get_one::<usize>("max-players").copied();
+
+    let mut store = MongoStore::new();
+    store.create_participant(&team);
+    println!("Created new team: \"{}\" ({})", team.name, team.id);
+}
+
+/// Get the team with the id given
+fn get_team(store: &MongoStore, team_id: &str) -> Option<Participant> {
+    let team_id = Uuid::parse_str(team_id).expect("Invalid team id");
+
+    match store.get_participant(&ParticipantId::from(team_id)) {
+        Some(team) if team.team.is_some() => Some(team),
+        Some(participant) => {
+            println!("Participant \"{}\" isn't a team.", participant.name);
+            None
+        }
+        None => {
+            println!("Cannot find team with id \"{}\".", team_id);
+            None
+        }
+    }
+}
+
+/// Get the member id given
+fn get_member(matches: &ArgMatches) -> ParticipantId {
+    let member_id = matches.get_one::<String>("member").expect("required");
+    let member_id = Uuid::parse_str(member_id).expect("Invalid member id");
+    ParticipantId::from(member_id)
+}
+
+/// Add a member to a team
+fn add_member(matches: &ArgMatches) {
+    let team_id = matches.get_one::<String>("ID").expect("required");
+    let member_id = get_member(matches);
+
+    let mut store = MongoStore::new();
+    let Some(mut team) = get_team(&store, team_id) else {
+        return;
+    };
+    if store.get_participant(&member_id).is_none() {
+        println!("Cannot find participant with id \"{}\".", member_id);
+        return;
+    }
+
+    let members = team.team.as_mut().expect("a team");
+    match members.add_member(&member_id, matches.get_flag("captain")) {
+        Ok(()) => {
+            store.update_participant(&team);
+            println!("Added {} to {}", member_id, team.name);
+        }
+        Err(error) => println!("Cannot add member, {error}."),
+    }
+}
+
+/// Remove a member from a team
+fn remove_member(matches: &ArgMatches) {
+    let team_id = matches.get_one::<String>("ID").expect("required");
+    let member_id = get_member(matches);
+
+    let mut store = MongoStore::new();
+    let Some(mut team) = get_team(&store, team_id) else {
+        return;
+    };
+
+    if team
+        .team
+        .as_mut()
+        .expect("a team")
+        .remove_member(&member_id)
+    {
+        store.update_participant(&team);
+        println!("Removed {} from {}", member_id, team.name);
+    } else {
+        println!("{} isn't a member of {}.", member_id, team.name);
+    }
+}
+
+/// Record which members played for a team in a match
+fn lineup(matches: &ArgMatches) {
+    let match_id = matches.get_one::<String>("match").expect("required");
+    let match_id = Uuid::parse_str(match_id).expect("Invalid match id");
+    let team_id = matches.get_one::<String>("team").expect("required");
+    let members: Vec<ParticipantId> = matches
+        .get_many::<String>("member")
+        .unwrap_or_default()
+        .map(|member| ParticipantId::from(Uuid::parse_str(member).expect("Invalid member id")))
+        .collect();
+
+    let mut store = MongoStore::new();
+    let Some(team) = get_team(&store, team_id) else {
+        return;
+    };
+    let Some(mut a_match) = store.get_match(&MatchId::from(match_id)) else {
+        println!("Cannot find match with id \"{}\".", match_id);
+        return;
+    };
+
+    match a_match.record_lineup(&team, &members) {
+        Ok(()) => match store.update_match(&a_match) {
+            Ok(()) => println!(
+                "Recorded {} members playing for {}",
+                members.len(),
+                team.name
+            ),
+            Err(error) => println!("Cannot record the lineup, {error}."),
+        },
+        Err(error) => println!("Cannot record the lineup, {error}."),
+    }
+}
+
+/// List all teams
+fn list(_matches: &ArgMatches) {
+    let store = MongoStore::new();
+    let participants = store.list_participants();
+    let names: HashMap<ParticipantId, &str> = participants
+        .iter()
+        .map(|participant| (participant.id, participant.name.as_str()))
+        .collect();
+
+    println!("Teams:");
+    for participant in &participants {
+        let Some(team) = &participant.team else {
+            continue;
+        };
+        println!("- {} \n\tid: {}", participant.name, participant.id);
+        for member in &team.members {
+            let name = names
+                .get(&member.participant_id)
+                .copied()
+                .unwrap_or("Unknown");
+            let captain = if member.captain { " (captain)" } else { "" };
+            println!("\t- {}{}", name, captain);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use bson::Uuid;
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use leagus::models::{
+    Match, ParticipantId, PointsTable, RatingSystem, RoundId, RubberKind, SeasonId, Tie, TieFormat,
+    TieId, VenueId,
+};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+use leagus::ratings;
+
+pub const CMD_NAME: &str = "ties";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing ties between teams, made up of rubbers")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create")
+                .about("Create a new tie between two teams")
+                .arg(
+                    arg!(
+                        -r --round <ID> "Id of the round the tie is played in"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --home <ID> "Id of the home team"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --away <ID> "Id of the away team"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --rubbers <ORDER> "The rubbers in order of play, e.g. \"S,S,D,S,S\""
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --"max-rubbers" <COUNT> "Most rubbers any one player may play"
+                    )
+                    .value_parser(value_parser!(usize)),
+                )
+                .arg(arg!(
+                    --"rating-order" "Singles players must be listed strongest first, by Elo"
+                )),
+        )
+        .subcommand(
+            Command::new("lineup")
+                .about("Submit the players a team puts forward for each rubber")
+                .arg(arg!(<ID> "Id of the tie").required(true))
+                .arg(
+                    arg!(
+                        -t --team <ID> "Id of the team"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        --rubber <PLAYERS> "Ids of the players of the next rubber, comma separated for doubles"
+                    )
+                    .action(ArgAction::Append)
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("start")
+                .about("Make the matches of each rubber once both lineups are in")
+                .arg(arg!(<ID> "Id of the tie").required(true))
+                .arg(
+                    arg!(
+                        -v --venue <ID> "Id of the venue the rubbers are played at"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("table")
+                .about("Show the teams' points table from the ties of a season")
+                .arg(
+                    arg!(
+                        -s --season <ID> "Id of the season"
+                    )
+                    .required(true),
+                ),
+        )
+}
+
+/// Delegate subcommands of the ties command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("create", sub_matches)) => create(sub_matches),
+        Some(("lineup", sub_matches)) => lineup(sub_matches),
+        Some(("start", sub_matches)) => start(sub_matches),
+        Some(("table", sub_matches)) => table(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Read an id argument
+fn get_id(matches: &ArgMatches, id: &str) -> Uuid {
+    let value = matches.get_one::<String>(id).expect("required");
+    Uuid::parse_str(value).unwrap_or_else(|_| panic!("Invalid {} id", id))
+}
+
+/// Create a new tie
+fn create(matches: &ArgMatches) {
+    let round_id = RoundId::from(get_id(matches, "round"));
+    let home = ParticipantId::from(get_id(matches, "home"));
+    let away = ParticipantId::from(get_id(matches, "away"));
+
+    let mut rubbers = Vec::new();
+    for rubber in matches
+        .get_one::<String>("rubbers")
+        .expect("required")
+        .split(',')
+    {
+        match rubber.trim() {
+            "S" | "s" => rubbers.push(RubberKind::Singles),
+            "D" | "d" => rubbers.push(RubberKind::Doubles),
+            _ => {
+                println!("Cannot read the rubber \"{}\", use S or D.", rubber);
+                return;
+            }
+        }
+    }
+    let format = TieFormat {
+        rubbers,
+        max_rubbers_per_player: matches.get_one::<usize>("max-rubbers").copied(),
+        singles_in_rating_order: matches.get_flag("rating-order"),
+    };
+
+    let mut store = MongoStore::new();
+    for team_id in [home, away] {
+        match store.get_participant(&team_id) {
+            Some(team) if team.team.is_some() => {}
+            Some(participant) => {
+                println!("Participant \"{}\" isn't a team.", participant.name);
+                return;
+            }
+            None => {
+                println!("Cannot find team with id \"{}\".", team_id);
+                return;
+            }
+        }
+    }
+
+    let tie = Tie::new(&round_id, &home, &away, format);
+    match store.create_tie(&tie) {
+        Ok(()) => println!("Created new tie: {}", tie.id),
+        Err(error) => println!("Cannot create the tie, {error}."),
+    }
+}
+
+/// Submit a team's lineup for a tie
+fn lineup(matches: &ArgMatches) {
+    let tie_id = TieId::from(get_id(matches, "ID"));
+    let team_id = ParticipantId::from(get_id(matches, "team"));
+    let rubbers: Vec<Vec<ParticipantId>> = matches
+        .get_many::<String>("rubber")
+        .unwrap_or_default()
+        .map(|players| {
+            players
+                .split(',')
+                .map(|player| {
+                    let player = Uuid::parse_str(player.trim()).expect("Invalid player id");
+                    ParticipantId::from(player)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut store = MongoStore::new();
+    let Some(mut tie) = store.get_tie(&tie_id) else {
+        println!("Cannot find tie with id \"{}\".", tie_id);
+        return;
+    };
+    let Some(team) = store.get_participant(&team_id) else {
+        println!("Cannot find team with id \"{}\".", team_id);
+        return;
+    };
+    let ratings = ratings::skills(&store.list_ratings(), RatingSystem::Elo);
+
+    match tie.submit_lineup(&team, rubbers, &ratings) {
+        Ok(()) => match store.update_tie(&tie) {
+            Ok(()) => println!("Submitted the lineup of {}", team.name),
+            Err(error) => println!("Cannot submit the lineup, {error}."),
+        },
+        Err(error) => println!("Cannot submit the lineup, {error}."),
+    }
+}
+
+/// Make the matches of a tie's rubbers
+fn start(matches: &ArgMatches) {
+    let tie_id = TieId::from(get_id(matches, "ID"));
+    let venue_id = VenueId::from(get_id(matches, "venue"));
+
+    let mut store = MongoStore::new();
+    let Some(mut tie) = store.get_tie(&tie_id) else {
+        println!("Cannot find tie with id \"{}\".", tie_id);
+        return;
+    };
+    let league = store
+        .get_round(&tie.round_id)
+        .and_then(|round| store.get_session(&round.session_id))
+        .and_then(|session| store.get_season(&session.season_id))
+        .and_then(|season| store.get_league(&season.league_id));
+    let Some(league) = league else {
+        println!("Cannot find the league of round \"{}\".", tie.round_id);
+        return;
+    };
+
+    match tie.matches(league.sport, &venue_id) {
+        Ok(rubbers) => match store.start_tie(&tie, &rubbers) {
+            Ok(()) => println!("Created {} rubbers for tie {}", rubbers.len(), tie.id),
+            Err(error) => println!("Cannot start the tie, {error}."),
+        },
+        Err(error) => println!("Cannot start the tie, {error}."),
+    }
+}
+
+/// Show the points table of the teams from their ties
+fn table(matches: &ArgMatches) {
+    let season_id = SeasonId::from(get_id(matches, "season"));
+
+    let store = MongoStore::new();
+    let Some(season) = store.get_season(&season_id) else {
+        println!("Cannot find season with id \"{}\".", season_id);
+        return;
+    };
+    let ties = store.list_ties_for_season(&season.id);
+    let rubbers = store.list_matches_for_season(&season.id);
+    let names: HashMap<ParticipantId, String> = store
+        .list_participants()
+        .into_iter()
+        .map(|participant| (participant.id, participant.name))
+        .collect();
+
+    let mut table = PointsTable::new();
+    for tie in &ties {
+        for team in [tie.home, tie.away] {
+            if table.entry(&team).is_none() {
+                if let Some(team) = store.get_participant(&team) {
+                    table.add_participant(&team);
+                }
+            }
+        }
+        table.record(&tie.details(&rubbers), &season.scoring);
+    }
+    // Each tie as a match between the two teams, for the tie-breakers which
+    // look at the results between teams.
+    let tie_matches: Vec<Match> = ties
+        .iter()
+        .filter_map(|tie| {
+            let venue_id = rubbers
+                .iter()
+                .find(|a_match| tie.rubbers.contains(&a_match.id))?
+                .venue_id;
+            let mut tie_match = Match::new(tie.round_id, venue_id);
+            tie_match.details = tie.details(&rubbers);
+            Some(tie_match)
+        })
+        .collect();
+
+    println!("Ties of {}:", season.name);
+    for tie in &ties {
+        let (home, away) = tie.rubbers_won(&rubbers);
+        let name = |id: &ParticipantId| names.get(id).cloned().unwrap_or_default();
+        println!(
+            "\t{} {} - {} {}",
+            name(&tie.home),
+            home,
+            away,
+            name(&tie.away)
+        );
+    }
+    println!("Table:");
+    for ranked in table.rank(&season.tie_breakers, &season.scoring, &tie_matches) {
+        let entry = &ranked.entry;
+        println!(
+            "\t{}. {}: {} played, {} points",
+            ranked.position,
+            entry.participant_name,
+            entry.played(),
+            entry.points
+        );
+    }
+}
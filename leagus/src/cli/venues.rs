@@ -0,0 +1,152 @@
+use bson::Uuid;
+use chrono::{NaiveTime, Weekday};
+use clap::{arg, ArgAction, ArgMatches, Command};
+use leagus::models::{Availability, Court, Surface, Venue, VenueId};
+use leagus::persistence::sync::{mongo_store::MongoStore, WriteableStore};
+
+pub const CMD_NAME: &str = "venues";
+
+pub fn commands() -> Command {
+    Command::new(CMD_NAME)
+        .about("Commands for managing venues and their courts")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create")
+                .about("Create a new venue")
+                .arg(
+                    arg!(
+                        -n --name <NAME> "Name of the new venue"
+                    )
+                    .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("add-court")
+                .about("Add a court, or table, to a venue")
+                .arg(arg!(<ID> "Id of the venue").required(true))
+                .arg(
+                    arg!(
+                        -n --name <NAME> "Name of the court, unique within the venue"
+                    )
+                    .required(true),
+                )
+                .arg(
+                    arg!(
+                        -s --surface <SURFACE> "What the court is made of"
+                    )
+                    .value_parser(["hard", "clay", "grass", "artificial-grass", "carpet", "wood"]),
+                )
+                .arg(
+                    arg!(
+                        -a --available <WINDOW> "A weekly window the court can be used, e.g. \"Tue 18:00-22:00\""
+                    )
+                    .action(ArgAction::Append),
+                ),
+        )
+        .subcommand(Command::new("list").about("List existing venues and their courts"))
+}
+
+/// Delegate subcommands of the venues command
+pub fn handle_subcommands(matches: &ArgMatches) {
+    match matches.subcommand() {
+        Some(("create", sub_matches)) => create(sub_matches),
+        Some(("add-court", sub_matches)) => add_court(sub_matches),
+        Some(("list", sub_matches)) => list(sub_matches),
+        _ => unreachable!("Must specify a subcommand"),
+    }
+}
+
+/// Create a new venue
+fn create(matches: &ArgMatches) {
+    let name = matches.get_one::<String>("name").expect("required");
+
+    let venue = Venue::new(name.clone());
+    let mut store = MongoStore::new();
+    store.create_venue(&venue);
+    println!("Created new venue: \"{}\" ({})", venue.name, venue.id);
+}
+
+/// Parse a weekly window such as "Tue 18:00-22:00"
+fn parse_availability(window: &str) -> Option<Availability> {
+    let (weekday, times) = window.trim().split_once(' ')?;
+    let (start, end) = times.trim().split_once('-')?;
+
+    let availability = Availability {
+        weekday: weekday.parse::<Weekday>().ok()?,
+        start: NaiveTime::parse_from_str(start.trim(), "%H:%M").ok()?,
+        end: NaiveTime::parse_from_str(end.trim(), "%H:%M").ok()?,
+    };
+    (availability.start < availability.end).then_some(availability)
+}
+
+/// Add a court to a venue
+fn add_court(matches: &ArgMatches) {
+    let venue_id = matches.get_one::<String>("ID").expect("required");
+    let venue_id = Uuid::parse_str(venue_id).expect("Invalid venue id");
+    let name = matches.get_one::<String>("name").expect("required");
+
+    let mut court = Court::new(name);
+    court.surface = matches
+        .get_one::<String>("surface")
+        .map(|surface| match surface.as_str() {
+            "hard" => Surface::Hard,
+            "clay" => Surface::Clay,
+            "grass" => Surface::Grass,
+            "artificial-grass" => Surface::ArtificialGrass,
+            "carpet" => Surface::Carpet,
+            _ => Surface::Wood,
+        });
+    for window in matches.get_many::<String>("available").unwrap_or_default() {
+        match parse_availability(window) {
+            Some(availability) => court.availability.push(availability),
+            None => {
+                println!(
+                    "Cannot read the window \"{}\", e.g. \"Tue 18:00-22:00\".",
+                    window
+                );
+                return;
+            }
+        }
+    }
+
+    let mut store = MongoStore::new();
+    let Some(mut venue) = store.get_venue(&VenueId::from(venue_id)) else {
+        println!("Cannot find venue with id \"{}\".", venue_id);
+        return;
+    };
+    if venue.court(name).is_some() {
+        println!(
+            "Venue \"{}\" already has a court named \"{}\".",
+            venue.name, name
+        );
+        return;
+    }
+
+    venue.courts.push(court);
+    store.update_venue(&venue);
+    println!("Added \"{}\" to {}", name, venue.name);
+}
+
+/// List all venues
+fn list(_matches: &ArgMatches) {
+    println!("Venues:");
+    let store = MongoStore::new();
+    for venue in store.list_venues() {
+        println!("- {} \n\tid: {}", venue.name, venue.id);
+        for court in &venue.courts {
+            let surface = court
+                .surface
+                .map(|surface| format!(" ({:?})", surface))
+                .unwrap_or_default();
+            println!("\t- {}{}", court.name, surface);
+            for window in &court.availability {
+                println!(
+                    "\t\t{} {}-{}",
+                    window.weekday,
+                    window.start.format("%H:%M"),
+                    window.end.format("%H:%M")
+                );
+            }
+        }
+    }
+}
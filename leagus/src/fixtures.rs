@@ -0,0 +1,43 @@
+pub mod groups;
+pub mod knockout;
+pub mod round_robin;
+
+use std::fmt::Display;
+
+use crate::models::{Match, ParticipantId, Round, Session, Sport};
+
+/// The sessions, rounds and matches generated for a season, ready to be
+/// persisted together.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Fixtures {
+    pub sessions: Vec<Session>,
+    pub rounds: Vec<Round>,
+    pub matches: Vec<Match>,
+}
+
+/// The reasons fixtures cannot be generated.
+#[derive(Debug, PartialEq, Clone)]
+pub enum FixtureError {
+    /// At least two participants are needed to play a match.
+    NotEnoughParticipants,
+    /// A participant was given more than once.
+    DuplicateParticipant(ParticipantId),
+    /// The sport has no match between two individual participants.
+    NoSingles(Sport),
+}
+
+impl Display for FixtureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FixtureError::NotEnoughParticipants => {
+                write!(f, "at least two participants are needed")
+            }
+            FixtureError::DuplicateParticipant(participant_id) => {
+                write!(f, "participant {participant_id} was given more than once")
+            }
+            FixtureError::NoSingles(sport) => write!(f, "{sport} is not played as singles"),
+        }
+    }
+}
+
+impl std::error::Error for FixtureError {}
@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+
+use crate::models::{
+    Group, GroupStage, Match, Participant, ParticipantId, Qualification, Round, Season, Session,
+    Sport, TieBreaker,
+};
+
+use super::round_robin::RoundRobin;
+use super::{FixtureError, Fixtures};
+
+/// Groups playing a round-robin each, ahead of a knockout.
+///
+/// The groups play on the same days, each session holding a round for every
+/// group with matches that day. Once the groups are done the qualifiers are
+/// drawn into a [`Knockout`](super::knockout::Knockout).
+#[derive(Debug, PartialEq, Clone)]
+pub struct Groups {
+    pub name: String,
+    /// The number of groups.
+    pub count: usize,
+    /// How each group's round-robin is played and scheduled.
+    pub round_robin: RoundRobin,
+    /// The tie-breakers used to rank each group.
+    pub tie_breakers: Vec<TieBreaker>,
+    pub qualification: Qualification,
+}
+
+impl Groups {
+    /// Draw the seeded participants into groups, snaking back and forth so
+    /// the top seeds are spread evenly. With three groups the seeds go to
+    /// groups A, B, C, C, B, A, A and so on.
+    pub fn draw<'a>(&self, seeds: &'a [Participant]) -> Vec<Vec<&'a Participant>> {
+        let mut groups = vec![Vec::new(); self.count];
+        for (index, participant) in seeds.iter().enumerate() {
+            let pass = index / self.count;
+            let position = index % self.count;
+            let group = match pass % 2 {
+                0 => position,
+                _ => self.count - 1 - position,
+            };
+            groups[group].push(participant);
+        }
+        groups
+    }
+
+    /// Draw the groups and generate the fixtures of their round-robins.
+    ///
+    /// Every group needs at least two participants.
+    pub fn generate(
+        &self,
+        season: &Season,
+        sport: Sport,
+        seeds: &[Participant],
+    ) -> Result<(GroupStage, Fixtures), FixtureError> {
+        if self.count == 0 || seeds.len() < self.count * 2 {
+            return Err(FixtureError::NotEnoughParticipants);
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = seeds.iter().find(|seed| !seen.insert(seed.id)) {
+            return Err(FixtureError::DuplicateParticipant(duplicate.id));
+        }
+
+        let mut groups = Vec::new();
+        let mut schedules = Vec::new();
+        for (index, drawn) in self.draw(seeds).into_iter().enumerate() {
+            let mut group = Group::new(&group_name(index), self.tie_breakers.clone());
+            for participant in &drawn {
+                group.table.add_participant(participant);
+            }
+            let ids: Vec<ParticipantId> = drawn.iter().map(|participant| participant.id).collect();
+            schedules.push(self.round_robin.pairings(&ids));
+            groups.push(group);
+        }
+
+        let mut fixtures = Fixtures::default();
+        let mut date = self.round_robin.start;
+        let days = schedules.iter().map(Vec::len).max().unwrap_or_default();
+
+        for day in 0..days {
+            let session = Session::new(&season.id, &date);
+
+            for (group, schedule) in groups.iter_mut().zip(&schedules) {
+                let Some(pairs) = schedule.get(day) else {
+                    continue;
+                };
+                let mut round = Round::new(session.id);
+
+                for (home, away) in pairs {
+                    let mut a_match = Match::new(round.id, self.round_robin.venue_id);
+                    a_match.details = sport
+                        .singles((*home, *away))
+                        .ok_or(FixtureError::NoSingles(sport))?;
+                    round.participants.extend([*home, *away]);
+                    fixtures.matches.push(a_match);
+                }
+
+                group.round_ids.push(round.id);
+                fixtures.rounds.push(round);
+            }
+
+            fixtures.sessions.push(session);
+            date += self.round_robin.interval;
+        }
+
+        let stage = GroupStage::new(&season.id, &self.name, groups, self.qualification);
+        Ok((stage, fixtures))
+    }
+}
+
+/// Groups are lettered, "Group A", "Group B" and so on, carrying on with
+/// numbers should there be more than 26.
+fn group_name(index: usize) -> String {
+    match u8::try_from(index) {
+        Ok(index) if index < 26 => format!("Group {}", char::from(b'A' + index)),
+        _ => format!("Group {}", index + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+
+    use crate::fixtures::round_robin::Legs;
+    use crate::models::{LeagueId, VenueId};
+
+    fn groups(count: usize) -> Groups {
+        Groups {
+            name: "Pools".to_string(),
+            count,
+            round_robin: RoundRobin {
+                legs: Legs::Single,
+                start: Utc.with_ymd_and_hms(2024, 1, 1, 18, 00, 00).unwrap(),
+                interval: TimeDelta::days(1),
+                venue_id: VenueId::new(),
+            },
+            tie_breakers: vec![TieBreaker::HeadToHead],
+            qualification: Qualification {
+                per_group: 2,
+                best_of_next: 0,
+            },
+        }
+    }
+
+    fn seeds(count: usize) -> Vec<Participant> {
+        (1..=count)
+            .map(|seed| Participant::new(format!("Seed {seed}")))
+            .collect()
+    }
+
+    #[test]
+    fn draw_snakes_the_seeds() {
+        let seeds = seeds(7);
+
+        let drawn: Vec<Vec<&str>> = groups(3)
+            .draw(&seeds)
+            .iter()
+            .map(|group| group.iter().map(|seed| seed.name.as_str()).collect())
+            .collect();
+
+        assert_eq!(
+            drawn,
+            [
+                vec!["Seed 1", "Seed 6", "Seed 7"],
+                vec!["Seed 2", "Seed 5"],
+                vec!["Seed 3", "Seed 4"],
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_rounds_for_each_group() {
+        let season = Season::new(&LeagueId::new(), &Utc::now(), &Utc::now(), "Cup");
+        let generator = groups(2);
+
+        let (stage, fixtures) = generator
+            .generate(&season, Sport::Chess, &seeds(7))
+            .unwrap();
+
+        // Snaking puts seeds 1, 4 and 5 in the first group, which plays
+        // over three days with a bye each day, as does the group of four.
+        assert_eq!(fixtures.sessions.len(), 3);
+        assert_eq!(fixtures.rounds.len(), 6);
+        assert_eq!(fixtures.matches.len(), 6 + 3);
+        assert_eq!(stage.groups[0].name, "Group A");
+        assert_eq!(stage.groups[0].table.entries.len(), 3);
+        assert_eq!(stage.groups[1].table.entries.len(), 4);
+
+        for a_match in &fixtures.matches {
+            let group = stage.group_for_round(&a_match.round_id).unwrap();
+            for participant_id in a_match.details.participants() {
+                assert!(group.table.entry(&participant_id).is_some());
+            }
+        }
+        for (round_id, session) in stage.groups[1].round_ids.iter().zip(&fixtures.sessions) {
+            let round = fixtures.rounds.iter().find(|round| round.id == *round_id);
+            assert_eq!(round.unwrap().session_id, session.id);
+        }
+    }
+
+    #[test]
+    fn every_group_needs_two_participants() {
+        let season = Season::new(&LeagueId::new(), &Utc::now(), &Utc::now(), "Cup");
+
+        assert_eq!(
+            groups(3).generate(&season, Sport::Chess, &seeds(5)),
+            Err(FixtureError::NotEnoughParticipants)
+        );
+        assert_eq!(
+            groups(0).generate(&season, Sport::Chess, &seeds(5)),
+            Err(FixtureError::NotEnoughParticipants)
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{
+    Bracket, BracketFormat, ParticipantId, RankedEntry, Round, Season, Session, Sport, VenueId,
+};
+use crate::ratings::SkillRatings;
+
+use super::{FixtureError, Fixtures};
+
+/// A knockout draw played on a single day, such as a season's finals.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Knockout {
+    pub name: String,
+    pub format: BracketFormat,
+    pub date: DateTime<Utc>,
+    /// Where the matches are played.
+    pub venue_id: VenueId,
+}
+
+impl Knockout {
+    /// Draw the bracket and create the session, with a single round, holding
+    /// the first matches. Later matches are added to the same round as the
+    /// bracket advances.
+    pub fn generate(
+        &self,
+        season: &Season,
+        sport: Sport,
+        seeds: Vec<ParticipantId>,
+    ) -> Result<(Bracket, Fixtures), FixtureError> {
+        if seeds.len() < 2 {
+            return Err(FixtureError::NotEnoughParticipants);
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = seeds.iter().find(|id| !seen.insert(**id)) {
+            return Err(FixtureError::DuplicateParticipant(*duplicate));
+        }
+        if !sport.has_singles() {
+            return Err(FixtureError::NoSingles(sport));
+        }
+
+        let session = Session::new(&season.id, &self.date);
+        let mut round = Round::new(session.id);
+        round.participants = seeds.clone();
+
+        let mut bracket = Bracket::new(&season.id, &self.name, sport, self.format, seeds);
+        let matches = bracket.schedule(&round.id, &self.venue_id);
+
+        let fixtures = Fixtures {
+            sessions: vec![session],
+            rounds: vec![round],
+            matches,
+        };
+        Ok((bracket, fixtures))
+    }
+}
+
+/// Seed participants by their position in the standings, taking the top
+/// `count` if given.
+pub fn seeds_from_standings(standings: &[RankedEntry], count: Option<usize>) -> Vec<ParticipantId> {
+    standings
+        .iter()
+        .take(count.unwrap_or(standings.len()))
+        .map(|ranked| ranked.entry.participant_id)
+        .collect()
+}
+
+/// Seed participants by their skill rating, highest first. Unrated
+/// participants are seeded last in the order given.
+pub fn seeds_from_ratings(
+    participants: &[ParticipantId],
+    ratings: &impl SkillRatings,
+) -> Vec<ParticipantId> {
+    let skill = |id: &ParticipantId| ratings.skill(id).unwrap_or(f64::NEG_INFINITY);
+
+    let mut seeds = participants.to_vec();
+    seeds.sort_by(|a, b| skill(b).total_cmp(&skill(a)));
+    seeds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    use crate::models::{BracketSection, LeagueId};
+
+    #[test]
+    fn seed_by_ratings() {
+        let (ann, bob, cat) = (
+            ParticipantId::new(),
+            ParticipantId::new(),
+            ParticipantId::new(),
+        );
+        let ratings = HashMap::from([(ann, 1400.0), (cat, 1600.0)]);
+
+        assert_eq!(
+            seeds_from_ratings(&[ann, bob, cat], &ratings),
+            [cat, ann, bob]
+        );
+    }
+
+    #[test]
+    fn generate_first_matches() {
+        let season = Season::new(&LeagueId::new(), &Utc::now(), &Utc::now(), "Summer");
+        let seeds: Vec<ParticipantId> = (0..5).map(|_| ParticipantId::new()).collect();
+        let knockout = Knockout {
+            name: "Finals".to_string(),
+            format: BracketFormat::SingleElimination {
+                third_place: true,
+                plate: false,
+            },
+            date: Utc::now(),
+            venue_id: VenueId::new(),
+        };
+
+        let (bracket, fixtures) = knockout
+            .generate(&season, Sport::Tennis, seeds.clone())
+            .unwrap();
+
+        // Only seeds 4 and 5 play in the first round, the rest have byes so
+        // seeds 2 and 3 can already play their semi-final.
+        assert_eq!(fixtures.sessions.len(), 1);
+        assert_eq!(fixtures.rounds[0].session_id, fixtures.sessions[0].id);
+        assert_eq!(fixtures.matches.len(), 2);
+        assert_eq!(
+            fixtures.matches[0].details.participants(),
+            [seeds[3], seeds[4]]
+        );
+        assert_eq!(
+            fixtures.matches[1].details.participants(),
+            [seeds[1], seeds[2]]
+        );
+        assert_eq!(fixtures.matches[0].round_id, fixtures.rounds[0].id);
+        assert!(bracket
+            .matches
+            .iter()
+            .any(|m| m.section == BracketSection::ThirdPlace));
+
+        assert_eq!(
+            knockout.generate(&season, Sport::Padel, seeds),
+            Err(FixtureError::NoSingles(Sport::Padel))
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::models::{Match, ParticipantId, Round, Season, Session, Sport, VenueId};
+
+use super::{FixtureError, Fixtures};
+
+/// How many times each pair of participants meet.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum Legs {
+    /// Everyone plays everyone once.
+    #[default]
+    Single,
+    /// Everyone plays everyone twice, once at home and once away.
+    Double,
+}
+
+/// A fixed round-robin, where every participant plays every other.
+///
+/// Pairings use the circle method: one participant stays put while the rest
+/// rotate around them, giving a new set of opponents each round. With an odd
+/// number of participants one of them sits out each round instead. The
+/// stationary participant alternates between home and away and everyone else
+/// swaps sides as they rotate, so nobody plays more than one extra match at
+/// home.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RoundRobin {
+    pub legs: Legs,
+    /// The date of the first session, each later one follows after `interval`.
+    pub start: DateTime<Utc>,
+    pub interval: TimeDelta,
+    /// Where the matches are played.
+    pub venue_id: VenueId,
+}
+
+impl RoundRobin {
+    /// Pair the participants for every round of the round-robin, each pair
+    /// given home side first. Participants with a bye are left out of the
+    /// round.
+    pub fn pairings(
+        &self,
+        participants: &[ParticipantId],
+    ) -> Vec<Vec<(ParticipantId, ParticipantId)>> {
+        // An empty slot stands in for the bye.
+        let mut circle: Vec<Option<ParticipantId>> =
+            participants.iter().copied().map(Some).collect();
+        if circle.len() % 2 == 1 {
+            circle.push(None);
+        }
+        let size = circle.len();
+
+        let mut rounds = Vec::new();
+        for round in 0..size.saturating_sub(1) {
+            let mut pairs = Vec::new();
+            for i in 0..size / 2 {
+                let (mut home, mut away) = (circle[i], circle[size - 1 - i]);
+                if i == 0 && round % 2 == 1 {
+                    (home, away) = (away, home);
+                }
+                if let (Some(home), Some(away)) = (home, away) {
+                    pairs.push((home, away));
+                }
+            }
+            rounds.push(pairs);
+
+            // Keep the first participant in place and rotate everyone else.
+            circle[1..].rotate_right(1);
+        }
+
+        if self.legs == Legs::Double {
+            let return_legs: Vec<Vec<(ParticipantId, ParticipantId)>> = rounds
+                .iter()
+                .map(|pairs| pairs.iter().map(|(home, away)| (*away, *home)).collect())
+                .collect();
+            rounds.extend(return_legs);
+        }
+
+        rounds
+    }
+
+    /// Generate the fixtures of a season, with a session holding a single
+    /// round for each round of the round-robin.
+    pub fn generate(
+        &self,
+        season: &Season,
+        sport: Sport,
+        participants: &[ParticipantId],
+    ) -> Result<Fixtures, FixtureError> {
+        if participants.len() < 2 {
+            return Err(FixtureError::NotEnoughParticipants);
+        }
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = participants.iter().find(|id| !seen.insert(**id)) {
+            return Err(FixtureError::DuplicateParticipant(*duplicate));
+        }
+
+        let mut fixtures = Fixtures::default();
+        let mut date = self.start;
+
+        for pairs in self.pairings(participants) {
+            let session = Session::new(&season.id, &date);
+            let mut round = Round::new(session.id);
+
+            for (home, away) in pairs {
+                let mut a_match = Match::new(round.id, self.venue_id);
+                a_match.details = sport
+                    .singles((home, away))
+                    .ok_or(FixtureError::NoSingles(sport))?;
+                round.participants.extend([home, away]);
+                fixtures.matches.push(a_match);
+            }
+
+            fixtures.sessions.push(session);
+            fixtures.rounds.push(round);
+            date += self.interval;
+        }
+
+        Ok(fixtures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    use crate::models::LeagueId;
+
+    fn round_robin(legs: Legs) -> RoundRobin {
+        RoundRobin {
+            legs,
+            start: Utc.with_ymd_and_hms(2024, 1, 1, 18, 00, 00).unwrap(),
+            interval: TimeDelta::weeks(1),
+            venue_id: VenueId::new(),
+        }
+    }
+
+    fn participants(count: usize) -> Vec<ParticipantId> {
+        (0..count).map(|_| ParticipantId::new()).collect()
+    }
+
+    /// Home matches minus away matches for each participant.
+    fn home_balance(rounds: &[Vec<(ParticipantId, ParticipantId)>]) -> HashMap<ParticipantId, i32> {
+        let mut balance = HashMap::new();
+        for (home, away) in rounds.iter().flatten() {
+            *balance.entry(*home).or_insert(0) += 1;
+            *balance.entry(*away).or_insert(0) -= 1;
+        }
+        balance
+    }
+
+    #[test]
+    fn everyone_plays_everyone_once() {
+        for count in 2..=9 {
+            let participants = participants(count);
+            let rounds = round_robin(Legs::Single).pairings(&participants);
+
+            let mut pairs = HashSet::new();
+            for (home, away) in rounds.iter().flatten() {
+                assert!(!pairs.contains(&(*away, *home)) && pairs.insert((*home, *away)));
+            }
+            assert_eq!(pairs.len(), count * (count - 1) / 2);
+
+            // Nobody plays twice in a round.
+            for round in &rounds {
+                let mut playing = HashSet::new();
+                for (home, away) in round {
+                    assert!(playing.insert(*home) && playing.insert(*away));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn odd_counts_give_one_bye_per_round() {
+        let participants = participants(5);
+        let rounds = round_robin(Legs::Single).pairings(&participants);
+
+        assert_eq!(rounds.len(), 5);
+        let mut byes = HashSet::new();
+        for round in &rounds {
+            assert_eq!(round.len(), 2);
+            let bye = participants
+                .iter()
+                .find(|id| !round.iter().any(|(home, away)| home == *id || away == *id))
+                .unwrap();
+            assert!(byes.insert(*bye));
+        }
+    }
+
+    #[test]
+    fn home_and_away_are_balanced() {
+        for count in 2..=10 {
+            let participants = participants(count);
+
+            let single = round_robin(Legs::Single).pairings(&participants);
+            let limit = if count % 2 == 0 { 1 } else { 0 };
+            assert!(home_balance(&single).values().all(|b| b.abs() <= limit));
+
+            let double = round_robin(Legs::Double).pairings(&participants);
+            assert_eq!(double.len(), single.len() * 2);
+            assert!(home_balance(&double).values().all(|b| *b == 0));
+        }
+    }
+
+    #[test]
+    fn generate_sessions_and_rounds() {
+        let season = Season::new(
+            &LeagueId::new(),
+            &Utc::now(),
+            &Utc::now(),
+            "Winter Round-Robin",
+        );
+        let participants = participants(4);
+        let generator = round_robin(Legs::Double);
+
+        let fixtures = generator
+            .generate(&season, Sport::Chess, &participants)
+            .unwrap();
+
+        assert_eq!(fixtures.sessions.len(), 6);
+        assert_eq!(fixtures.rounds.len(), 6);
+        assert_eq!(fixtures.matches.len(), 12);
+        assert_eq!(
+            fixtures.sessions[2].date,
+            generator.start + TimeDelta::weeks(2)
+        );
+        for (session, round) in fixtures.sessions.iter().zip(&fixtures.rounds) {
+            assert_eq!(session.season_id, season.id);
+            assert_eq!(round.session_id, session.id);
+            assert_eq!(round.participants.len(), 4);
+        }
+        for a_match in &fixtures.matches {
+            assert_eq!(a_match.details.sport(), Some(Sport::Chess));
+            assert_eq!(a_match.venue_id, generator.venue_id);
+        }
+    }
+
+    #[test]
+    fn invalid_participants() {
+        let season = Season::new(&LeagueId::new(), &Utc::now(), &Utc::now(), "");
+        let generator = round_robin(Legs::Single);
+        let ann = ParticipantId::new();
+        let bob = ParticipantId::new();
+
+        assert_eq!(
+            generator.generate(&season, Sport::Tennis, &[ann]),
+            Err(FixtureError::NotEnoughParticipants)
+        );
+        assert_eq!(
+            generator.generate(&season, Sport::Tennis, &[ann, bob, ann]),
+            Err(FixtureError::DuplicateParticipant(ann))
+        );
+        assert_eq!(
+            generator.generate(&season, Sport::Padel, &[ann, bob]),
+            Err(FixtureError::NoSingles(Sport::Padel))
+        );
+    }
+}
@@ -1,2 +1,6 @@
+pub mod calendar;
+pub mod fixtures;
+pub mod match_making;
 pub mod models;
 pub mod persistence;
+pub mod ratings;
@@ -1,7 +1,10 @@
 mod cli;
 
 use clap::command;
-use cli::{database, leagues, participants, seasons, sessions};
+use cli::{
+    brackets, calendars, database, groups, handicaps, ladders, leagues, matches, participants,
+    ratings, seasons, sessions, teams, ties, venues,
+};
 
 /// The CLI interface for Leagus
 fn main() {
@@ -13,7 +16,17 @@ fn main() {
         .subcommand(leagues::commands())
         .subcommand(seasons::commands())
         .subcommand(sessions::commands())
+        .subcommand(matches::commands())
         .subcommand(participants::commands())
+        .subcommand(ratings::commands())
+        .subcommand(handicaps::commands())
+        .subcommand(brackets::commands())
+        .subcommand(groups::commands())
+        .subcommand(ladders::commands())
+        .subcommand(venues::commands())
+        .subcommand(teams::commands())
+        .subcommand(ties::commands())
+        .subcommand(calendars::commands())
         .get_matches();
 
     match matches.subcommand() {
@@ -21,9 +34,19 @@ fn main() {
         Some((leagues::CMD_NAME, sub_matches)) => leagues::handle_subcommands(sub_matches),
         Some((seasons::CMD_NAME, sub_matches)) => seasons::handle_subcommands(sub_matches),
         Some((sessions::CMD_NAME, sub_matches)) => sessions::handle_subcommands(sub_matches),
+        Some((matches::CMD_NAME, sub_matches)) => matches::handle_subcommands(sub_matches),
         Some((participants::CMD_NAME, sub_matches)) => {
             participants::handle_subcommands(sub_matches)
         }
+        Some((ratings::CMD_NAME, sub_matches)) => ratings::handle_subcommands(sub_matches),
+        Some((handicaps::CMD_NAME, sub_matches)) => handicaps::handle_subcommands(sub_matches),
+        Some((brackets::CMD_NAME, sub_matches)) => brackets::handle_subcommands(sub_matches),
+        Some((groups::CMD_NAME, sub_matches)) => groups::handle_subcommands(sub_matches),
+        Some((ladders::CMD_NAME, sub_matches)) => ladders::handle_subcommands(sub_matches),
+        Some((venues::CMD_NAME, sub_matches)) => venues::handle_subcommands(sub_matches),
+        Some((teams::CMD_NAME, sub_matches)) => teams::handle_subcommands(sub_matches),
+        Some((ties::CMD_NAME, sub_matches)) => ties::handle_subcommands(sub_matches),
+        Some((calendars::CMD_NAME, sub_matches)) => calendars::handle_subcommands(sub_matches),
         _ => unreachable!("Exhausted list of subcommands and subcommand_required prevents `None`"),
     }
 }
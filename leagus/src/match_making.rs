@@ -0,0 +1,279 @@
+pub mod doubles;
+pub mod swiss;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{
+    Match, MatchMakingStrategy, ParticipantId, PointsTable, Round, RoundId, ScoringSystem,
+    SessionId, Side, Sport, Venue, VenueId,
+};
+
+/// Two participants playing together on the same side.
+pub type Team = (ParticipantId, ParticipantId);
+
+/// The participants of a round paired into matches.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Pairings {
+    /// Each pair of opponents, home side first.
+    pub pairs: Vec<(ParticipantId, ParticipantId)>,
+    /// Each pair of opposing teams in doubles matches, home team first.
+    pub doubles: Vec<(Team, Team)>,
+    /// The participants left without a match this round, to be recorded as
+    /// the round's sit-outs.
+    pub byes: Vec<ParticipantId>,
+}
+
+/// The season so far, which match-making strategies draw on to pair the next
+/// round.
+#[derive(Debug, Clone, Copy)]
+pub struct SeasonHistory<'a> {
+    pub table: &'a PointsTable,
+    pub scoring: &'a ScoringSystem,
+    /// The rounds already played in the season.
+    pub rounds: &'a [Round],
+    /// The matches of those rounds.
+    pub matches: &'a [Match],
+    /// The skill of each rated participant, used to balance teams.
+    pub skills: &'a HashMap<ParticipantId, f64>,
+    /// The participants registered for the season, the only ones who are
+    /// paired, or `None` to pair anyone in the round.
+    pub roster: Option<&'a [ParticipantId]>,
+}
+
+impl MatchMakingStrategy {
+    /// Pair the participants of a round, or `None` if the round's matches are
+    /// made by hand.
+    pub fn pair(&self, round: &Round, history: &SeasonHistory) -> Option<Pairings> {
+        match self {
+            MatchMakingStrategy::Manual => None,
+            MatchMakingStrategy::Swiss => Some(swiss::pair(round, history)),
+            MatchMakingStrategy::RotatingDoubles => Some(doubles::pair(round, history)),
+        }
+    }
+}
+
+/// Why matches could not all be given a court.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CourtError {
+    /// There were more matches than courts free.
+    NotEnoughCourts { matches: usize, free: usize },
+}
+
+impl Display for CourtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CourtError::NotEnoughCourts { matches, free } => {
+                write!(f, "{matches} matches but only {free} courts free")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CourtError {}
+
+impl Round {
+    /// Pair the participants of the round with its strategy, recording who
+    /// sat out. Participants not on the season's roster are left out.
+    /// Returns `None`, leaving the round as it is, if the round's matches are
+    /// made by hand.
+    pub fn pair(&mut self, history: &SeasonHistory) -> Option<Pairings> {
+        let mut registered = self.clone();
+        if let Some(roster) = history.roster {
+            registered.participants.retain(|id| roster.contains(id));
+        }
+        let pairings = self.strategy.pair(&registered, history)?;
+        self.sit_outs = pairings.byes.clone();
+        Some(pairings)
+    }
+
+    /// Limit the round to as many matches as the venue has courts free at the
+    /// time, keeping any lower capacity the round already has. Venues without
+    /// courts don't limit the round.
+    pub fn fit_courts(&mut self, venue: &Venue, at: &DateTime<Utc>) {
+        if let Some(free) = venue.capacity(at) {
+            self.capacity = Some(self.capacity.map_or(free, |capacity| capacity.min(free)));
+        }
+    }
+
+    /// How many of the participants can play in the round, given the number
+    /// of participants in each match.
+    pub fn places(&self, per_match: usize) -> usize {
+        let matches = self.participants.len() / per_match;
+        self.capacity.unwrap_or(matches).min(matches) * per_match
+    }
+}
+
+/// Put each match on a court of the venue which is available at the time.
+///
+/// Courts already taken by other matches of the round are left alone, so no
+/// court is booked twice in a round. Matches already on a court keep it and
+/// nothing is changed unless every match can be given a court. Venues without
+/// courts are left to sort themselves out.
+pub fn assign_courts(
+    matches: &mut [Match],
+    venue: &Venue,
+    at: &DateTime<Utc>,
+    round_matches: &[Match],
+) -> Result<(), CourtError> {
+    if venue.courts.is_empty() {
+        return Ok(());
+    }
+
+    let taken: HashSet<&str> = round_matches
+        .iter()
+        .chain(matches.iter())
+        .filter(|a_match| a_match.venue_id == venue.id)
+        .filter_map(|a_match| a_match.court.as_deref())
+        .collect();
+    let free: Vec<String> = venue
+        .courts_available(at)
+        .into_iter()
+        .filter(|court| !taken.contains(court.name.as_str()))
+        .map(|court| court.name.clone())
+        .collect();
+
+    let waiting = matches
+        .iter()
+        .filter(|a_match| a_match.court.is_none())
+        .count();
+    if waiting > free.len() {
+        return Err(CourtError::NotEnoughCourts {
+            matches: waiting,
+            free: free.len(),
+        });
+    }
+
+    for (a_match, court) in matches
+        .iter_mut()
+        .filter(|a_match| a_match.court.is_none())
+        .zip(free)
+    {
+        a_match.court = Some(court);
+    }
+    Ok(())
+}
+
+/// Choose who sits out a round.
+///
+/// Nobody sits out twice in a session before everyone in the round has sat
+/// out once: those who have sat out the fewest rounds of the session are
+/// chosen first, then those with the fewest byes this season. Remaining ties
+/// go to the candidates in the order given.
+pub fn sit_outs(
+    candidates: &[ParticipantId],
+    count: usize,
+    session_id: &SessionId,
+    history: &SeasonHistory,
+) -> Vec<ParticipantId> {
+    let in_session = history.sit_outs_in_session(session_id);
+    let in_season = history.bye_counts();
+
+    let mut order = candidates.to_vec();
+    order.sort_by_key(|id| {
+        (
+            in_session.get(id).copied().unwrap_or_default(),
+            in_season.get(id).copied().unwrap_or_default(),
+        )
+    });
+    order.truncate(count);
+    order
+}
+
+impl Pairings {
+    /// Create a match in the round for each pair and each pair of teams, or
+    /// `None` if the sport is not played as singles or doubles as needed.
+    pub fn matches(
+        &self,
+        round_id: &RoundId,
+        sport: Sport,
+        venue_id: &VenueId,
+    ) -> Option<Vec<Match>> {
+        let singles = self.pairs.iter().map(|players| sport.singles(*players));
+        let doubles = self.doubles.iter().map(|teams| sport.doubles(*teams));
+
+        singles
+            .chain(doubles)
+            .map(|details| {
+                let mut a_match = Match::new(*round_id, *venue_id);
+                a_match.details = details?;
+                Some(a_match)
+            })
+            .collect()
+    }
+}
+
+impl SeasonHistory<'_> {
+    /// Whether two participants have already met, as opponents, this season.
+    pub fn have_met(&self, a: &ParticipantId, b: &ParticipantId) -> bool {
+        self.matches.iter().any(|a_match| {
+            let details = &a_match.details;
+            match (details.side_of(a), details.side_of(b)) {
+                (Some(side_a), Some(side_b)) => side_a != side_b,
+                _ => false,
+            }
+        })
+    }
+
+    /// Everyone who was in a round without being given a match.
+    pub fn byes(&self) -> HashSet<ParticipantId> {
+        self.bye_counts().into_keys().collect()
+    }
+
+    /// How many rounds each participant was in without being given a match.
+    /// Participants who have always played, or who are not on the roster, are
+    /// left out.
+    pub fn bye_counts(&self) -> HashMap<ParticipantId, usize> {
+        let mut playing: HashMap<RoundId, HashSet<ParticipantId>> = HashMap::new();
+        for a_match in self.matches {
+            playing
+                .entry(a_match.round_id)
+                .or_default()
+                .extend(a_match.details.participants());
+        }
+
+        self.rounds
+            .iter()
+            .flat_map(|round| {
+                let playing = playing.get(&round.id);
+                round
+                    .participants
+                    .iter()
+                    .filter(move |id| !playing.is_some_and(|playing| playing.contains(id)))
+                    .filter(|id| self.roster.is_none_or(|roster| roster.contains(id)))
+            })
+            .fold(HashMap::new(), |mut counts, id| {
+                *counts.entry(*id).or_default() += 1;
+                counts
+            })
+    }
+
+    /// How many rounds of the session each participant has sat out.
+    pub fn sit_outs_in_session(&self, session_id: &SessionId) -> HashMap<ParticipantId, usize> {
+        let mut counts = HashMap::new();
+        for round in self
+            .rounds
+            .iter()
+            .filter(|round| round.session_id == *session_id)
+        {
+            for id in &round.sit_outs {
+                *counts.entry(*id).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Matches played at home minus those played away, for a participant.
+    pub fn home_balance(&self, participant_id: &ParticipantId) -> i32 {
+        self.matches
+            .iter()
+            .filter_map(|a_match| a_match.details.side_of(participant_id))
+            .map(|side| match side {
+                Side::Home => 1,
+                Side::Away => -1,
+            })
+            .sum()
+    }
+}
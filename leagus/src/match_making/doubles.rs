@@ -0,0 +1,589 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::models::{ParticipantId, Round, RoundId, SessionId};
+
+use super::{sit_outs, Pairings, SeasonHistory, Team};
+
+/// The cost of partnering someone again within the same session.
+const SESSION_PARTNER: f64 = 100.0;
+/// The cost of partnering someone again from earlier in the season.
+const SEASON_PARTNER: f64 = 10.0;
+/// The cost of facing someone again within the same session.
+const SESSION_OPPONENT: f64 = 20.0;
+/// The cost of facing someone again from earlier in the season.
+const SEASON_OPPONENT: f64 = 2.0;
+/// The cost of each point of skill between the two teams of a match.
+const IMBALANCE: f64 = 0.05;
+
+/// Pair the participants of a round into doubles matches with partners
+/// rotated.
+///
+/// When the participants don't divide into fours, or there are more fours
+/// than the round has room for, some sit out as chosen by [`sit_outs`],
+/// favouring those who have played the most and then those listed last. The
+/// rest are first grouped by skill, the
+/// strongest and weakest of each four against the middle two, and then
+/// players are swapped between places while that lowers the cost of the
+/// pairings: repeated partners cost the most, then repeated opponents, more
+/// so within the same session than from earlier in the season, and then any
+/// difference in skill between the teams. Unrated participants are taken to
+/// be of average skill.
+pub fn pair(round: &Round, history: &SeasonHistory) -> Pairings {
+    let played = played_counts(history);
+    let mut candidates = round.participants.clone();
+    candidates.reverse();
+    candidates.sort_by_key(|id| Reverse(played.get(id).copied().unwrap_or_default()));
+    let count = round.participants.len() - round.places(4);
+    let byes = sit_outs(&candidates, count, &round.session_id, history);
+
+    let mut order: Vec<ParticipantId> = round
+        .participants
+        .iter()
+        .filter(|id| !byes.contains(id))
+        .copied()
+        .collect();
+
+    let costs = Costs::new(&round.session_id, &order, history);
+    order.sort_by(|a, b| costs.skill(b).total_cmp(&costs.skill(a)));
+    let mut places: Vec<ParticipantId> = order
+        .chunks(4)
+        .flat_map(|four| [four[0], four[3], four[1], four[2]])
+        .collect();
+    improve(&mut places, &costs);
+
+    let doubles = places
+        .chunks(4)
+        .map(|four| ((four[0], four[1]), (four[2], four[3])))
+        .collect();
+
+    Pairings {
+        doubles,
+        byes,
+        ..Default::default()
+    }
+}
+
+/// Swap players between places while any swap lowers the total cost.
+fn improve(places: &mut [ParticipantId], costs: &Costs) {
+    let match_cost = |places: &[ParticipantId], index: usize| {
+        let four = &places[index * 4..index * 4 + 4];
+        costs.of_match(&(four[0], four[1]), &(four[2], four[3]))
+    };
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for a in 0..places.len() {
+            for b in a + 1..places.len() {
+                let (match_a, match_b) = (a / 4, b / 4);
+                let before = match match_a == match_b {
+                    true => match_cost(places, match_a),
+                    false => match_cost(places, match_a) + match_cost(places, match_b),
+                };
+                places.swap(a, b);
+                let after = match match_a == match_b {
+                    true => match_cost(places, match_a),
+                    false => match_cost(places, match_a) + match_cost(places, match_b),
+                };
+                // Only keep swaps which make a real difference, so rounding
+                // can't keep the search going.
+                if after < before - f64::EPSILON {
+                    improved = true;
+                } else {
+                    places.swap(a, b);
+                }
+            }
+        }
+    }
+}
+
+/// The number of matches each participant has played this season.
+fn played_counts(history: &SeasonHistory) -> HashMap<ParticipantId, usize> {
+    let mut counts = HashMap::new();
+    for a_match in history.matches {
+        for id in a_match.details.participants() {
+            *counts.entry(id).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// The cost of putting participants together, as partners or opponents, given
+/// who has played with and against whom.
+struct Costs<'a> {
+    /// Cost of each pair having played together, keyed in id order.
+    partners: HashMap<(ParticipantId, ParticipantId), f64>,
+    /// Cost of each pair having played against each other, keyed in id order.
+    opponents: HashMap<(ParticipantId, ParticipantId), f64>,
+    skills: &'a HashMap<ParticipantId, f64>,
+    average_skill: f64,
+}
+
+impl<'a> Costs<'a> {
+    fn new(
+        session_id: &SessionId,
+        participants: &[ParticipantId],
+        history: &SeasonHistory<'a>,
+    ) -> Costs<'a> {
+        let sessions: HashMap<RoundId, SessionId> = history
+            .rounds
+            .iter()
+            .map(|round| (round.id, round.session_id))
+            .collect();
+
+        let mut partners = HashMap::new();
+        let mut opponents = HashMap::new();
+        for a_match in history.matches {
+            let Some((home, away)) = a_match.details.sides() else {
+                continue;
+            };
+            let this_session = sessions.get(&a_match.round_id) == Some(session_id);
+            let (partner_cost, opponent_cost) = match this_session {
+                true => (SESSION_PARTNER, SESSION_OPPONENT),
+                false => (SEASON_PARTNER, SEASON_OPPONENT),
+            };
+
+            for side in [&home, &away] {
+                for (index, a) in side.iter().enumerate() {
+                    for b in &side[index + 1..] {
+                        *partners.entry(key(a, b)).or_default() += partner_cost;
+                    }
+                }
+            }
+            for a in &home {
+                for b in &away {
+                    *opponents.entry(key(a, b)).or_default() += opponent_cost;
+                }
+            }
+        }
+
+        let rated: Vec<f64> = participants
+            .iter()
+            .filter_map(|id| history.skills.get(id).copied())
+            .collect();
+        let average_skill = match rated.is_empty() {
+            true => 0.0,
+            false => rated.iter().sum::<f64>() / rated.len() as f64,
+        };
+
+        Costs {
+            partners,
+            opponents,
+            skills: history.skills,
+            average_skill,
+        }
+    }
+
+    fn skill(&self, id: &ParticipantId) -> f64 {
+        self.skills.get(id).copied().unwrap_or(self.average_skill)
+    }
+
+    fn of_match(&self, home: &Team, away: &Team) -> f64 {
+        let partners: f64 = [home, away]
+            .iter()
+            .filter_map(|(a, b)| self.partners.get(&key(a, b)))
+            .sum();
+        let opponents: f64 = [home.0, home.1]
+            .iter()
+            .flat_map(|a| [away.0, away.1].map(|b| key(a, &b)))
+            .filter_map(|pair| self.opponents.get(&pair))
+            .sum();
+        let imbalance =
+            (self.skill(&home.0) + self.skill(&home.1) - self.skill(&away.0) - self.skill(&away.1))
+                .abs();
+
+        partners + opponents + imbalance * IMBALANCE
+    }
+}
+
+/// A pair of participants in a consistent order.
+fn key(a: &ParticipantId, b: &ParticipantId) -> (ParticipantId, ParticipantId) {
+    match a.id.bytes() <= b.id.bytes() {
+        true => (*a, *b),
+        false => (*b, *a),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    use crate::match_making::{assign_courts, CourtError};
+    use crate::models::{
+        Court, Match, MatchDetails, MatchMakingStrategy, Participant, PointsTable, ScoringSystem,
+        Sport, Venue, VenueId,
+    };
+    use chrono::Utc;
+
+    fn players(names: &[&str]) -> Vec<Participant> {
+        names
+            .iter()
+            .map(|name| Participant::new(name.to_string()))
+            .collect()
+    }
+
+    fn round(session_id: SessionId, participants: &[Participant]) -> Round {
+        let mut round = Round::new(session_id);
+        round.participants = participants
+            .iter()
+            .map(|participant| participant.id)
+            .collect();
+        round
+    }
+
+    fn doubles(
+        round: &Round,
+        home: (&Participant, &Participant),
+        away: (&Participant, &Participant),
+    ) -> Match {
+        let mut a_match = Match::new(round.id, VenueId::new());
+        a_match.details = MatchDetails::TennisDoubles {
+            players: ((home.0.id, home.1.id), (away.0.id, away.1.id)),
+            scores: Vec::new(),
+        };
+        a_match
+    }
+
+    /// The names of each team, sorted so the order of partners and of the
+    /// teams in a match doesn't matter.
+    fn teams(pairings: &Pairings, participants: &[Participant]) -> Vec<Vec<Vec<String>>> {
+        let name = |id: &ParticipantId| {
+            participants
+                .iter()
+                .find(|participant| participant.id == *id)
+                .map(|participant| participant.name.clone())
+                .unwrap()
+        };
+        pairings
+            .doubles
+            .iter()
+            .map(|(home, away)| {
+                let mut teams: Vec<Vec<String>> = [home, away]
+                    .iter()
+                    .map(|(a, b)| {
+                        let mut team = vec![name(a), name(b)];
+                        team.sort();
+                        team
+                    })
+                    .collect();
+                teams.sort();
+                teams
+            })
+            .collect()
+    }
+
+    fn team(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn skills(participants: &[Participant], ratings: &[f64]) -> HashMap<ParticipantId, f64> {
+        participants
+            .iter()
+            .zip(ratings)
+            .map(|(participant, rating)| (participant.id, *rating))
+            .collect()
+    }
+
+    #[test]
+    fn balance_teams_by_skill() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let skills = skills(&participants, &[1800.0, 1600.0, 1400.0, 1200.0]);
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &skills,
+            roster: None,
+        };
+
+        let pairings = pair(&round(SessionId::new(), &participants), &history);
+
+        assert_eq!(
+            teams(&pairings, &participants),
+            [[team(&["Ann", "Dan"]), team(&["Bob", "Cat"])]]
+        );
+        assert!(pairings.byes.is_empty());
+    }
+
+    #[test]
+    fn rotate_partners_within_a_session() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let [ann, bob, cat, dan] = participants.clone().try_into().unwrap();
+        let skills = skills(&participants, &[1800.0, 1600.0, 1400.0, 1200.0]);
+        let session_id = SessionId::new();
+        let first = round(session_id, &participants);
+        let second = round(session_id, &participants);
+        let matches = [
+            doubles(&first, (&ann, &dan), (&bob, &cat)),
+            doubles(&second, (&ann, &cat), (&bob, &dan)),
+        ];
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let rounds = [first, second];
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &skills,
+            roster: None,
+        };
+
+        // The strongest pair is the only partnership not yet played tonight.
+        let pairings = pair(&round(session_id, &participants), &history);
+
+        assert_eq!(
+            teams(&pairings, &participants),
+            [[team(&["Ann", "Bob"]), team(&["Cat", "Dan"])]]
+        );
+    }
+
+    #[test]
+    fn skill_outweighs_partners_from_earlier_sessions() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let [ann, bob, cat, dan] = participants.clone().try_into().unwrap();
+        let skills = skills(&participants, &[1800.0, 1600.0, 1400.0, 1200.0]);
+        let earlier = round(SessionId::new(), &participants);
+        let matches = [doubles(&earlier, (&ann, &dan), (&bob, &cat))];
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let rounds = [earlier];
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &skills,
+            roster: None,
+        };
+
+        // Ann and Cat against Bob and Dan is a little uneven, but better than
+        // repeating last week's partners.
+        let pairings = pair(&round(SessionId::new(), &participants), &history);
+
+        assert_eq!(
+            teams(&pairings, &participants),
+            [[team(&["Ann", "Cat"]), team(&["Bob", "Dan"])]]
+        );
+    }
+
+    #[test]
+    fn sit_outs_go_to_those_who_have_sat_out_least() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve", "Fay"]);
+        let [ann, bob, cat, dan, eve, fay] = participants.clone().try_into().unwrap();
+        let session_id = SessionId::new();
+        let mut first = round(session_id, &participants);
+        // Eve and Fay sat out the first round.
+        first.sit_outs = vec![eve.id, fay.id];
+        let matches = [doubles(&first, (&ann, &bob), (&cat, &dan))];
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let rounds = [first];
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: None,
+        };
+
+        let pairings = pair(&round(session_id, &participants), &history);
+
+        assert_eq!(pairings.byes, [dan.id, cat.id]);
+        let playing: Vec<ParticipantId> = pairings
+            .doubles
+            .iter()
+            .flat_map(|((a, b), (c, d))| [*a, *b, *c, *d])
+            .collect();
+        for id in [ann.id, bob.id, eve.id, fay.id] {
+            assert!(playing.contains(&id));
+        }
+        // Ann and Bob partnered in the first round so are split up.
+        assert_eq!(
+            teams(&pairings, &participants)[0]
+                .iter()
+                .filter(|team| **team == ["Ann", "Bob"])
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn only_the_roster_is_paired_or_given_byes() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve", "Gus"]);
+        let [ann, bob, cat, dan, eve, gus] = participants.clone().try_into().unwrap();
+        let roster = [ann.id, bob.id, cat.id, dan.id, eve.id];
+        // Gus turned up to the first round without being registered.
+        let first = round(SessionId::new(), &participants);
+        let matches = [doubles(&first, (&ann, &bob), (&cat, &dan))];
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let rounds = [first];
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: Some(&roster),
+        };
+
+        assert_eq!(history.bye_counts(), HashMap::from([(eve.id, 1)]));
+
+        let mut next = round(SessionId::new(), &participants);
+        next.strategy = MatchMakingStrategy::RotatingDoubles;
+        let pairings = next.pair(&history).unwrap();
+
+        // Eve had a bye last time so plays, and Gus is left out altogether.
+        assert_eq!(next.sit_outs.len(), 1);
+        assert_ne!(next.sit_outs, [eve.id]);
+        assert!(!next.sit_outs.contains(&gus.id));
+        assert!(!pairings
+            .doubles
+            .iter()
+            .any(|((a, b), (c, d))| [a, b, c, d].contains(&&gus.id)));
+    }
+
+    #[test]
+    fn nobody_sits_out_twice_before_everyone_has_once() {
+        let names: Vec<String> = (1..=14).map(|number| format!("Player {number}")).collect();
+        let participants: Vec<Participant> = names
+            .iter()
+            .map(|name| Participant::new(name.clone()))
+            .collect();
+        let session_id = SessionId::new();
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let mut rounds: Vec<Round> = Vec::new();
+        let mut matches: Vec<Match> = Vec::new();
+
+        // 14 players on 3 courts, two sit out each round.
+        for _ in 0..7 {
+            let mut next = round(session_id, &participants);
+            next.capacity = Some(3);
+            next.strategy = MatchMakingStrategy::RotatingDoubles;
+            let history = SeasonHistory {
+                table: &table,
+                scoring: &scoring,
+                rounds: &rounds,
+                matches: &matches,
+                skills: &HashMap::new(),
+                roster: None,
+            };
+
+            let pairings = next.pair(&history).unwrap();
+            assert_eq!(pairings.doubles.len(), 3);
+            assert_eq!(next.sit_outs.len(), 2);
+
+            matches.extend(
+                pairings
+                    .matches(&next.id, Sport::Tennis, &VenueId::new())
+                    .unwrap(),
+            );
+            rounds.push(next);
+        }
+
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let sat_out = history.sit_outs_in_session(&session_id);
+        for participant in &participants {
+            assert_eq!(
+                sat_out.get(&participant.id),
+                Some(&1),
+                "{}",
+                participant.name
+            );
+        }
+    }
+
+    #[test]
+    fn rotating_doubles_rounds_create_doubles_matches() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve"]);
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let mut round = round(SessionId::new(), &participants);
+        round.strategy = MatchMakingStrategy::RotatingDoubles;
+
+        let pairings = round.pair(&history).unwrap();
+        assert_eq!(pairings.byes.len(), 1);
+        assert_eq!(round.sit_outs, pairings.byes);
+        assert!(pairings.pairs.is_empty());
+
+        let venue_id = VenueId::new();
+        let matches = pairings
+            .matches(&round.id, Sport::Tennis, &venue_id)
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert!(matches!(
+            matches[0].details,
+            MatchDetails::TennisDoubles { .. }
+        ));
+        assert_eq!(pairings.matches(&round.id, Sport::Chess, &venue_id), None);
+    }
+
+    #[test]
+    fn courts_are_not_double_booked_in_a_round() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve", "Fay", "Gus", "Hal"]);
+        let mut venue = Venue::new("Club".to_string());
+        venue.courts = vec![Court::new("Court 1"), Court::new("Court 2")];
+        let now = Utc::now();
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let mut round = round(SessionId::new(), &participants);
+        round.capacity = Some(3);
+        round.fit_courts(&venue, &now);
+        assert_eq!(round.capacity, Some(2), "only two courts are free");
+
+        let pairings = pair(&round, &history);
+        let mut matches = pairings
+            .matches(&round.id, Sport::Tennis, &venue.id)
+            .unwrap();
+        // Court 1 is already taken by a match of the round.
+        let mut earlier = Match::new(round.id, venue.id);
+        earlier.court = Some("Court 1".to_string());
+
+        assert_eq!(
+            assign_courts(&mut matches, &venue, &now, &[earlier.clone()]),
+            Err(CourtError::NotEnoughCourts {
+                matches: 2,
+                free: 1
+            })
+        );
+        assert!(matches.iter().all(|a_match| a_match.court.is_none()));
+
+        assert_eq!(assign_courts(&mut matches, &venue, &now, &[]), Ok(()));
+        let courts: Vec<_> = matches
+            .iter()
+            .map(|a_match| a_match.court.as_deref())
+            .collect();
+        assert_eq!(courts, [Some("Court 1"), Some("Court 2")]);
+    }
+}
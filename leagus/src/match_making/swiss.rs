@@ -0,0 +1,509 @@
+use std::cmp::Reverse;
+
+use crate::models::{buchholz, sonneborn_berger, ParticipantId, Round};
+
+use super::{sit_outs, Pairings, SeasonHistory};
+
+/// Pair the participants using the Swiss system.
+///
+/// Participants are ordered by their points in the table, with ties broken by
+/// Buchholz and then Sonneborn-Berger, and each in turn is paired with the
+/// highest placed participant they have not yet met. Should that leave
+/// someone without an opponent, earlier pairings are revisited. If no pairing
+/// avoids every rematch, as can happen late in a long event, or finding one
+/// takes too long, each is paired with the highest placed they have not met
+/// where one is left, keeping rematches few.
+///
+/// With an odd number of participants, or more pairs than the round has room
+/// for, some sit out as chosen by [`sit_outs`], favouring the lowest placed.
+/// The participant who has played more at home plays away, otherwise the
+/// higher placed participant is at home.
+pub fn pair(round: &Round, history: &SeasonHistory) -> Pairings {
+    let mut order = standings(&round.participants, history);
+
+    let mut candidates = order.clone();
+    candidates.reverse();
+    let count = order.len() - round.places(2);
+    let byes = sit_outs(&candidates, count, &round.session_id, history);
+    order.retain(|id| !byes.contains(id));
+
+    let mut steps = MAX_STEPS;
+    let pairs = pair_up(&order, history, &mut steps)
+        .unwrap_or_else(|| pair_greedily(&order, history))
+        .into_iter()
+        .map(|(higher, lower)| {
+            if history.home_balance(&higher) > history.home_balance(&lower) {
+                (lower, higher)
+            } else {
+                (higher, lower)
+            }
+        })
+        .collect();
+
+    Pairings {
+        pairs,
+        byes,
+        ..Default::default()
+    }
+}
+
+/// The participants ordered by points, Buchholz and then Sonneborn-Berger.
+/// Participants not yet in the table have no points.
+pub fn standings(participants: &[ParticipantId], history: &SeasonHistory) -> Vec<ParticipantId> {
+    let mut order = participants.to_vec();
+    order.sort_by_cached_key(|id| {
+        let points = history.table.entry(id).map(|entry| entry.points);
+        Reverse((
+            points.unwrap_or_default(),
+            buchholz(id, history.matches, history.scoring),
+            sonneborn_berger(id, history.matches, history.scoring),
+        ))
+    });
+    order
+}
+
+/// The most pairings tried in looking for one without rematches. Backtracking
+/// can otherwise take exponentially long when none exists.
+const MAX_STEPS: usize = 10_000;
+
+/// Pair the ordered participants without any rematches, backtracking when a
+/// participant is left without an eligible opponent. Gives up with `None`
+/// once the steps run out.
+fn pair_up(
+    order: &[ParticipantId],
+    history: &SeasonHistory,
+    steps: &mut usize,
+) -> Option<Vec<(ParticipantId, ParticipantId)>> {
+    let Some((first, rest)) = order.split_first() else {
+        return Some(Vec::new());
+    };
+
+    for (index, opponent) in rest.iter().enumerate() {
+        if history.have_met(first, opponent) {
+            continue;
+        }
+        if *steps == 0 {
+            return None;
+        }
+        *steps -= 1;
+
+        let mut remaining = rest.to_vec();
+        remaining.remove(index);
+        if let Some(mut pairs) = pair_up(&remaining, history, steps) {
+            pairs.insert(0, (*first, *opponent));
+            return Some(pairs);
+        }
+    }
+
+    None
+}
+
+/// Pair each participant in turn with the highest placed they have not yet
+/// met, or the next in order if they have met everyone left. Without
+/// backtracking some rematches may be played, but only where needed.
+fn pair_greedily(
+    order: &[ParticipantId],
+    history: &SeasonHistory,
+) -> Vec<(ParticipantId, ParticipantId)> {
+    let mut remaining = order.to_vec();
+    let mut pairs = Vec::new();
+    while remaining.len() >= 2 {
+        let first = remaining.remove(0);
+        let index = remaining
+            .iter()
+            .position(|opponent| !history.have_met(&first, opponent))
+            .unwrap_or(0);
+        pairs.push((first, remaining.remove(index)));
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    use crate::match_making::assign_courts;
+    use crate::models::{
+        Court, Match, MatchDetails, MatchMakingStrategy, MatchOutcome, Participant, PointsTable,
+        Round, RoundId, ScoringSystem, SessionId, Side, Sport, Venue, VenueId,
+    };
+    use chrono::Utc;
+
+    fn chess(round: &Round, home: &Participant, away: &Participant, result: MatchOutcome) -> Match {
+        let mut a_match = Match::new(round.id, VenueId::new());
+        a_match.details = MatchDetails::Chess {
+            players: (home.id, away.id),
+            result: Some(result),
+        };
+        a_match
+    }
+
+    fn names(ids: &[ParticipantId], participants: &[Participant]) -> Vec<String> {
+        ids.iter()
+            .map(|id| {
+                participants
+                    .iter()
+                    .find(|participant| participant.id == *id)
+                    .map(|participant| participant.name.clone())
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn pair_names(pairings: &Pairings, participants: &[Participant]) -> Vec<(String, String)> {
+        pairings
+            .pairs
+            .iter()
+            .map(|(home, away)| {
+                let names = names(&[*home, *away], participants);
+                (names[0].clone(), names[1].clone())
+            })
+            .collect()
+    }
+
+    fn players(names: &[&str]) -> Vec<Participant> {
+        names
+            .iter()
+            .map(|name| Participant::new(name.to_string()))
+            .collect()
+    }
+
+    fn ids(participants: &[Participant]) -> Vec<ParticipantId> {
+        participants
+            .iter()
+            .map(|participant| participant.id)
+            .collect()
+    }
+
+    fn round_of(participants: &[Participant]) -> Round {
+        let mut round = Round::new(SessionId::new());
+        round.participants = ids(participants);
+        round
+    }
+
+    fn pairs(names: &[(&str, &str)]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|(home, away)| (home.to_string(), away.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn first_round_pairs_in_order() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: None,
+        };
+
+        let pairings = pair(&round_of(&participants), &history);
+
+        assert_eq!(
+            pair_names(&pairings, &participants),
+            pairs(&[("Ann", "Bob"), ("Cat", "Dan")])
+        );
+        assert!(pairings.byes.is_empty());
+    }
+
+    #[test]
+    fn pair_by_points_without_rematches() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let [ann, bob, cat, dan] = participants.clone().try_into().unwrap();
+        let mut first = Round::new(SessionId::new());
+        first.participants = ids(&participants);
+        let mut second = Round::new(SessionId::new());
+        second.participants = ids(&participants);
+
+        let matches = vec![
+            chess(&first, &ann, &bob, MatchOutcome::Win(Side::Home)),
+            chess(&first, &cat, &dan, MatchOutcome::Win(Side::Home)),
+            chess(&second, &ann, &cat, MatchOutcome::Draw),
+            chess(&second, &bob, &dan, MatchOutcome::Win(Side::Home)),
+        ];
+        let scoring = ScoringSystem::default();
+        let table = PointsTable::from_matches(&participants, &matches, &scoring);
+        let rounds = [first, second];
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: None,
+        };
+
+        assert_eq!(
+            names(&standings(&ids(&participants), &history), &participants),
+            ["Ann", "Cat", "Bob", "Dan"]
+        );
+
+        // Ann has already met Cat and Bob so plays Dan, and having played
+        // twice at home plays away.
+        let pairings = pair(&round_of(&participants), &history);
+
+        assert_eq!(
+            pair_names(&pairings, &participants),
+            pairs(&[("Dan", "Ann"), ("Cat", "Bob")])
+        );
+    }
+
+    #[test]
+    fn byes_go_to_the_lowest_placed_without_one() {
+        let participants = players(&["Ann", "Bob", "Cat"]);
+        let [ann, bob, _cat] = participants.clone().try_into().unwrap();
+        let mut round = Round::new(SessionId::new());
+        round.participants = ids(&participants);
+        let matches = vec![chess(&round, &ann, &bob, MatchOutcome::Win(Side::Home))];
+        let scoring = ScoringSystem::default();
+        let table = PointsTable::from_matches(&participants, &matches, &scoring);
+        let rounds = [round];
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: None,
+        };
+
+        // Bob played the leader so is placed above Cat on Buchholz.
+        assert_eq!(
+            names(&standings(&ids(&participants), &history), &participants),
+            ["Ann", "Bob", "Cat"]
+        );
+
+        // Cat sat out the first round so Bob sits out the second.
+        let pairings = pair(&round_of(&participants), &history);
+
+        assert_eq!(names(&pairings.byes, &participants), ["Bob"]);
+        assert_eq!(
+            pair_names(&pairings, &participants),
+            pairs(&[("Cat", "Ann")])
+        );
+    }
+
+    #[test]
+    fn pair_no_more_than_the_round_has_room_for() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve"]);
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let mut round = round_of(&participants);
+        round.capacity = Some(1);
+
+        let pairings = pair(&round, &history);
+
+        assert_eq!(
+            pair_names(&pairings, &participants),
+            pairs(&[("Ann", "Bob")])
+        );
+        assert_eq!(names(&pairings.byes, &participants), ["Eve", "Dan", "Cat"]);
+    }
+
+    #[test]
+    fn unavoidable_rematches_are_kept_few() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve", "Fay"]);
+        let [ann, bob, cat, dan, eve, fay] = participants.clone().try_into().unwrap();
+        let round = Round::new(SessionId::new());
+
+        // Ann has met everyone, so must play a rematch, and Cat has met Dan.
+        let mut matches: Vec<Match> = [&bob, &cat, &dan, &eve, &fay]
+            .into_iter()
+            .map(|opponent| chess(&round, &ann, opponent, MatchOutcome::Draw))
+            .collect();
+        matches.push(chess(&round, &cat, &dan, MatchOutcome::Draw));
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let order = ids(&participants);
+
+        assert_eq!(pair_up(&order, &history, &mut MAX_STEPS.clone()), None);
+        assert_eq!(
+            pair_greedily(&order, &history),
+            [(ann.id, bob.id), (cat.id, eve.id), (dan.id, fay.id)]
+        );
+    }
+
+    #[test]
+    fn give_up_avoiding_rematches_when_out_of_steps() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let order = ids(&participants);
+
+        assert!(pair_up(&order, &history, &mut MAX_STEPS.clone()).is_some());
+        assert_eq!(pair_up(&order, &history, &mut 1), None);
+    }
+
+    #[test]
+    fn sit_outs_rotate_when_the_courts_are_full() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan", "Eve", "Fay"]);
+        let mut venue = Venue::new("Club".to_string());
+        venue.courts = vec![Court::new("Court 1"), Court::new("Court 2")];
+        let now = Utc::now();
+        let session_id = SessionId::new();
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let roster = ids(&participants);
+        let mut rounds: Vec<Round> = Vec::new();
+        let mut matches: Vec<Match> = Vec::new();
+
+        // Six players on two courts, as sessions pair-round does it.
+        for _ in 0..3 {
+            let mut next = round_of(&participants);
+            next.session_id = session_id;
+            next.strategy = MatchMakingStrategy::Swiss;
+            let history = SeasonHistory {
+                table: &table,
+                scoring: &scoring,
+                rounds: &rounds,
+                matches: &matches,
+                skills: &HashMap::new(),
+                roster: Some(&roster),
+            };
+
+            next.fit_courts(&venue, &now);
+            let pairings = next.pair(&history).unwrap();
+            let mut round_matches = pairings.matches(&next.id, Sport::Chess, &venue.id).unwrap();
+            assert_eq!(assign_courts(&mut round_matches, &venue, &now, &[]), Ok(()));
+            assert_eq!(round_matches.len(), 2);
+            assert_eq!(next.sit_outs.len(), 2);
+
+            matches.extend(round_matches);
+            rounds.push(next);
+        }
+
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &rounds,
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: Some(&roster),
+        };
+        let sat_out = history.sit_outs_in_session(&session_id);
+        for participant in &participants {
+            assert_eq!(
+                sat_out.get(&participant.id),
+                Some(&1),
+                "{}",
+                participant.name
+            );
+        }
+    }
+
+    #[test]
+    fn ties_broken_by_buchholz() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let [ann, bob, cat, dan] = participants.clone().try_into().unwrap();
+        let round = Round::new(SessionId::new());
+
+        // Ann and Dan both have one win, but Ann lost to the unbeaten Bob
+        // while Dan lost to Ann.
+        let matches = vec![
+            chess(&round, &ann, &dan, MatchOutcome::Win(Side::Home)),
+            chess(&round, &bob, &cat, MatchOutcome::Win(Side::Home)),
+            chess(&round, &bob, &ann, MatchOutcome::Win(Side::Home)),
+            chess(&round, &cat, &dan, MatchOutcome::Win(Side::Away)),
+        ];
+        let scoring = ScoringSystem::default();
+        let table = PointsTable::from_matches(&participants, &matches, &scoring);
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &matches,
+            skills: &HashMap::new(),
+            roster: None,
+        };
+
+        assert_eq!(
+            names(
+                &standings(&[cat.id, dan.id, ann.id, bob.id], &history),
+                &participants
+            ),
+            ["Bob", "Ann", "Dan", "Cat"]
+        );
+    }
+
+    #[test]
+    fn manual_rounds_are_not_paired() {
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: None,
+        };
+        let mut round = Round::new(SessionId::new());
+        round.participants = vec![ParticipantId::new(), ParticipantId::new()];
+
+        assert_eq!(round.strategy.pair(&round, &history), None);
+
+        round.strategy = MatchMakingStrategy::Swiss;
+        let pairings = round.strategy.pair(&round, &history).unwrap();
+        let matches = pairings
+            .matches(&RoundId::new(), Sport::Chess, &VenueId::new())
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn only_pair_the_roster() {
+        let participants = players(&["Ann", "Bob", "Cat", "Dan"]);
+        let roster = ids(&participants[..3]);
+        let table = PointsTable::new();
+        let scoring = ScoringSystem::default();
+        let history = SeasonHistory {
+            table: &table,
+            scoring: &scoring,
+            rounds: &[],
+            matches: &[],
+            skills: &HashMap::new(),
+            roster: Some(&roster),
+        };
+        let mut round = round_of(&participants);
+        round.strategy = MatchMakingStrategy::Swiss;
+
+        let pairings = round.pair(&history).unwrap();
+
+        assert_eq!(
+            pair_names(&pairings, &participants),
+            pairs(&[("Ann", "Bob")])
+        );
+        assert_eq!(pairings.byes, [participants[2].id]);
+    }
+}
@@ -1,29 +1,57 @@
 mod a_match;
+mod bracket;
+mod challenge;
+mod division;
+mod group_stage;
+mod handicap;
 mod id;
+mod ladder;
 mod league;
 mod participant;
 mod points_table;
+mod rating;
+mod recurrence;
 mod round;
 mod season;
 mod session;
+mod sport;
+mod team;
+mod tie;
+mod tie_breaker;
 mod venue;
 
 // Rexport sub-modules into this module for ease of use.
 pub use self::a_match::*;
+pub use self::bracket::*;
+pub use self::challenge::*;
+pub use self::division::*;
+pub use self::group_stage::*;
+pub use self::handicap::*;
 pub use self::id::*;
+pub use self::ladder::*;
 pub use self::league::*;
 pub use self::participant::*;
 pub use self::points_table::*;
+pub use self::rating::*;
+pub use self::recurrence::*;
 pub use self::round::*;
 pub use self::season::*;
 pub use self::session::*;
+pub use self::sport::*;
+pub use self::team::*;
+pub use self::tie::*;
+pub use self::tie_breaker::*;
 pub use self::venue::*;
 
 // Aliases for typesafe IDs
+pub type BracketId = ID<Bracket>;
+pub type ChallengeId = ID<Challenge>;
+pub type GroupStageId = ID<GroupStage>;
 pub type LeagueId = ID<League>;
 pub type SeasonId = ID<Season>;
 pub type SessionId = ID<Session>;
 pub type RoundId = ID<Round>;
 pub type MatchId = ID<Match>;
 pub type ParticipantId = ID<Participant>;
+pub type TieId = ID<Tie>;
 pub type VenueId = ID<Venue>;
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_with::with_prefix;
 
-use super::{MatchId, ParticipantId, RoundId, VenueId};
+use super::{
+    Bracket, GroupStage, Lineup, MatchDetailsError, MatchId, Participant, ParticipantId, RoundId,
+    ScoringSystem, SeasonError, Sport, TeamError, VenueId,
+};
 
 // Prefix the various IDs to avoid clashes
 with_prefix!(prefix_round "round");
@@ -13,9 +16,8 @@ with_prefix!(prefix_venue "venue");
 /// involve participants and located at a venue. In the end a match will produce
 /// a result.
 ///
-/// TODO: This might need to become an Enum because there are different type of
-/// matches which can be played and each may have different ways of calculate a
-/// result.
+/// The different types of matches which can be played, and how each calculates
+/// a result, are described by the [`MatchDetails`] of the match's [`Sport`].
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Match {
     // Flatten will inline this field into its parent.
@@ -26,11 +28,27 @@ pub struct Match {
     pub round_id: RoundId,
     #[serde(flatten, with = "prefix_venue")]
     pub venue_id: VenueId,
+    /// The name of the court within the venue the match is played on.
+    #[serde(default)]
+    pub court: Option<String>,
     pub details: MatchDetails,
+    /// Whether the result has been confirmed. Only confirmed results count
+    /// towards ratings.
+    #[serde(default)]
+    pub confirmed: bool,
+    /// Which members played for each team in the match.
+    #[serde(default)]
+    pub lineups: Vec<Lineup>,
+    /// The handicap start each side was given, in a season with handicaps.
+    #[serde(default)]
+    pub starts: Option<(u8, u8)>,
 }
 
 /// Match Details represents the participants and scores in a format relevant to a particular kind
 /// of match. For example a tennis singles match or tennis doubles match.
+///
+/// The first of the players (or pair of players) is the [`Side::Home`] side and
+/// each score is given from the home side's perspective.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum MatchDetails {
     /// Placeholder Value
@@ -48,6 +66,276 @@ pub enum MatchDetails {
         ),
         scores: Vec<(u8, u8)>,
     },
+
+    PadelDoubles {
+        players: (
+            (ParticipantId, ParticipantId),
+            (ParticipantId, ParticipantId),
+        ),
+        scores: Vec<(u8, u8)>,
+    },
+
+    BadmintonSingles {
+        players: (ParticipantId, ParticipantId),
+        scores: Vec<(u8, u8)>,
+    },
+
+    BadmintonDoubles {
+        players: (
+            (ParticipantId, ParticipantId),
+            (ParticipantId, ParticipantId),
+        ),
+        scores: Vec<(u8, u8)>,
+    },
+
+    SquashSingles {
+        players: (ParticipantId, ParticipantId),
+        scores: Vec<(u8, u8)>,
+    },
+
+    TableTennisSingles {
+        players: (ParticipantId, ParticipantId),
+        scores: Vec<(u8, u8)>,
+    },
+
+    TableTennisDoubles {
+        players: (
+            (ParticipantId, ParticipantId),
+            (ParticipantId, ParticipantId),
+        ),
+        scores: Vec<(u8, u8)>,
+    },
+
+    PickleballSingles {
+        players: (ParticipantId, ParticipantId),
+        scores: Vec<(u8, u8)>,
+    },
+
+    PickleballDoubles {
+        players: (
+            (ParticipantId, ParticipantId),
+            (ParticipantId, ParticipantId),
+        ),
+        scores: Vec<(u8, u8)>,
+    },
+
+    /// A game of chess, the home player has the white pieces.
+    Chess {
+        players: (ParticipantId, ParticipantId),
+        result: Option<MatchOutcome>,
+    },
+
+    /// A generic head-to-head match decided by a single score, such as the
+    /// goals in a game of football. Level scores are a draw.
+    HeadToHeadScore {
+        players: (ParticipantId, ParticipantId),
+        score: Option<(u32, u32)>,
+    },
+
+    /// A generic head-to-head match where only the result, a win, draw or
+    /// loss, is recorded.
+    HeadToHeadResult {
+        players: (ParticipantId, ParticipantId),
+        result: Option<MatchOutcome>,
+    },
+}
+
+/// One of the two sides of a match.
+#[derive(Debug, Eq, Hash, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Side {
+    Home,
+    Away,
+}
+
+/// The result of a decided match.
+#[derive(Debug, Eq, Hash, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum MatchOutcome {
+    Win(Side),
+    Draw,
+}
+
+impl Side {
+    /// The other side of the match.
+    pub fn opponent(&self) -> Side {
+        match self {
+            Side::Home => Side::Away,
+            Side::Away => Side::Home,
+        }
+    }
+}
+
+impl MatchDetails {
+    /// The sport these details describe, or `None` for placeholder and generic
+    /// head-to-head details which are not tied to any one sport.
+    pub fn sport(&self) -> Option<Sport> {
+        match self {
+            MatchDetails::None
+            | MatchDetails::HeadToHeadScore { .. }
+            | MatchDetails::HeadToHeadResult { .. } => None,
+            MatchDetails::TennisSingles { .. } | MatchDetails::TennisDoubles { .. } => {
+                Some(Sport::Tennis)
+            }
+            MatchDetails::PadelDoubles { .. } => Some(Sport::Padel),
+            MatchDetails::BadmintonSingles { .. } | MatchDetails::BadmintonDoubles { .. } => {
+                Some(Sport::Badminton)
+            }
+            MatchDetails::SquashSingles { .. } => Some(Sport::Squash),
+            MatchDetails::TableTennisSingles { .. } | MatchDetails::TableTennisDoubles { .. } => {
+                Some(Sport::TableTennis)
+            }
+            MatchDetails::PickleballSingles { .. } | MatchDetails::PickleballDoubles { .. } => {
+                Some(Sport::Pickleball)
+            }
+            MatchDetails::Chess { .. } => Some(Sport::Chess),
+        }
+    }
+
+    /// The participants on the home and away sides of the match.
+    pub fn sides(&self) -> Option<(Vec<ParticipantId>, Vec<ParticipantId>)> {
+        match self {
+            MatchDetails::None => None,
+            MatchDetails::TennisSingles { players, .. }
+            | MatchDetails::BadmintonSingles { players, .. }
+            | MatchDetails::SquashSingles { players, .. }
+            | MatchDetails::TableTennisSingles { players, .. }
+            | MatchDetails::PickleballSingles { players, .. }
+            | MatchDetails::Chess { players, .. }
+            | MatchDetails::HeadToHeadScore { players, .. }
+            | MatchDetails::HeadToHeadResult { players, .. } => {
+                Some((vec![players.0], vec![players.1]))
+            }
+            MatchDetails::TennisDoubles { players, .. }
+            | MatchDetails::PadelDoubles { players, .. }
+            | MatchDetails::BadmintonDoubles { players, .. }
+            | MatchDetails::TableTennisDoubles { players, .. }
+            | MatchDetails::PickleballDoubles { players, .. } => Some((
+                vec![players.0 .0, players.0 .1],
+                vec![players.1 .0, players.1 .1],
+            )),
+        }
+    }
+
+    /// All the participants of the match.
+    pub fn participants(&self) -> Vec<ParticipantId> {
+        self.sides()
+            .map(|(home, away)| home.into_iter().chain(away).collect())
+            .unwrap_or_default()
+    }
+
+    /// The side a participant is playing on, if they are playing at all.
+    pub fn side_of(&self, participant_id: &ParticipantId) -> Option<Side> {
+        let (home, away) = self.sides()?;
+
+        if home.contains(participant_id) {
+            Some(Side::Home)
+        } else if away.contains(participant_id) {
+            Some(Side::Away)
+        } else {
+            None
+        }
+    }
+
+    /// The set scores of the match, for sports which are scored in sets.
+    pub fn set_scores(&self) -> Option<&[(u8, u8)]> {
+        match self {
+            MatchDetails::None
+            | MatchDetails::Chess { .. }
+            | MatchDetails::HeadToHeadScore { .. }
+            | MatchDetails::HeadToHeadResult { .. } => None,
+            MatchDetails::TennisSingles { scores, .. }
+            | MatchDetails::TennisDoubles { scores, .. }
+            | MatchDetails::PadelDoubles { scores, .. }
+            | MatchDetails::BadmintonSingles { scores, .. }
+            | MatchDetails::BadmintonDoubles { scores, .. }
+            | MatchDetails::SquashSingles { scores, .. }
+            | MatchDetails::TableTennisSingles { scores, .. }
+            | MatchDetails::TableTennisDoubles { scores, .. }
+            | MatchDetails::PickleballSingles { scores, .. }
+            | MatchDetails::PickleballDoubles { scores, .. } => Some(scores),
+        }
+    }
+
+    /// Mutable access to the set scores of the match, for sports which are
+    /// scored in sets.
+    pub fn set_scores_mut(&mut self) -> Option<&mut Vec<(u8, u8)>> {
+        match self {
+            MatchDetails::None
+            | MatchDetails::Chess { .. }
+            | MatchDetails::HeadToHeadScore { .. }
+            | MatchDetails::HeadToHeadResult { .. } => None,
+            MatchDetails::TennisSingles { scores, .. }
+            | MatchDetails::TennisDoubles { scores, .. }
+            | MatchDetails::PadelDoubles { scores, .. }
+            | MatchDetails::BadmintonSingles { scores, .. }
+            | MatchDetails::BadmintonDoubles { scores, .. }
+            | MatchDetails::SquashSingles { scores, .. }
+            | MatchDetails::TableTennisSingles { scores, .. }
+            | MatchDetails::TableTennisDoubles { scores, .. }
+            | MatchDetails::PickleballSingles { scores, .. }
+            | MatchDetails::PickleballDoubles { scores, .. } => Some(scores),
+        }
+    }
+
+    /// The total score of each side. This is the goals (or points) of a
+    /// score-based match, or the games won across all sets of a set-based
+    /// match. Matches with only a result have no score.
+    pub fn score_totals(&self) -> Option<(u32, u32)> {
+        match self {
+            MatchDetails::HeadToHeadScore { score, .. } => *score,
+            _ => self.set_scores().map(|scores| {
+                scores.iter().fold((0, 0), |(home, away), score| {
+                    (home + u32::from(score.0), away + u32::from(score.1))
+                })
+            }),
+        }
+    }
+
+    /// The outcome of the match, or `None` if it is not yet decided.
+    pub fn outcome(&self) -> Option<MatchOutcome> {
+        match self {
+            MatchDetails::Chess { result, .. } | MatchDetails::HeadToHeadResult { result, .. } => {
+                *result
+            }
+            MatchDetails::HeadToHeadScore { score, .. } => {
+                score.map(|(home, away)| match home.cmp(&away) {
+                    std::cmp::Ordering::Greater => MatchOutcome::Win(Side::Home),
+                    std::cmp::Ordering::Less => MatchOutcome::Win(Side::Away),
+                    std::cmp::Ordering::Equal => MatchOutcome::Draw,
+                })
+            }
+            _ => self.sport().and_then(|sport| sport.outcome(self)),
+        }
+    }
+
+    /// The details with each side's handicap start applied.
+    ///
+    /// Score-based matches add the start to the side's score afterwards, like
+    /// handicap pins in bowling. Set-based matches are played from the start,
+    /// so each set's score already includes it, and matches with only a
+    /// result have no score to start from. Both are left as they are.
+    pub fn with_starts(&self, starts: (u8, u8)) -> MatchDetails {
+        match self {
+            MatchDetails::HeadToHeadScore { players, score } => MatchDetails::HeadToHeadScore {
+                players: *players,
+                score: score
+                    .map(|(home, away)| (home + u32::from(starts.0), away + u32::from(starts.1))),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    /// Check no side scored less in a set than the start it played from.
+    pub fn check_starts(&self, starts: (u8, u8)) -> Result<(), MatchDetailsError> {
+        for (index, score) in self.set_scores().unwrap_or_default().iter().enumerate() {
+            if score.0 < starts.0 || score.1 < starts.1 {
+                return Err(MatchDetailsError::BelowStart {
+                    set: index + 1,
+                    score: *score,
+                });
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Match {
@@ -56,8 +344,109 @@ impl Match {
             id: MatchId::new(),
             round_id,
             venue_id,
+            court: None,
             details: MatchDetails::None,
+            confirmed: false,
+            lineups: Vec::new(),
+            starts: None,
+        }
+    }
+
+    /// Set the details of the match after checking they are valid for the
+    /// sport being played.
+    pub fn set_details(
+        &mut self,
+        sport: &Sport,
+        details: MatchDetails,
+    ) -> Result<(), MatchDetailsError> {
+        sport.validate(&details)?;
+        if let Some(starts) = self.starts {
+            details.check_starts(starts)?;
+        }
+        self.details = details;
+        Ok(())
+    }
+
+    /// Confirm the result of the match, advancing the bracket it was drawn in
+    /// or recording it in the table of its group. Returns the bracket's
+    /// matches which are now ready to be played, or nothing if the result was
+    /// already confirmed. A bracket's matches must have a winner.
+    pub fn confirm(
+        &mut self,
+        bracket: Option<&mut Bracket>,
+        group_stage: Option<&mut GroupStage>,
+        scoring: &ScoringSystem,
+    ) -> Result<Vec<Match>, SeasonError> {
+        if self.confirmed {
+            return Ok(Vec::new());
+        }
+        let won = matches!(self.scored_details().outcome(), Some(MatchOutcome::Win(_)));
+        if bracket.is_some() && !won {
+            return Err(SeasonError::KnockoutUndecided(self.id));
         }
+        self.confirmed = true;
+
+        if let Some(group_stage) = group_stage {
+            group_stage.record(self, scoring);
+        }
+
+        Ok(match bracket {
+            Some(bracket) => bracket.advance(self),
+            None => Vec::new(),
+        })
+    }
+
+    /// The details the match is scored by, with any handicap starts applied.
+    pub fn scored_details(&self) -> MatchDetails {
+        match self.starts {
+            Some(starts) => self.details.with_starts(starts),
+            None => self.details.clone(),
+        }
+    }
+
+    /// Record the members who played for a team in the match, replacing any
+    /// lineup recorded for it before.
+    pub fn record_lineup(
+        &mut self,
+        team: &Participant,
+        members: &[ParticipantId],
+    ) -> Result<(), TeamError> {
+        let rules = team.team.as_ref().ok_or(TeamError::NotATeam)?;
+        if self.details.side_of(&team.id).is_none() {
+            return Err(TeamError::NotInMatch);
+        }
+        let mut lineup: Vec<ParticipantId> = Vec::new();
+        for member in members {
+            if !lineup.contains(member) {
+                lineup.push(*member);
+            }
+        }
+        let members = lineup;
+        rules.check_lineup(&members)?;
+        let opponents = self
+            .lineups
+            .iter()
+            .filter(|lineup| lineup.team_id != team.id)
+            .flat_map(|lineup| &lineup.members);
+        for opponent in opponents {
+            if members.contains(opponent) {
+                return Err(TeamError::PlayingForOpponent(*opponent));
+            }
+        }
+
+        self.lineups.retain(|lineup| lineup.team_id != team.id);
+        self.lineups.push(Lineup {
+            team_id: team.id,
+            members,
+        });
+        Ok(())
+    }
+
+    /// The members who played for a team in the match, if recorded.
+    pub fn lineup(&self, team_id: &ParticipantId) -> Option<&Lineup> {
+        self.lineups
+            .iter()
+            .find(|lineup| lineup.team_id == *team_id)
     }
 }
 
@@ -67,6 +456,8 @@ mod tests {
     use bson::{doc, Uuid};
     use pretty_assertions::assert_eq;
 
+    use crate::models::{PointsTable, ScoringSystem};
+
     #[test]
     fn serialize_as_bson() {
         let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
@@ -80,7 +471,11 @@ mod tests {
             id,
             round_id,
             venue_id,
+            court: None,
             details: MatchDetails::None,
+            confirmed: false,
+            lineups: Vec::new(),
+            starts: None,
         };
 
         let bson = bson::to_document(&a_match).unwrap();
@@ -89,7 +484,11 @@ mod tests {
             "_id": a_match.id,
             "round_id": a_match.round_id,
             "venue_id": a_match.venue_id,
+            "court": null,
             "details": bson::to_bson(&a_match.details).unwrap(),
+            "confirmed": a_match.confirmed,
+            "lineups": [],
+            "starts": null,
         };
 
         assert_eq!(bson, expected_bson);
@@ -111,7 +510,14 @@ mod tests {
             id,
             round_id,
             venue_id,
+            court: Some("Court 1".to_string()),
             details: MatchDetails::TennisSingles { players, scores },
+            confirmed: true,
+            lineups: vec![Lineup {
+                team_id: players.0,
+                members: vec![players.1],
+            }],
+            starts: Some((0, 2)),
         };
 
         let bson = bson::to_document(&a_match).unwrap();
@@ -120,9 +526,147 @@ mod tests {
             "_id": a_match.id,
             "round_id": a_match.round_id,
             "venue_id": a_match.venue_id,
+            "court": "Court 1",
             "details": bson::to_bson(&a_match.details).unwrap(),
+            "confirmed": a_match.confirmed,
+            "lineups": [{
+                "team_id": players.0,
+                "members": [{ "_id": players.1 }],
+            }],
+            "starts": [0, 2],
         };
 
         assert_eq!(bson, expected_bson);
     }
+
+    #[test]
+    fn set_details_validates_for_sport() {
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        let players = (ParticipantId::new(), ParticipantId::new());
+
+        let invalid = MatchDetails::BadmintonSingles {
+            players,
+            scores: vec![(21, 20)],
+        };
+        assert!(a_match.set_details(&Sport::Badminton, invalid).is_err());
+        assert_eq!(a_match.details, MatchDetails::None);
+
+        let valid = MatchDetails::BadmintonSingles {
+            players,
+            scores: vec![(21, 19), (18, 21), (30, 29)],
+        };
+        assert_eq!(a_match.set_details(&Sport::Badminton, valid), Ok(()));
+        assert_eq!(
+            a_match.details.outcome(),
+            Some(MatchOutcome::Win(Side::Home))
+        );
+    }
+
+    #[test]
+    fn head_to_head_score_can_be_drawn() {
+        let players = (ParticipantId::new(), ParticipantId::new());
+
+        let drawn = MatchDetails::HeadToHeadScore {
+            players,
+            score: Some((2, 2)),
+        };
+        let won = MatchDetails::HeadToHeadScore {
+            players,
+            score: Some((1, 3)),
+        };
+        let unplayed = MatchDetails::HeadToHeadScore {
+            players,
+            score: None,
+        };
+
+        assert_eq!(drawn.outcome(), Some(MatchOutcome::Draw));
+        assert_eq!(won.outcome(), Some(MatchOutcome::Win(Side::Away)));
+        assert_eq!(won.score_totals(), Some((1, 3)));
+        assert_eq!(unplayed.outcome(), None);
+    }
+
+    #[test]
+    fn score_totals_of_sets() {
+        let details = MatchDetails::TennisSingles {
+            players: (ParticipantId::new(), ParticipantId::new()),
+            scores: vec![(6, 4), (3, 6), (7, 6)],
+        };
+
+        assert_eq!(details.score_totals(), Some((16, 16)));
+        assert_eq!(details.outcome(), Some(MatchOutcome::Win(Side::Home)));
+    }
+
+    #[test]
+    fn sides_of_doubles() {
+        let ids: Vec<ParticipantId> = (0..4).map(|_| ParticipantId::new()).collect();
+        let details = MatchDetails::TennisDoubles {
+            players: ((ids[0], ids[1]), (ids[2], ids[3])),
+            scores: Vec::new(),
+        };
+
+        assert_eq!(
+            details.sides(),
+            Some((vec![ids[0], ids[1]], vec![ids[2], ids[3]]))
+        );
+        assert_eq!(details.side_of(&ids[3]), Some(Side::Away));
+        assert_eq!(details.side_of(&ParticipantId::new()), None);
+    }
+
+    #[test]
+    fn record_lineups_while_the_team_earns_the_points() {
+        let mut home = Participant::new_team("Rovers".to_string());
+        let mut away = Participant::new_team("United".to_string());
+        let players: Vec<ParticipantId> = (0..5).map(|_| ParticipantId::new()).collect();
+        let rovers = home.team.as_mut().unwrap();
+        rovers.eligibility.min_players = Some(2);
+        rovers.eligibility.max_players = Some(2);
+        for player in &players[..3] {
+            rovers.add_member(player, false).unwrap();
+        }
+        let united = away.team.as_mut().unwrap();
+        for player in &players[2..] {
+            united.add_member(player, false).unwrap();
+        }
+
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::HeadToHeadResult {
+            players: (home.id, away.id),
+            result: Some(MatchOutcome::Win(Side::Home)),
+        };
+
+        assert_eq!(
+            a_match.record_lineup(&home, &players[..1]),
+            Err(TeamError::TooFewPlayers { min: 2, played: 1 })
+        );
+        assert_eq!(
+            a_match.record_lineup(&home, &players[..3]),
+            Err(TeamError::TooManyPlayers { max: 2, played: 3 })
+        );
+        assert_eq!(
+            a_match.record_lineup(&home, &players[3..]),
+            Err(TeamError::NotAMember(players[3]))
+        );
+        assert_eq!(a_match.record_lineup(&away, &players[2..]), Ok(()));
+        // Cat is in both teams but can only play for one side.
+        assert_eq!(
+            a_match.record_lineup(&home, &players[1..3]),
+            Err(TeamError::PlayingForOpponent(players[2]))
+        );
+        assert_eq!(a_match.record_lineup(&home, &players[..2]), Ok(()));
+        assert_eq!(a_match.lineup(&home.id).unwrap().members, &players[..2]);
+
+        let individual = Participant::new("Ann".to_string());
+        assert_eq!(
+            a_match.record_lineup(&individual, &[]),
+            Err(TeamError::NotATeam)
+        );
+
+        let table = PointsTable::from_matches(
+            &[home.clone(), away.clone()],
+            &[a_match],
+            &ScoringSystem::default(),
+        );
+        assert_eq!(table.entry(&home.id).unwrap().points, 3);
+        assert!(table.entry(&players[0]).is_none());
+    }
 }
@@ -0,0 +1,575 @@
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{
+    BracketId, Match, MatchId, MatchOutcome, ParticipantId, RoundId, SeasonId, Side, Sport, VenueId,
+};
+
+with_prefix!(prefix_season "season");
+with_prefix!(prefix_match "match");
+
+/// A knockout draw, such as the finals of a season.
+///
+/// The bracket is a graph of matches. Each side of a match is filled by a
+/// seed or by the winner or loser of an earlier match, so results flow through
+/// the bracket as matches are decided. Seeds beyond the number of participants
+/// are byes and whoever is drawn against a bye goes straight through.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Bracket {
+    // Flatten will inline this field into its parent.
+    // See https://serde.rs/attr-flatten.html
+    #[serde(flatten)]
+    pub id: BracketId,
+    #[serde(flatten, with = "prefix_season")]
+    pub season_id: SeasonId,
+    pub name: String,
+    pub sport: Sport,
+    pub format: BracketFormat,
+    /// The participants in seed order, top seed first.
+    pub seeds: Vec<ParticipantId>,
+    pub matches: Vec<BracketMatch>,
+}
+
+/// The shape of a knockout draw.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum BracketFormat {
+    /// Participants are out after a single defeat.
+    SingleElimination {
+        /// The losing semi-finalists play off for third place.
+        third_place: bool,
+        /// Those beaten in their first match play a knockout of their own.
+        plate: bool,
+    },
+    /// Participants drop into a losers bracket after their first defeat and
+    /// are out after their second. The winners of each bracket meet in a
+    /// single grand final.
+    DoubleElimination,
+}
+
+/// The part of the bracket a match belongs to.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum BracketSection {
+    /// The main draw, or the winners bracket of a double elimination.
+    Main,
+    Losers,
+    GrandFinal,
+    ThirdPlace,
+    Plate,
+}
+
+/// A match in a bracket.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BracketMatch {
+    pub section: BracketSection,
+    /// The round within the section, starting at 1.
+    pub round: usize,
+    pub home: Entrant,
+    pub away: Entrant,
+    /// The match once it has been scheduled.
+    #[serde(default, flatten, with = "prefix_match")]
+    pub match_id: Option<MatchId>,
+    pub winner: Option<Side>,
+}
+
+/// Who fills one side of a bracket match.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Entrant {
+    /// A seed, starting at 1.
+    Seed(usize),
+    /// The winner of the bracket match at this index.
+    Winner(usize),
+    /// The loser of the bracket match at this index.
+    Loser(usize),
+}
+
+/// Who actually occupies a side of a bracket match right now.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Occupant {
+    Participant(ParticipantId),
+    /// Nobody, the other side goes through unopposed.
+    Bye,
+    /// Not known until an earlier match is decided.
+    Pending,
+}
+
+impl Bracket {
+    /// Draw a bracket for the seeded participants.
+    ///
+    /// The draw is made up to the next power of two with the top seeds kept
+    /// apart until the later rounds, so any byes go to the top seeds.
+    pub fn new(
+        season_id: &SeasonId,
+        name: &str,
+        sport: Sport,
+        format: BracketFormat,
+        seeds: Vec<ParticipantId>,
+    ) -> Bracket {
+        let mut bracket = Bracket {
+            id: BracketId::new(),
+            season_id: *season_id,
+            name: name.to_string(),
+            sport,
+            format,
+            seeds,
+            matches: Vec::new(),
+        };
+
+        let size = bracket.seeds.len().next_power_of_two().max(2);
+        let entrants = seed_order(size).into_iter().map(Entrant::Seed).collect();
+        let main = bracket.knockout(BracketSection::Main, entrants);
+
+        match format {
+            BracketFormat::SingleElimination { third_place, plate } => {
+                if main.len() >= 2 {
+                    if third_place {
+                        let semi_finals = &main[main.len() - 2];
+                        bracket.push(
+                            BracketSection::ThirdPlace,
+                            1,
+                            Entrant::Loser(semi_finals[0]),
+                            Entrant::Loser(semi_finals[1]),
+                        );
+                    }
+                    if plate {
+                        let first_losers = main[0].iter().copied().map(Entrant::Loser).collect();
+                        bracket.knockout(BracketSection::Plate, first_losers);
+                    }
+                }
+            }
+            BracketFormat::DoubleElimination => {
+                if main.len() >= 2 {
+                    bracket.losers_bracket(&main);
+                }
+            }
+        }
+
+        bracket
+    }
+
+    /// Who occupies each side of the bracket match at this index.
+    pub fn occupants(&self, index: usize) -> (Occupant, Occupant) {
+        let bracket_match = &self.matches[index];
+        (
+            self.occupant(&bracket_match.home),
+            self.occupant(&bracket_match.away),
+        )
+    }
+
+    /// The winner and loser of the bracket match at this index. A participant
+    /// drawn against a bye wins without playing.
+    pub fn result(&self, index: usize) -> (Occupant, Occupant) {
+        let (home, away) = self.occupants(index);
+
+        match (self.matches[index].winner, home, away) {
+            (Some(Side::Home), _, _) => (home, away),
+            (Some(Side::Away), _, _) => (away, home),
+            (None, Occupant::Participant(_), Occupant::Bye) => (home, away),
+            (None, Occupant::Bye, Occupant::Participant(_)) => (away, home),
+            (None, Occupant::Bye, Occupant::Bye) => (Occupant::Bye, Occupant::Bye),
+            _ => (Occupant::Pending, Occupant::Pending),
+        }
+    }
+
+    /// The winner of the whole bracket, once the final is decided.
+    pub fn champion(&self) -> Option<ParticipantId> {
+        let grand_final = self
+            .matches
+            .iter()
+            .position(|m| m.section == BracketSection::GrandFinal);
+        let main_final = self
+            .matches
+            .iter()
+            .rposition(|m| m.section == BracketSection::Main);
+
+        match self.result(grand_final.or(main_final)?).0 {
+            Occupant::Participant(participant_id) => Some(participant_id),
+            _ => None,
+        }
+    }
+
+    /// Create a match for every bracket match whose participants are now
+    /// known, in the given round and venue.
+    pub fn schedule(&mut self, round_id: &RoundId, venue_id: &VenueId) -> Vec<Match> {
+        let mut scheduled = Vec::new();
+
+        for index in 0..self.matches.len() {
+            if self.matches[index].match_id.is_some() || self.matches[index].winner.is_some() {
+                continue;
+            }
+            let (Occupant::Participant(home), Occupant::Participant(away)) = self.occupants(index)
+            else {
+                continue;
+            };
+            let Some(details) = self.sport.singles((home, away)) else {
+                continue;
+            };
+
+            let mut a_match = Match::new(*round_id, *venue_id);
+            a_match.details = details;
+            self.matches[index].match_id = Some(a_match.id);
+            scheduled.push(a_match);
+        }
+
+        scheduled
+    }
+
+    /// Record the result of a confirmed match and schedule any matches which
+    /// are now ready, in the same round and venue. Matches which are not part
+    /// of the bracket, or have not been won, are ignored.
+    pub fn advance(&mut self, a_match: &Match) -> Vec<Match> {
+        let Some(MatchOutcome::Win(side)) = a_match.scored_details().outcome() else {
+            return Vec::new();
+        };
+        let Some(bracket_match) = self
+            .matches
+            .iter_mut()
+            .find(|m| m.match_id == Some(a_match.id))
+        else {
+            return Vec::new();
+        };
+
+        bracket_match.winner = Some(side);
+        self.schedule(&a_match.round_id, &a_match.venue_id)
+    }
+
+    fn occupant(&self, entrant: &Entrant) -> Occupant {
+        match entrant {
+            Entrant::Seed(seed) => self
+                .seeds
+                .get(seed - 1)
+                .map_or(Occupant::Bye, |id| Occupant::Participant(*id)),
+            Entrant::Winner(index) => self.result(*index).0,
+            Entrant::Loser(index) => self.result(*index).1,
+        }
+    }
+
+    fn push(
+        &mut self,
+        section: BracketSection,
+        round: usize,
+        home: Entrant,
+        away: Entrant,
+    ) -> usize {
+        self.matches.push(BracketMatch {
+            section,
+            round,
+            home,
+            away,
+            match_id: None,
+            winner: None,
+        });
+        self.matches.len() - 1
+    }
+
+    /// Add a knockout between the entrants, given in draw order, returning
+    /// the indices of the matches in each round.
+    fn knockout(&mut self, section: BracketSection, entrants: Vec<Entrant>) -> Vec<Vec<usize>> {
+        let mut rounds: Vec<Vec<usize>> = Vec::new();
+        let mut entrants = entrants;
+
+        while entrants.len() >= 2 {
+            let round = rounds.len() + 1;
+            let indices: Vec<usize> = entrants
+                .chunks(2)
+                .map(|pair| self.push(section, round, pair[0], pair[1]))
+                .collect();
+            entrants = indices.iter().copied().map(Entrant::Winner).collect();
+            rounds.push(indices);
+        }
+
+        rounds
+    }
+
+    /// Add the losers bracket and grand final of a double elimination.
+    ///
+    /// The losers of the first round play each other, then each later round of
+    /// the winners bracket drops its losers in against the survivors, in
+    /// reverse order to keep apart those who have just met.
+    fn losers_bracket(&mut self, main: &[Vec<usize>]) {
+        let first_losers: Vec<Entrant> = main[0].iter().copied().map(Entrant::Loser).collect();
+        let mut survivors: Vec<usize> = first_losers
+            .chunks(2)
+            .map(|pair| self.push(BracketSection::Losers, 1, pair[0], pair[1]))
+            .collect();
+        let mut round = 1;
+
+        for (depth, winners_round) in main.iter().enumerate().skip(1) {
+            round += 1;
+            survivors = survivors
+                .iter()
+                .zip(winners_round.iter().rev())
+                .map(|(survivor, dropped)| {
+                    self.push(
+                        BracketSection::Losers,
+                        round,
+                        Entrant::Winner(*survivor),
+                        Entrant::Loser(*dropped),
+                    )
+                })
+                .collect();
+
+            if depth < main.len() - 1 {
+                round += 1;
+                survivors = survivors
+                    .chunks(2)
+                    .map(|pair| {
+                        self.push(
+                            BracketSection::Losers,
+                            round,
+                            Entrant::Winner(pair[0]),
+                            Entrant::Winner(pair[1]),
+                        )
+                    })
+                    .collect();
+            }
+        }
+
+        let winners_final = main[main.len() - 1][0];
+        self.push(
+            BracketSection::GrandFinal,
+            1,
+            Entrant::Winner(winners_final),
+            Entrant::Winner(survivors[0]),
+        );
+    }
+}
+
+/// The seeds in draw order for a bracket of `size`, a power of two, so that
+/// seeds 1 and 2 can only meet in the final, 1 to 4 in the semi-finals and
+/// so on.
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let count = order.len() * 2;
+        order = order
+            .into_iter()
+            .flat_map(|seed| [seed, count + 1 - seed])
+            .collect();
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{MatchDetails, ScoringSystem, SeasonError};
+
+    fn seeds(count: usize) -> Vec<ParticipantId> {
+        (0..count).map(|_| ParticipantId::new()).collect()
+    }
+
+    fn single(third_place: bool, plate: bool) -> BracketFormat {
+        BracketFormat::SingleElimination { third_place, plate }
+    }
+
+    /// Decide every match for the winning side and advance the bracket.
+    fn play_round(bracket: &mut Bracket, matches: Vec<Match>, winner: Side) -> Vec<Match> {
+        let mut next = Vec::new();
+        for mut a_match in matches {
+            if let MatchDetails::Chess { result, .. } = &mut a_match.details {
+                *result = Some(MatchOutcome::Win(winner));
+            }
+            next.extend(bracket.advance(&a_match));
+        }
+        next
+    }
+
+    #[test]
+    fn seed_order_keeps_top_seeds_apart() {
+        assert_eq!(seed_order(2), [1, 2]);
+        assert_eq!(seed_order(4), [1, 4, 2, 3]);
+        assert_eq!(seed_order(8), [1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn byes_go_to_the_top_seeds() {
+        let seeds = seeds(6);
+        let mut bracket = Bracket::new(
+            &SeasonId::new(),
+            "Finals",
+            Sport::Chess,
+            single(false, false),
+            seeds.clone(),
+        );
+
+        // 8 places, so seeds 1 and 2 have byes into the semi-finals.
+        assert_eq!(bracket.matches.len(), 7);
+        assert_eq!(
+            bracket.occupants(0),
+            (Occupant::Participant(seeds[0]), Occupant::Bye)
+        );
+        assert_eq!(bracket.result(0).0, Occupant::Participant(seeds[0]));
+
+        let first = bracket.schedule(&RoundId::new(), &VenueId::new());
+        assert_eq!(first.len(), 2);
+        assert_eq!(
+            first[0].details.participants(),
+            [seeds[3], seeds[4]],
+            "seed 4 plays seed 5"
+        );
+    }
+
+    #[test]
+    fn winners_advance_to_the_final() {
+        let seeds = seeds(4);
+        let mut bracket = Bracket::new(
+            &SeasonId::new(),
+            "Finals",
+            Sport::Chess,
+            single(true, false),
+            seeds.clone(),
+        );
+
+        let semi_finals = bracket.schedule(&RoundId::new(), &VenueId::new());
+        assert_eq!(semi_finals.len(), 2);
+        assert_eq!(bracket.champion(), None);
+
+        // The home sides, seeds 1 and 2, win.
+        let finals = play_round(&mut bracket, semi_finals, Side::Home);
+        assert_eq!(finals.len(), 2, "final and third-place play-off");
+        assert_eq!(finals[0].details.participants(), [seeds[0], seeds[1]]);
+        assert_eq!(finals[1].details.participants(), [seeds[3], seeds[2]]);
+
+        play_round(&mut bracket, finals, Side::Away);
+        assert_eq!(bracket.champion(), Some(seeds[1]));
+    }
+
+    #[test]
+    fn confirming_results_advances_the_bracket() {
+        let seeds = seeds(4);
+        let mut bracket = Bracket::new(
+            &SeasonId::new(),
+            "Finals",
+            Sport::Chess,
+            single(false, false),
+            seeds.clone(),
+        );
+        let mut semi_finals = bracket.schedule(&RoundId::new(), &VenueId::new());
+        let scoring = ScoringSystem::default();
+
+        // A drawn knockout match can't be confirmed, so its result can be
+        // corrected.
+        if let MatchDetails::Chess { result, .. } = &mut semi_finals[0].details {
+            *result = Some(MatchOutcome::Draw);
+        }
+        assert_eq!(
+            semi_finals[0].confirm(Some(&mut bracket), None, &scoring),
+            Err(SeasonError::KnockoutUndecided(semi_finals[0].id))
+        );
+        assert!(!semi_finals[0].confirmed);
+
+        for a_match in &mut semi_finals {
+            if let MatchDetails::Chess { result, .. } = &mut a_match.details {
+                *result = Some(MatchOutcome::Win(Side::Home));
+            }
+        }
+        assert_eq!(
+            semi_finals[0].confirm(Some(&mut bracket), None, &scoring),
+            Ok(Vec::new())
+        );
+        assert!(semi_finals[0].confirmed);
+        assert_eq!(
+            semi_finals[0].confirm(Some(&mut bracket), None, &scoring),
+            Ok(Vec::new()),
+            "confirming again changes nothing"
+        );
+
+        let finals = semi_finals[1]
+            .confirm(Some(&mut bracket), None, &scoring)
+            .unwrap();
+        assert_eq!(finals.len(), 1);
+        assert_eq!(finals[0].details.participants(), [seeds[0], seeds[1]]);
+    }
+
+    #[test]
+    fn plate_for_first_round_losers() {
+        let bracket = Bracket::new(
+            &SeasonId::new(),
+            "Finals",
+            Sport::Chess,
+            single(false, true),
+            seeds(8),
+        );
+
+        let plate: Vec<&BracketMatch> = bracket
+            .matches
+            .iter()
+            .filter(|m| m.section == BracketSection::Plate)
+            .collect();
+        assert_eq!(plate.len(), 3);
+        assert_eq!(plate[0].home, Entrant::Loser(0));
+        assert_eq!(plate[0].away, Entrant::Loser(1));
+    }
+
+    #[test]
+    fn double_elimination() {
+        let seeds = seeds(4);
+        let mut bracket = Bracket::new(
+            &SeasonId::new(),
+            "Finals",
+            Sport::Chess,
+            BracketFormat::DoubleElimination,
+            seeds.clone(),
+        );
+
+        // 3 winners bracket, 2 losers bracket and the grand final.
+        assert_eq!(bracket.matches.len(), 6);
+
+        let round_id = RoundId::new();
+        let first = bracket.schedule(&round_id, &VenueId::new());
+        let second = play_round(&mut bracket, first, Side::Home);
+        assert_eq!(second.len(), 2, "winners final and first losers match");
+
+        // Seed 2 wins the winners final and seed 3 knocks out seed 4.
+        let third = play_round(&mut bracket, second, Side::Away);
+        assert_eq!(third.len(), 1);
+        assert_eq!(third[0].details.participants(), [seeds[2], seeds[0]]);
+        assert_eq!(third[0].round_id, round_id);
+
+        // Seed 1 comes back through the losers bracket to win the grand final.
+        let grand_final = play_round(&mut bracket, third, Side::Away);
+        assert_eq!(grand_final[0].details.participants(), [seeds[1], seeds[0]]);
+        play_round(&mut bracket, grand_final, Side::Away);
+        assert_eq!(bracket.champion(), Some(seeds[0]));
+    }
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let season_uuid = Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let seed_uuid = Uuid::parse_str("22222222-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let seed = ParticipantId::from(seed_uuid);
+
+        let mut bracket = Bracket::new(
+            &SeasonId::from(season_uuid),
+            "Finals",
+            Sport::Chess,
+            single(false, false),
+            vec![seed],
+        );
+        bracket.id = BracketId::from(uuid);
+
+        let bson = bson::to_document(&bracket).unwrap();
+
+        let expected_bson = doc! {
+            "_id": bracket.id,
+            "season_id": bracket.season_id,
+            "name": "Finals",
+            "sport": "Chess",
+            "format": { "SingleElimination": { "third_place": false, "plate": false } },
+            "seeds": bson::to_bson(&bracket.seeds).unwrap(),
+            "matches": [{
+                "section": "Main",
+                "round": bson::to_bson(&1usize).unwrap(),
+                "home": { "Seed": bson::to_bson(&1usize).unwrap() },
+                "away": { "Seed": bson::to_bson(&2usize).unwrap() },
+                "winner": null,
+            }],
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{ChallengeId, LadderError, LadderRules, LeagueId, MatchOutcome, ParticipantId};
+
+with_prefix!(prefix_league "league");
+with_prefix!(prefix_challenger "challenger");
+with_prefix!(prefix_defender "defender");
+
+/// A challenge between two players on a ladder.
+///
+/// The challenged player, the defender, has until the deadline to accept or
+/// decline. Once accepted the deadline moves on to the date the match must be
+/// played by.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Challenge {
+    // Flatten will inline this field into its parent.
+    // See https://serde.rs/attr-flatten.html
+    #[serde(flatten)]
+    pub id: ChallengeId,
+    #[serde(flatten, with = "prefix_league")]
+    pub league_id: LeagueId,
+    #[serde(flatten, with = "prefix_challenger")]
+    pub challenger: ParticipantId,
+    #[serde(flatten, with = "prefix_defender")]
+    pub defender: ParticipantId,
+    pub issued: DateTime<Utc>,
+    pub deadline: DateTime<Utc>,
+    pub status: ChallengeStatus,
+    /// The result once played, with the challenger as the home side.
+    pub result: Option<MatchOutcome>,
+}
+
+/// Where a challenge is in its life.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum ChallengeStatus {
+    /// Waiting for the defender to respond.
+    Issued,
+    /// Waiting to be played.
+    Accepted,
+    Declined,
+    Played,
+    /// The deadline passed without a response or without being played.
+    Expired,
+}
+
+impl Challenge {
+    /// Issue a new challenge, to be answered within the ladder's response
+    /// time.
+    pub fn new(
+        league_id: &LeagueId,
+        challenger: &ParticipantId,
+        defender: &ParticipantId,
+        rules: &LadderRules,
+        now: DateTime<Utc>,
+    ) -> Challenge {
+        Challenge {
+            id: ChallengeId::new(),
+            league_id: *league_id,
+            challenger: *challenger,
+            defender: *defender,
+            issued: now,
+            deadline: now + TimeDelta::days(rules.response_days.into()),
+            status: ChallengeStatus::Issued,
+            result: None,
+        }
+    }
+
+    /// Whether the challenge is still to be answered or played.
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            ChallengeStatus::Issued | ChallengeStatus::Accepted
+        )
+    }
+
+    /// Whether the participant is either side of the challenge.
+    pub fn involves(&self, participant_id: &ParticipantId) -> bool {
+        self.challenger == *participant_id || self.defender == *participant_id
+    }
+
+    /// Accept the challenge, giving the players the ladder's playing time to
+    /// play it.
+    pub fn accept(&mut self, rules: &LadderRules, now: DateTime<Utc>) -> Result<(), LadderError> {
+        self.respond(now)?;
+        self.status = ChallengeStatus::Accepted;
+        self.deadline = now + TimeDelta::days(rules.play_days.into());
+        Ok(())
+    }
+
+    /// Decline the challenge, leaving the ladder as it is.
+    pub fn decline(&mut self, now: DateTime<Utc>) -> Result<(), LadderError> {
+        self.respond(now)?;
+        self.status = ChallengeStatus::Declined;
+        Ok(())
+    }
+
+    /// Check the challenge can still be answered.
+    fn respond(&self, now: DateTime<Utc>) -> Result<(), LadderError> {
+        if self.status != ChallengeStatus::Issued {
+            return Err(LadderError::NotOpen(self.status));
+        }
+        if now > self.deadline {
+            return Err(LadderError::PastDeadline);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    fn challenge(now: DateTime<Utc>) -> Challenge {
+        Challenge::new(
+            &LeagueId::new(),
+            &ParticipantId::new(),
+            &ParticipantId::new(),
+            &LadderRules::default(),
+            now,
+        )
+    }
+
+    #[test]
+    fn accept_before_the_deadline() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let rules = LadderRules::default();
+
+        let mut accepted = challenge(now);
+        let later = now + TimeDelta::days(2);
+        assert_eq!(accepted.accept(&rules, later), Ok(()));
+        assert_eq!(accepted.status, ChallengeStatus::Accepted);
+        assert_eq!(accepted.deadline, later + TimeDelta::days(14));
+        assert_eq!(
+            accepted.decline(later),
+            Err(LadderError::NotOpen(ChallengeStatus::Accepted))
+        );
+
+        let mut late = challenge(now);
+        assert_eq!(
+            late.accept(&rules, now + TimeDelta::days(8)),
+            Err(LadderError::PastDeadline)
+        );
+        assert!(late.is_open());
+
+        let mut declined = challenge(now);
+        assert_eq!(declined.decline(now), Ok(()));
+        assert!(!declined.is_open());
+    }
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let league_uuid = Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let challenger_uuid = Uuid::parse_str("22222222-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let defender_uuid = Uuid::parse_str("33333333-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+
+        let mut challenge = Challenge::new(
+            &LeagueId::from(league_uuid),
+            &ParticipantId::from(challenger_uuid),
+            &ParticipantId::from(defender_uuid),
+            &LadderRules::default(),
+            now,
+        );
+        challenge.id = ChallengeId::from(uuid);
+
+        let bson = bson::to_document(&challenge).unwrap();
+
+        let expected_bson = doc! {
+            "_id": uuid,
+            "league_id": league_uuid,
+            "challenger_id": challenger_uuid,
+            "defender_id": defender_uuid,
+            "issued": "2024-01-01T12:00:00Z",
+            "deadline": "2024-01-08T12:00:00Z",
+            "status": "Issued",
+            "result": null,
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+}
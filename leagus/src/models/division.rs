@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Match, Participant, PointsTable, RankedEntry, ScoringSystem, TieBreaker};
+
+/// A tier of a league.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Division {
+    pub name: String,
+    /// The number relegated from this division, and promoted from the one
+    /// below, at the end of each season.
+    #[serde(default)]
+    pub exchange: usize,
+}
+
+/// The participants of a division in a season, with its own points table.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SeasonDivision {
+    pub name: String,
+    pub table: PointsTable,
+}
+
+impl Division {
+    pub fn new(name: &str, exchange: usize) -> Division {
+        Division {
+            name: name.to_string(),
+            exchange,
+        }
+    }
+}
+
+impl SeasonDivision {
+    /// Create a division with no participants (yet).
+    pub fn new(name: &str) -> SeasonDivision {
+        SeasonDivision {
+            name: name.to_string(),
+            table: PointsTable::new(),
+        }
+    }
+
+    /// Rank the division using the tie-breakers.
+    ///
+    /// The matches may include those of other divisions, the table is rebuilt
+    /// from only those played between the division's participants.
+    pub fn standings(
+        &self,
+        tie_breakers: &[TieBreaker],
+        scoring: &ScoringSystem,
+        matches: &[Match],
+    ) -> Vec<RankedEntry> {
+        let matches: Vec<Match> = matches
+            .iter()
+            .filter(|a_match| {
+                a_match
+                    .details
+                    .participants()
+                    .iter()
+                    .all(|id| self.table.entry(id).is_some())
+            })
+            .cloned()
+            .collect();
+        let participants: Vec<Participant> = self
+            .table
+            .entries
+            .iter()
+            .map(|entry| Participant {
+                id: entry.participant_id,
+                name: entry.participant_name.clone(),
+                team: None,
+            })
+            .collect();
+        PointsTable::from_matches(&participants, &matches, scoring).rank(
+            tie_breakers,
+            scoring,
+            &matches,
+        )
+    }
+}
+
+/// Move participants between divisions at the end of a season.
+///
+/// The standings are those of each division, top division first. Across each
+/// boundary the bottom of the upper division swap with the top of the lower
+/// one, as many as the upper division's exchange. Returns the participants of
+/// each division for the next season, those relegated into it first and those
+/// promoted into it last.
+///
+/// A division too small to both promote and relegate its full exchange
+/// promotes first and relegates whoever is left.
+pub fn promote_and_relegate(
+    divisions: &[Division],
+    standings: &[Vec<RankedEntry>],
+) -> Vec<Vec<Participant>> {
+    let mut next: Vec<Vec<Participant>> = vec![Vec::new(); divisions.len()];
+
+    for (index, division) in divisions.iter().enumerate() {
+        let ranked: Vec<Participant> = standings
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(|ranked| Participant {
+                id: ranked.entry.participant_id,
+                name: ranked.entry.participant_name.clone(),
+                team: None,
+            })
+            .collect();
+
+        let promoted = match index {
+            0 => 0,
+            _ => divisions[index - 1].exchange.min(ranked.len()),
+        };
+        let relegated = match divisions.get(index + 1) {
+            Some(_) => division.exchange.min(ranked.len() - promoted),
+            None => 0,
+        };
+        let staying = ranked.len() - relegated;
+
+        if promoted > 0 {
+            next[index - 1].extend_from_slice(&ranked[..promoted]);
+        }
+        next[index].extend_from_slice(&ranked[promoted..staying]);
+        if relegated > 0 {
+            next[index + 1].extend_from_slice(&ranked[staying..]);
+        }
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{MatchDetails, MatchOutcome, RoundId, Side, VenueId};
+
+    fn division(names: &[&str]) -> SeasonDivision {
+        let mut division = SeasonDivision::new("Division");
+        for name in names {
+            division
+                .table
+                .add_participant(&Participant::new(name.to_string()));
+        }
+        division
+    }
+
+    fn names(participants: &[Participant]) -> Vec<&str> {
+        participants
+            .iter()
+            .map(|participant| participant.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn standings_only_use_matches_within_the_division() {
+        let division = division(&["Bob", "Ann"]);
+        let ann = division.table.entries[1].participant_id;
+        let cat = Participant::new("Cat".to_string()).id;
+
+        // Cat, from another division, beat Ann. Counting it would put Ann
+        // ahead of Bob on Buchholz.
+        let mut elsewhere = Match::new(RoundId::new(), VenueId::new());
+        elsewhere.details = MatchDetails::Chess {
+            players: (cat, ann),
+            result: Some(MatchOutcome::Win(Side::Home)),
+        };
+
+        let standings = division.standings(
+            &[TieBreaker::Buchholz],
+            &ScoringSystem::default(),
+            &[elsewhere],
+        );
+        let order: Vec<(usize, &str)> = standings
+            .iter()
+            .map(|ranked| (ranked.position, ranked.entry.participant_name.as_str()))
+            .collect();
+        assert_eq!(order, [(1, "Bob"), (1, "Ann")]);
+    }
+
+    #[test]
+    fn swap_across_each_boundary() {
+        let divisions = [
+            Division::new("Premier", 2),
+            Division::new("Championship", 1),
+            Division::new("League One", 0),
+        ];
+        let standings = [
+            division(&["P1", "P2", "P3", "P4"]),
+            division(&["C1", "C2", "C3", "C4"]),
+            division(&["L1", "L2", "L3"]),
+        ]
+        .iter()
+        .map(|division| division.standings(&[], &ScoringSystem::default(), &[]))
+        .collect::<Vec<_>>();
+
+        let next = promote_and_relegate(&divisions, &standings);
+
+        assert_eq!(names(&next[0]), ["P1", "P2", "C1", "C2"]);
+        assert_eq!(names(&next[1]), ["P3", "P4", "C3", "L1"]);
+        assert_eq!(names(&next[2]), ["C4", "L2", "L3"]);
+    }
+
+    #[test]
+    fn small_divisions_promote_first() {
+        let divisions = [
+            Division::new("Premier", 2),
+            Division::new("Championship", 2),
+            Division::new("League One", 0),
+        ];
+        let standings = [
+            division(&["P1", "P2", "P3"]),
+            division(&["C1", "C2", "C3"]),
+            division(&["L1", "L2"]),
+        ]
+        .iter()
+        .map(|division| division.standings(&[], &ScoringSystem::default(), &[]))
+        .collect::<Vec<_>>();
+
+        let next = promote_and_relegate(&divisions, &standings);
+
+        assert_eq!(names(&next[0]), ["P1", "C1", "C2"]);
+        assert_eq!(names(&next[1]), ["P2", "P3", "L1", "L2"]);
+        assert_eq!(names(&next[2]), ["C3"]);
+    }
+}
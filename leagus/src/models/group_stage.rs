@@ -0,0 +1,348 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{
+    BracketId, GroupStageId, Match, ParticipantId, PointsTable, RankedEntry, RoundId,
+    ScoringSystem, SeasonId, TieBreaker,
+};
+
+with_prefix!(prefix_season "season");
+with_prefix!(prefix_bracket "bracket");
+
+/// Pool play ahead of a knockout.
+///
+/// Participants are split into groups, each playing a round-robin in rounds of
+/// its own and ranked in a mini points table. The best of each group then go
+/// through to a knockout [`Bracket`](super::Bracket).
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct GroupStage {
+    // Flatten will inline this field into its parent.
+    // See https://serde.rs/attr-flatten.html
+    #[serde(flatten)]
+    pub id: GroupStageId,
+    #[serde(flatten, with = "prefix_season")]
+    pub season_id: SeasonId,
+    pub name: String,
+    pub groups: Vec<Group>,
+    pub qualification: Qualification,
+    /// The knockout the qualifiers went through to, once it has been drawn.
+    #[serde(default, flatten, with = "prefix_bracket")]
+    pub bracket_id: Option<BracketId>,
+}
+
+/// A group of participants who play each other.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub table: PointsTable,
+    /// The ordered tie-breakers used to rank participants level on points.
+    #[serde(default)]
+    pub tie_breakers: Vec<TieBreaker>,
+    /// The rounds the group's matches are played in.
+    pub round_ids: Vec<RoundId>,
+}
+
+/// Who goes through from the groups to the knockout.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct Qualification {
+    /// The number of places in every group which go through.
+    pub per_group: usize,
+    /// The number of the next placed, across all groups, which also go
+    /// through, e.g. the best third-placed.
+    #[serde(default)]
+    pub best_of_next: usize,
+}
+
+impl GroupStage {
+    /// Create a new [`GroupStage`] with a generated id.
+    pub fn new(
+        season_id: &SeasonId,
+        name: &str,
+        groups: Vec<Group>,
+        qualification: Qualification,
+    ) -> GroupStage {
+        GroupStage {
+            id: GroupStageId::new(),
+            season_id: *season_id,
+            name: name.to_string(),
+            groups,
+            qualification,
+            bracket_id: None,
+        }
+    }
+
+    /// The group whose matches are played in the round.
+    pub fn group_for_round(&self, round_id: &RoundId) -> Option<&Group> {
+        self.groups
+            .iter()
+            .find(|group| group.round_ids.contains(round_id))
+    }
+
+    /// Record the result of a match in the table of its group, returning
+    /// whether it was one of the group stage's matches.
+    pub fn record(&mut self, a_match: &Match, scoring: &ScoringSystem) -> bool {
+        let group = self
+            .groups
+            .iter_mut()
+            .find(|group| group.round_ids.contains(&a_match.round_id));
+
+        match group {
+            Some(group) => {
+                group.table.record(&a_match.scored_details(), scoring);
+                group.table.sort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The participants who go through to the knockout, in seed order.
+    ///
+    /// Those who finished in the same place in their groups are seeded
+    /// together, better places first. Within a place they are ordered by
+    /// points, then score difference and then score, which also decides the
+    /// best of the next placed.
+    pub fn qualifiers(&self, scoring: &ScoringSystem, matches: &[Match]) -> Vec<ParticipantId> {
+        let standings: Vec<Vec<RankedEntry>> = self
+            .groups
+            .iter()
+            .map(|group| group.standings(scoring, matches))
+            .collect();
+        let placed = |place: usize| -> Vec<&RankedEntry> {
+            let mut entries: Vec<&RankedEntry> = standings
+                .iter()
+                .filter_map(|ranked| ranked.get(place))
+                .collect();
+            entries.sort_by(|a, b| compare_across_groups(a, b));
+            entries
+        };
+
+        let per_group = self.qualification.per_group;
+        (0..per_group)
+            .flat_map(placed)
+            .chain(
+                placed(per_group)
+                    .into_iter()
+                    .take(self.qualification.best_of_next),
+            )
+            .map(|ranked| ranked.entry.participant_id)
+            .collect()
+    }
+}
+
+impl Group {
+    /// Create a group with an empty table.
+    pub fn new(name: &str, tie_breakers: Vec<TieBreaker>) -> Group {
+        Group {
+            name: name.to_string(),
+            table: PointsTable::new(),
+            tie_breakers,
+            round_ids: Vec::new(),
+        }
+    }
+
+    /// Rank the group's table using its tie-breakers.
+    ///
+    /// The matches may include those of other groups, only the group's own
+    /// are used to settle head-to-head ties.
+    pub fn standings(&self, scoring: &ScoringSystem, matches: &[Match]) -> Vec<RankedEntry> {
+        let matches: Vec<Match> = matches
+            .iter()
+            .filter(|a_match| self.round_ids.contains(&a_match.round_id))
+            .cloned()
+            .collect();
+        self.table.rank(&self.tie_breakers, scoring, &matches)
+    }
+}
+
+/// Order participants from different groups, best first.
+fn compare_across_groups(a: &RankedEntry, b: &RankedEntry) -> Ordering {
+    let (a, b) = (&a.entry, &b.entry);
+    b.points
+        .cmp(&a.points)
+        .then(b.score_difference().cmp(&a.score_difference()))
+        .then(b.score_for.cmp(&a.score_for))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{MatchDetails, Participant, VenueId};
+
+    fn group(name: &str, participants: &[&Participant]) -> Group {
+        let mut group = Group::new(name, vec![TieBreaker::HeadToHead]);
+        group.round_ids.push(RoundId::new());
+        for participant in participants {
+            group.table.add_participant(participant);
+        }
+        group
+    }
+
+    fn football(group: &Group, home: &Participant, away: &Participant, score: (u32, u32)) -> Match {
+        let mut a_match = Match::new(group.round_ids[0], VenueId::new());
+        a_match.details = MatchDetails::HeadToHeadScore {
+            players: (home.id, away.id),
+            score: Some(score),
+        };
+        a_match
+    }
+
+    #[test]
+    fn record_in_the_group_of_the_round() {
+        let ann = Participant::new("Ann".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let a = group("Group A", &[&ann, &bob]);
+        let b = group("Group B", &[]);
+        let mut stage = GroupStage::new(
+            &SeasonId::new(),
+            "Pools",
+            vec![a.clone(), b],
+            Qualification {
+                per_group: 1,
+                best_of_next: 0,
+            },
+        );
+
+        let a_match = football(&a, &bob, &ann, (2, 0));
+        assert!(stage.record(&a_match, &ScoringSystem::default()));
+        assert_eq!(
+            stage.group_for_round(&a.round_ids[0]).unwrap().name,
+            "Group A"
+        );
+
+        let table = &stage.groups[0].table;
+        assert_eq!(table.entries[0].participant_name, "Bob");
+        assert_eq!(table.entries[0].points, 3);
+
+        let elsewhere = Match::new(RoundId::new(), VenueId::new());
+        assert!(!stage.record(&elsewhere, &ScoringSystem::default()));
+    }
+
+    #[test]
+    fn confirming_a_result_records_it_once() {
+        let ann = Participant::new("Ann".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let a = group("Group A", &[&ann, &bob]);
+        let mut stage = GroupStage::new(
+            &SeasonId::new(),
+            "Pools",
+            vec![a.clone()],
+            Qualification {
+                per_group: 1,
+                best_of_next: 0,
+            },
+        );
+
+        let mut a_match = football(&a, &ann, &bob, (1, 1));
+        let scoring = ScoringSystem::default();
+        assert_eq!(
+            a_match.confirm(None, Some(&mut stage), &scoring),
+            Ok(Vec::new())
+        );
+        a_match.confirm(None, Some(&mut stage), &scoring).unwrap();
+
+        let table = &stage.groups[0].table;
+        assert_eq!(
+            table.entries[0].draws, 1,
+            "confirming again changes nothing"
+        );
+        assert_eq!(table.entries[0].points, 1);
+        assert_eq!(table.entries[1].points, 1);
+    }
+
+    #[test]
+    fn qualify_top_of_each_group_and_best_runners_up() {
+        let players: Vec<Participant> = ["A1", "A2", "A3", "B1", "B2", "B3", "C1", "C2", "C3"]
+            .into_iter()
+            .map(|name| Participant::new(name.to_string()))
+            .collect();
+        let [a1, a2, a3, b1, b2, b3, c1, c2, c3] = players.try_into().unwrap();
+
+        let groups = vec![
+            group("Group A", &[&a1, &a2, &a3]),
+            group("Group B", &[&b1, &b2, &b3]),
+            group("Group C", &[&c1, &c2, &c3]),
+        ];
+        let matches = vec![
+            // A1 wins both, A2 beats A3.
+            football(&groups[0], &a1, &a2, (2, 0)),
+            football(&groups[0], &a1, &a3, (1, 0)),
+            football(&groups[0], &a2, &a3, (3, 0)),
+            // B1 wins both, B2 draws with B3.
+            football(&groups[1], &b1, &b2, (5, 0)),
+            football(&groups[1], &b1, &b3, (4, 0)),
+            football(&groups[1], &b2, &b3, (1, 1)),
+            // C1 and C2 draw, both beat C3.
+            football(&groups[2], &c1, &c2, (0, 0)),
+            football(&groups[2], &c1, &c3, (2, 1)),
+            football(&groups[2], &c2, &c3, (1, 0)),
+        ];
+
+        let scoring = ScoringSystem::default();
+        let mut stage = GroupStage::new(
+            &SeasonId::new(),
+            "Pools",
+            groups,
+            Qualification {
+                per_group: 1,
+                best_of_next: 2,
+            },
+        );
+        for a_match in &matches {
+            stage.record(a_match, &scoring);
+        }
+
+        // B1 has the best score difference of the group winners. C1 and C2
+        // are level on every tie-breaker so share first place, with C2 listed
+        // second and taking the best of the next placed on points.
+        assert_eq!(
+            stage.qualifiers(&scoring, &matches),
+            [b1.id, a1.id, c1.id, c2.id, a2.id]
+        );
+    }
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let season_uuid = Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let round_uuid = Uuid::parse_str("22222222-2248-4345-80ec-b88499f9ff1e").unwrap();
+
+        let mut group = Group::new("Group A", Vec::new());
+        group.round_ids.push(RoundId::from(round_uuid));
+        let mut stage = GroupStage::new(
+            &SeasonId::from(season_uuid),
+            "Pools",
+            vec![group],
+            Qualification {
+                per_group: 2,
+                best_of_next: 0,
+            },
+        );
+        stage.id = GroupStageId::from(uuid);
+
+        let bson = bson::to_document(&stage).unwrap();
+
+        let expected_bson = doc! {
+            "_id": stage.id,
+            "season_id": stage.season_id,
+            "name": "Pools",
+            "groups": [{
+                "name": "Group A",
+                "table": { "entries": [] },
+                "tie_breakers": [],
+                "round_ids": bson::to_bson(&stage.groups[0].round_ids).unwrap(),
+            }],
+            "qualification": {
+                "per_group": bson::to_bson(&2usize).unwrap(),
+                "best_of_next": bson::to_bson(&0usize).unwrap(),
+            },
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{Match, MatchDetails, MatchOutcome, ParticipantId, RatingSystem, SessionId, Side};
+
+with_prefix!(prefix_participant "participant");
+
+/// The handicaps of a season, giving weaker participants a start so that
+/// mixed-ability matches stay competitive.
+///
+/// A start is a number of games, or points, a participant begins each set
+/// with. Starts are derived from ratings, or set by hand, and adjusted after
+/// each session from the handicapped results.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Handicaps {
+    pub rules: HandicapRules,
+    #[serde(default)]
+    pub starts: Vec<Handicap>,
+    /// The sessions the starts have already been adjusted after.
+    #[serde(default)]
+    pub adjusted: Vec<SessionId>,
+}
+
+/// How starts are derived from ratings.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct HandicapRules {
+    /// The rating points one game, or point, of start is worth.
+    pub rating_per_start: f64,
+    /// The largest start anyone is given.
+    pub max_start: u8,
+    #[serde(default)]
+    pub system: RatingSystem,
+}
+
+/// The start a participant is given.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Handicap {
+    #[serde(flatten, with = "prefix_participant")]
+    pub participant_id: ParticipantId,
+    pub start: u8,
+    /// Set by hand, and so left alone when starts are derived or adjusted.
+    #[serde(default)]
+    pub manual: bool,
+}
+
+/// A change to a participant's start after a session.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct StartChange {
+    pub participant_id: ParticipantId,
+    pub before: u8,
+    pub after: u8,
+}
+
+impl Handicaps {
+    pub fn new(rules: HandicapRules) -> Handicaps {
+        Handicaps {
+            rules,
+            starts: Vec::new(),
+            adjusted: Vec::new(),
+        }
+    }
+
+    /// The start a participant is given, none if they have no handicap.
+    pub fn start(&self, participant_id: &ParticipantId) -> u8 {
+        self.handicap(participant_id)
+            .map(|handicap| handicap.start)
+            .unwrap_or(0)
+    }
+
+    /// Get the handicap of a participant.
+    pub fn handicap(&self, participant_id: &ParticipantId) -> Option<&Handicap> {
+        self.starts
+            .iter()
+            .find(|handicap| handicap.participant_id == *participant_id)
+    }
+
+    /// Set a participant's start by hand. It is kept until cleared, however
+    /// they play.
+    pub fn set(&mut self, participant_id: &ParticipantId, start: u8) {
+        self.set_start(participant_id, start, true);
+    }
+
+    /// Clear a participant's handicap so their start is derived again.
+    /// Returns `false` if they have no handicap.
+    pub fn clear(&mut self, participant_id: &ParticipantId) -> bool {
+        let handicaps = self.starts.len();
+        self.starts
+            .retain(|handicap| handicap.participant_id != *participant_id);
+        self.starts.len() < handicaps
+    }
+
+    /// Derive the starts of the participants from their ratings.
+    ///
+    /// The strongest rated participant plays off scratch and everyone else is
+    /// given a game, or point, for every `rating_per_start` they are rated
+    /// below them, up to `max_start`. Participants without a rating, or with a
+    /// start set by hand, are left as they are.
+    pub fn derive(&mut self, participants: &[ParticipantId], skills: &HashMap<ParticipantId, f64>) {
+        let rated: Vec<(ParticipantId, f64)> = participants
+            .iter()
+            .filter_map(|id| skills.get(id).map(|skill| (*id, *skill)))
+            .collect();
+        let Some(best) = rated.iter().map(|(_, skill)| *skill).reduce(f64::max) else {
+            return;
+        };
+
+        for (participant_id, skill) in rated {
+            if self
+                .handicap(&participant_id)
+                .is_some_and(|handicap| handicap.manual)
+            {
+                continue;
+            }
+            let start = ((best - skill) / self.rules.rating_per_start).round();
+            let start = start.clamp(0.0, f64::from(self.rules.max_start)) as u8;
+            self.set_start(&participant_id, start, false);
+        }
+    }
+
+    /// The starts each side of a match is given.
+    ///
+    /// A pair's start is the average of the partners' starts, rounded down.
+    /// Only the difference between the sides matters, so the stronger side
+    /// plays off scratch.
+    pub fn starts(&self, details: &MatchDetails) -> Option<(u8, u8)> {
+        let (home, away) = details.sides()?;
+        let side_start = |side: &[ParticipantId]| {
+            let total: usize = side.iter().map(|id| usize::from(self.start(id))).sum();
+            (total / side.len().max(1)) as u8
+        };
+        let (home, away) = (side_start(&home), side_start(&away));
+        let scratch = home.min(away);
+
+        Some((home - scratch, away - scratch))
+    }
+
+    /// Adjust the starts after a session from its handicapped results.
+    ///
+    /// Anyone who won more matches than they lost gives up a game, or point,
+    /// of their start and anyone who lost more than they won is given one
+    /// more, up to `max_start`. Starts set by hand are left alone, and a
+    /// session is only ever adjusted after once. Returns the starts which
+    /// changed.
+    pub fn adjust(&mut self, session_id: &SessionId, matches: &[Match]) -> Vec<StartChange> {
+        if self.adjusted.contains(session_id) {
+            return Vec::new();
+        }
+        self.adjusted.push(*session_id);
+
+        let mut net_wins: HashMap<ParticipantId, i32> = HashMap::new();
+        for a_match in matches {
+            let details = a_match.scored_details();
+            let (Some(MatchOutcome::Win(winner)), Some((home, away))) =
+                (details.outcome(), details.sides())
+            else {
+                continue;
+            };
+            for (side, participants) in [(Side::Home, home), (Side::Away, away)] {
+                let won = if side == winner { 1 } else { -1 };
+                for participant_id in participants {
+                    *net_wins.entry(participant_id).or_default() += won;
+                }
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (participant_id, net) in net_wins {
+            let handicap = self.handicap(&participant_id);
+            if handicap.is_some_and(|handicap| handicap.manual) {
+                continue;
+            }
+            let before = handicap.map(|handicap| handicap.start).unwrap_or(0);
+            let after = match net.signum() {
+                1 => before.saturating_sub(1),
+                -1 => before.saturating_add(1).min(self.rules.max_start),
+                _ => before,
+            };
+            if after != before {
+                self.set_start(&participant_id, after, false);
+                changes.push(StartChange {
+                    participant_id,
+                    before,
+                    after,
+                });
+            }
+        }
+        changes
+    }
+
+    fn set_start(&mut self, participant_id: &ParticipantId, start: u8, manual: bool) {
+        match self
+            .starts
+            .iter_mut()
+            .find(|handicap| handicap.participant_id == *participant_id)
+        {
+            Some(handicap) => {
+                handicap.start = start;
+                handicap.manual = manual;
+            }
+            None => self.starts.push(Handicap {
+                participant_id: *participant_id,
+                start,
+                manual,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RoundId, VenueId};
+    use bson::{doc, Uuid};
+    use pretty_assertions::assert_eq;
+
+    fn rules() -> HandicapRules {
+        HandicapRules {
+            rating_per_start: 50.0,
+            max_start: 6,
+            system: RatingSystem::Elo,
+        }
+    }
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let participant_id = ParticipantId::from(uuid);
+        let mut handicaps = Handicaps::new(rules());
+        handicaps.set(&participant_id, 3);
+
+        let bson = bson::to_document(&handicaps).unwrap();
+
+        let expected_bson = doc! {
+            "rules": {
+                "rating_per_start": 50.0,
+                "max_start": 6,
+                "system": "Elo",
+            },
+            "starts": [{
+                "participant_id": participant_id,
+                "start": 3,
+                "manual": true,
+            }],
+            "adjusted": [],
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+
+    #[test]
+    fn derive_starts_from_ratings() {
+        let [ann, bob, cat, dan] = [(); 4].map(|_| ParticipantId::new());
+        let skills = HashMap::from([(ann, 1600.0), (bob, 1480.0), (cat, 1000.0), (dan, 1500.0)]);
+        let mut handicaps = Handicaps::new(rules());
+        handicaps.set(&dan, 1);
+
+        handicaps.derive(&[ann, bob, cat, dan], &skills);
+
+        assert_eq!(handicaps.start(&ann), 0);
+        assert_eq!(handicaps.start(&bob), 2);
+        assert_eq!(handicaps.start(&cat), 6);
+        assert_eq!(handicaps.start(&dan), 1);
+
+        let doubles = MatchDetails::TennisDoubles {
+            players: ((ann, cat), (bob, dan)),
+            scores: Vec::new(),
+        };
+        assert_eq!(handicaps.starts(&doubles), Some((2, 0)));
+    }
+
+    #[test]
+    fn adjust_after_a_session_once() {
+        let [ann, bob] = [(); 2].map(|_| ParticipantId::new());
+        let mut handicaps = Handicaps::new(rules());
+        handicaps.set_start(&bob, 4, false);
+
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::HeadToHeadScore {
+            players: (ann, bob),
+            score: Some((10, 8)),
+        };
+        a_match.starts = handicaps.starts(&a_match.details);
+
+        // Bob's start turns the loss into a win.
+        let session_id = SessionId::new();
+        let changes = handicaps.adjust(&session_id, &[a_match.clone()]);
+
+        assert_eq!(
+            changes.iter().find(|change| change.participant_id == bob),
+            Some(&StartChange {
+                participant_id: bob,
+                before: 4,
+                after: 3,
+            })
+        );
+        assert_eq!(handicaps.start(&ann), 1);
+        assert_eq!(handicaps.adjust(&session_id, &[a_match]), Vec::new());
+    }
+}
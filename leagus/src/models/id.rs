@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 
 use bson::Bson;
 use bson::Uuid;
@@ -7,7 +8,7 @@ use serde::{Deserialize, Serialize};
 /// Generic container for the various IDs.
 /// Each type of ID is really just a Uuid but is a distinct type.
 /// E.g. an ID<League> is a different type from ID<Season>.
-#[derive(Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ID<T> {
     // The mongodb id field is named "_id"
     #[serde(rename = "_id")]
@@ -45,15 +46,28 @@ impl<T> From<Uuid> for ID<T> {
 // implement Clone which we don't need or want here.
 impl<T> Clone for ID<T> {
     fn clone(&self) -> Self {
-        Self {
-            id: self.id,
-            id_type: self.id_type,
-        }
+        *self
     }
 }
 
 impl<T> Copy for ID<T> {}
 
+// As with Clone, comparing and hashing only depend on the Uuid so the type
+// parameter does not need to implement these traits.
+impl<T> PartialEq for ID<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for ID<T> {}
+
+impl<T> Hash for ID<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 impl<T> Display for ID<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.id.fmt(f)
@@ -0,0 +1,451 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{Challenge, ChallengeStatus, LeagueId, MatchOutcome, ParticipantId, Side};
+
+with_prefix!(prefix_participant "participant");
+
+/// A ladder, where players climb by challenging those above them rather than
+/// playing fixed rounds.
+///
+/// A challenger who wins swaps rungs with the defender. A defender who lets a
+/// challenge go unanswered past its deadline forfeits it, while a challenge
+/// accepted but not played in time simply lapses. Players who go too long
+/// without playing drop down the ladder.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Ladder {
+    pub rules: LadderRules,
+    /// The rungs of the ladder, top first.
+    #[serde(default)]
+    pub rungs: Vec<Rung>,
+}
+
+/// How a ladder is run.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct LadderRules {
+    /// How many rungs above themselves a player may challenge.
+    pub reach: usize,
+    /// The days a defender has to accept or decline a challenge.
+    pub response_days: u32,
+    /// The days an accepted challenge has to be played in.
+    pub play_days: u32,
+    /// The days a player may go without playing before dropping down.
+    pub inactivity_days: u32,
+    /// The rungs an inactive player drops.
+    pub inactivity_drop: usize,
+}
+
+/// A player's place on a ladder.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Rung {
+    #[serde(flatten, with = "prefix_participant")]
+    pub participant_id: ParticipantId,
+    /// When the player joined, last played or was last dropped for being
+    /// inactive.
+    pub active: DateTime<Utc>,
+}
+
+/// The reasons a ladder cannot do what was asked of it.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LadderError {
+    NotOnLadder(ParticipantId),
+    AlreadyOnLadder(ParticipantId),
+    /// Players may only challenge those within reach above them.
+    OutOfReach,
+    /// A player may only be in one open challenge at a time.
+    AlreadyChallenged(ParticipantId),
+    /// The challenge is no longer waiting for this.
+    NotOpen(ChallengeStatus),
+    PastDeadline,
+}
+
+impl Ladder {
+    /// Create an empty ladder.
+    pub fn new(rules: LadderRules) -> Ladder {
+        Ladder {
+            rules,
+            rungs: Vec::new(),
+        }
+    }
+
+    /// The rung of a participant, starting at 1 for the top.
+    pub fn position(&self, participant_id: &ParticipantId) -> Option<usize> {
+        self.rungs
+            .iter()
+            .position(|rung| rung.participant_id == *participant_id)
+            .map(|index| index + 1)
+    }
+
+    /// Add a participant to the bottom of the ladder.
+    pub fn join(
+        &mut self,
+        participant_id: &ParticipantId,
+        now: DateTime<Utc>,
+    ) -> Result<(), LadderError> {
+        if self.position(participant_id).is_some() {
+            return Err(LadderError::AlreadyOnLadder(*participant_id));
+        }
+        self.rungs.push(Rung {
+            participant_id: *participant_id,
+            active: now,
+        });
+        Ok(())
+    }
+
+    /// Issue a challenge to a defender within reach above the challenger.
+    ///
+    /// `open` are the ladder's open challenges, neither player may already be
+    /// in one of them.
+    pub fn challenge(
+        &self,
+        league_id: &LeagueId,
+        challenger: &ParticipantId,
+        defender: &ParticipantId,
+        open: &[Challenge],
+        now: DateTime<Utc>,
+    ) -> Result<Challenge, LadderError> {
+        let challenger_rung = self
+            .position(challenger)
+            .ok_or(LadderError::NotOnLadder(*challenger))?;
+        let defender_rung = self
+            .position(defender)
+            .ok_or(LadderError::NotOnLadder(*defender))?;
+        if defender_rung >= challenger_rung || challenger_rung - defender_rung > self.rules.reach {
+            return Err(LadderError::OutOfReach);
+        }
+        for player in [challenger, defender] {
+            let busy = open
+                .iter()
+                .any(|challenge| challenge.is_open() && challenge.involves(player));
+            if busy {
+                return Err(LadderError::AlreadyChallenged(*player));
+            }
+        }
+
+        Ok(Challenge::new(
+            league_id,
+            challenger,
+            defender,
+            &self.rules,
+            now,
+        ))
+    }
+
+    /// Record the result of an accepted challenge, swapping the players if
+    /// the challenger won. A result played after the deadline is refused.
+    pub fn settle(
+        &mut self,
+        challenge: &mut Challenge,
+        outcome: MatchOutcome,
+        now: DateTime<Utc>,
+    ) -> Result<(), LadderError> {
+        if challenge.status != ChallengeStatus::Accepted {
+            return Err(LadderError::NotOpen(challenge.status));
+        }
+        if now > challenge.deadline {
+            return Err(LadderError::PastDeadline);
+        }
+        challenge.status = ChallengeStatus::Played;
+        challenge.result = Some(outcome);
+
+        if outcome == MatchOutcome::Win(Side::Home) {
+            self.promote(&challenge.challenger, &challenge.defender);
+        }
+        for rung in self.rungs.iter_mut() {
+            if challenge.involves(&rung.participant_id) {
+                rung.active = now;
+            }
+        }
+        Ok(())
+    }
+
+    /// Close every open challenge whose deadline has passed, returning those
+    /// which changed.
+    ///
+    /// An unanswered challenge is forfeited by the defender, so the
+    /// challenger takes their rung.
+    pub fn expire<'a>(
+        &mut self,
+        challenges: &'a mut [Challenge],
+        now: DateTime<Utc>,
+    ) -> Vec<&'a Challenge> {
+        let mut expired = Vec::new();
+        for challenge in challenges.iter_mut() {
+            if !challenge.is_open() || now <= challenge.deadline {
+                continue;
+            }
+            if challenge.status == ChallengeStatus::Issued {
+                self.promote(&challenge.challenger, &challenge.defender);
+            }
+            challenge.status = ChallengeStatus::Expired;
+            expired.push(&*challenge);
+        }
+        expired
+    }
+
+    /// Drop every player who has not played within the inactivity period,
+    /// returning those dropped.
+    ///
+    /// The period starts again once dropped, so a player only drops once for
+    /// each period they go without playing.
+    pub fn penalise_inactive(&mut self, now: DateTime<Utc>) -> Vec<ParticipantId> {
+        let period = TimeDelta::days(self.rules.inactivity_days.into());
+        let inactive: Vec<ParticipantId> = self
+            .rungs
+            .iter()
+            .filter(|rung| now - rung.active > period)
+            .map(|rung| rung.participant_id)
+            .collect();
+
+        // Drop from the bottom up so those already dropped are not passed
+        // over again by those above them.
+        for participant_id in inactive.iter().rev() {
+            let Some(index) = self
+                .rungs
+                .iter()
+                .position(|rung| rung.participant_id == *participant_id)
+            else {
+                continue;
+            };
+            let mut rung = self.rungs.remove(index);
+            rung.active = now;
+            let index = (index + self.rules.inactivity_drop).min(self.rungs.len());
+            self.rungs.insert(index, rung);
+        }
+
+        inactive
+    }
+
+    /// Swap the winner and loser if the winner is below.
+    fn promote(&mut self, winner: &ParticipantId, loser: &ParticipantId) {
+        if let (Some(winner), Some(loser)) = (self.position(winner), self.position(loser)) {
+            if winner > loser {
+                self.rungs.swap(winner - 1, loser - 1);
+            }
+        }
+    }
+}
+
+impl Default for LadderRules {
+    /// Challenge up to three rungs above, a week to respond and two weeks to
+    /// play. Dropping a rung for each month without playing.
+    fn default() -> Self {
+        LadderRules {
+            reach: 3,
+            response_days: 7,
+            play_days: 14,
+            inactivity_days: 30,
+            inactivity_drop: 1,
+        }
+    }
+}
+
+impl Display for LadderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LadderError::NotOnLadder(participant_id) => {
+                write!(f, "participant {participant_id} is not on the ladder")
+            }
+            LadderError::AlreadyOnLadder(participant_id) => {
+                write!(f, "participant {participant_id} is already on the ladder")
+            }
+            LadderError::OutOfReach => write!(f, "the defender is not within reach above"),
+            LadderError::AlreadyChallenged(participant_id) => {
+                write!(f, "participant {participant_id} is already in a challenge")
+            }
+            LadderError::NotOpen(status) => write!(f, "the challenge is {status:?}"),
+            LadderError::PastDeadline => write!(f, "the deadline has passed"),
+        }
+    }
+}
+
+impl std::error::Error for LadderError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap()
+    }
+
+    fn ladder(count: usize) -> (Ladder, Vec<ParticipantId>) {
+        let mut ladder = Ladder::new(LadderRules::default());
+        let players: Vec<ParticipantId> = (0..count).map(|_| ParticipantId::new()).collect();
+        for player in &players {
+            ladder.join(player, start()).unwrap();
+        }
+        (ladder, players)
+    }
+
+    fn order(ladder: &Ladder) -> Vec<ParticipantId> {
+        ladder
+            .rungs
+            .iter()
+            .map(|rung| rung.participant_id)
+            .collect()
+    }
+
+    #[test]
+    fn challenge_within_reach() {
+        let (ladder, p) = ladder(6);
+        let league_id = LeagueId::new();
+
+        assert!(ladder
+            .challenge(&league_id, &p[4], &p[1], &[], start())
+            .is_ok());
+        assert_eq!(
+            ladder.challenge(&league_id, &p[4], &p[0], &[], start()),
+            Err(LadderError::OutOfReach)
+        );
+        assert_eq!(
+            ladder.challenge(&league_id, &p[1], &p[4], &[], start()),
+            Err(LadderError::OutOfReach)
+        );
+
+        let open = [ladder
+            .challenge(&league_id, &p[3], &p[2], &[], start())
+            .unwrap()];
+        assert_eq!(
+            ladder.challenge(&league_id, &p[5], &p[3], &open, start()),
+            Err(LadderError::AlreadyChallenged(p[3]))
+        );
+    }
+
+    #[test]
+    fn challenger_swaps_places_when_they_win() {
+        let (mut ladder, p) = ladder(4);
+        let league_id = LeagueId::new();
+        let later = start() + TimeDelta::days(3);
+
+        let mut won = ladder
+            .challenge(&league_id, &p[3], &p[1], &[], start())
+            .unwrap();
+        assert_eq!(
+            ladder.settle(&mut won, MatchOutcome::Win(Side::Home), later),
+            Err(LadderError::NotOpen(ChallengeStatus::Issued))
+        );
+        won.accept(&ladder.rules, start()).unwrap();
+        ladder
+            .settle(&mut won, MatchOutcome::Win(Side::Home), later)
+            .unwrap();
+        assert_eq!(order(&ladder), [p[0], p[3], p[2], p[1]]);
+        assert_eq!(ladder.rungs[1].active, later);
+
+        let mut lost = ladder
+            .challenge(&league_id, &p[2], &p[0], &[], start())
+            .unwrap();
+        lost.accept(&ladder.rules, start()).unwrap();
+        ladder
+            .settle(&mut lost, MatchOutcome::Win(Side::Away), later)
+            .unwrap();
+        assert_eq!(order(&ladder), [p[0], p[3], p[2], p[1]]);
+        assert_eq!(lost.status, ChallengeStatus::Played);
+    }
+
+    #[test]
+    fn results_after_the_deadline_are_refused() {
+        let (mut ladder, p) = ladder(4);
+        let league_id = LeagueId::new();
+
+        let mut late = ladder
+            .challenge(&league_id, &p[3], &p[2], &[], start())
+            .unwrap();
+        late.accept(&ladder.rules, start()).unwrap();
+        let deadline = late.deadline;
+        assert_eq!(
+            ladder.settle(
+                &mut late,
+                MatchOutcome::Win(Side::Home),
+                deadline + TimeDelta::seconds(1)
+            ),
+            Err(LadderError::PastDeadline)
+        );
+        assert_eq!(late.status, ChallengeStatus::Accepted);
+        assert_eq!(order(&ladder), p);
+
+        ladder
+            .settle(&mut late, MatchOutcome::Win(Side::Home), deadline)
+            .unwrap();
+        assert_eq!(order(&ladder), [p[0], p[1], p[3], p[2]]);
+    }
+
+    #[test]
+    fn unanswered_challenges_are_forfeited() {
+        let (mut ladder, p) = ladder(4);
+        let league_id = LeagueId::new();
+
+        let unanswered = ladder
+            .challenge(&league_id, &p[1], &p[0], &[], start())
+            .unwrap();
+        let mut unplayed = ladder
+            .challenge(&league_id, &p[3], &p[2], &[], start())
+            .unwrap();
+        unplayed.accept(&ladder.rules, start()).unwrap();
+        let mut challenges = [unanswered, unplayed];
+
+        assert!(ladder
+            .expire(&mut challenges, start() + TimeDelta::days(7))
+            .is_empty());
+
+        let expired = ladder.expire(&mut challenges, start() + TimeDelta::days(8));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(order(&ladder), [p[1], p[0], p[2], p[3]]);
+
+        let expired = ladder.expire(&mut challenges, start() + TimeDelta::days(15));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(order(&ladder), [p[1], p[0], p[2], p[3]]);
+        assert!(challenges
+            .iter()
+            .all(|challenge| challenge.status == ChallengeStatus::Expired));
+    }
+
+    #[test]
+    fn inactive_players_drop() {
+        let (mut ladder, p) = ladder(4);
+        ladder.rungs[3].active = start() + TimeDelta::days(20);
+        ladder.rungs[2].active = start() + TimeDelta::days(20);
+
+        let now = start() + TimeDelta::days(31);
+        assert_eq!(ladder.penalise_inactive(now), [p[0], p[1]]);
+        assert_eq!(order(&ladder), [p[2], p[0], p[1], p[3]]);
+
+        // Nobody drops again until another period passes.
+        assert!(ladder
+            .penalise_inactive(now + TimeDelta::days(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn serialize_as_bson() {
+        let participant_uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let mut ladder = Ladder::new(LadderRules::default());
+        ladder
+            .join(&ParticipantId::from(participant_uuid), start())
+            .unwrap();
+
+        let bson = bson::to_document(&ladder).unwrap();
+
+        let expected_bson = doc! {
+            "rules": {
+                "reach": bson::to_bson(&3usize).unwrap(),
+                "response_days": bson::to_bson(&7u32).unwrap(),
+                "play_days": bson::to_bson(&14u32).unwrap(),
+                "inactivity_days": bson::to_bson(&30u32).unwrap(),
+                "inactivity_drop": bson::to_bson(&1usize).unwrap(),
+            },
+            "rungs": [{
+                "participant_id": participant_uuid,
+                "active": "2024-01-01T12:00:00Z",
+            }],
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+}
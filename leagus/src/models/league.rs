@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_with::with_prefix;
 
-use super::{LeagueId, SeasonId};
+use super::{Division, Ladder, LeagueId, SeasonId, Sport};
 
 // Prefix the various IDs to avoid clashes
 with_prefix!(prefix_active_season "active_season");
@@ -19,20 +19,62 @@ pub struct League {
     #[serde(default)]
     pub description: String,
 
+    /// The sport played in the league, which all its matches must follow.
+    #[serde(default)]
+    pub sport: Sport,
+
+    /// How the league is played.
+    #[serde(default)]
+    pub format: LeagueFormat,
+
+    /// The tiers of the league, top first. Leagues without divisions have a
+    /// single table each season.
+    #[serde(default)]
+    pub divisions: Vec<Division>,
+
     #[serde(default, flatten, with = "prefix_active_season")]
     pub active_season: Option<SeasonId>,
 }
 
+/// The way a league is played.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub enum LeagueFormat {
+    /// Seasons of sessions and rounds, with a points table.
+    #[default]
+    Seasons,
+    /// A ladder of challenges, without fixed rounds.
+    Ladder(Ladder),
+}
+
 impl League {
     /// Creates a new [`League`].
-    pub fn new(name: &str, description: &str) -> League {
+    pub fn new(name: &str, description: &str, sport: Sport) -> League {
         League {
             id: LeagueId::new(),
             name: String::from(name),
             description: String::from(description),
+            sport,
+            format: LeagueFormat::default(),
+            divisions: Vec::new(),
             active_season: None,
         }
     }
+
+    /// The league's ladder, if it is played as one.
+    pub fn ladder(&self) -> Option<&Ladder> {
+        match &self.format {
+            LeagueFormat::Ladder(ladder) => Some(ladder),
+            LeagueFormat::Seasons => None,
+        }
+    }
+
+    /// The league's ladder to change, if it is played as one.
+    pub fn ladder_mut(&mut self) -> Option<&mut Ladder> {
+        match &mut self.format {
+            LeagueFormat::Ladder(ladder) => Some(ladder),
+            LeagueFormat::Seasons => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -54,6 +96,9 @@ mod tests {
             id,
             name,
             description,
+            sport: Sport::Tennis,
+            format: LeagueFormat::Seasons,
+            divisions: Vec::new(),
             active_season: None,
         };
 
@@ -63,6 +108,9 @@ mod tests {
             "_id":"a8d1d978-2248-4345-80ec-b88499f9ff1e",
             "name":"Epic",
             "description":"Tim's your uncle",
+            "sport":"Tennis",
+            "format":"Seasons",
+            "divisions":[],
         });
 
         assert_str_eq!(json, expected_json.to_string());
@@ -79,6 +127,9 @@ mod tests {
             id,
             name,
             description,
+            sport: Sport::Tennis,
+            format: LeagueFormat::Seasons,
+            divisions: Vec::new(),
             active_season: None,
         };
 
@@ -88,6 +139,9 @@ mod tests {
             "_id": uuid,
             "name": "Epic",
             "description": "Tim's your uncle",
+            "sport": "Tennis",
+            "format": "Seasons",
+            "divisions": [],
         };
 
         assert_eq!(bson, expected_bson);
@@ -107,6 +161,9 @@ mod tests {
             id,
             name,
             description,
+            sport: Sport::Tennis,
+            format: LeagueFormat::Seasons,
+            divisions: Vec::new(),
             active_season: Some(season_id),
         };
 
@@ -116,6 +173,9 @@ mod tests {
             "_id": uuid,
             "name": "Epic",
             "description": "Tim's your uncle",
+            "sport": "Tennis",
+            "format": "Seasons",
+            "divisions": [],
             "active_season_id": season_id
         };
 
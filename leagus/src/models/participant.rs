@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use super::ParticipantId;
+use super::{ParticipantId, Team};
 
 /// A participant capable of participating in matches. This could be an
 /// individual or team.
@@ -11,6 +11,9 @@ pub struct Participant {
     #[serde(flatten)]
     pub id: ParticipantId,
     pub name: String,
+    /// The members of the participant, if it's a team.
+    #[serde(default)]
+    pub team: Option<Team>,
 }
 
 impl Participant {
@@ -18,10 +21,20 @@ impl Participant {
         Participant {
             id: ParticipantId::new(),
             name,
+            team: None,
         }
     }
 }
 
+impl Participant {
+    /// Create a new team, with no members (yet).
+    pub fn new_team(name: String) -> Participant {
+        let mut team = Participant::new(name);
+        team.team = Some(Team::default());
+        team
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,13 +47,18 @@ mod tests {
         let id = ParticipantId::from(uuid);
         let name = "Jacob".to_string();
 
-        let participant = Participant { id, name };
+        let participant = Participant {
+            id,
+            name,
+            team: None,
+        };
 
         let bson = bson::to_document(&participant).unwrap();
 
         let expected_bson = doc! {
             "_id": participant.id,
             "name": participant.name,
+            "team": null,
         };
 
         assert_eq!(bson, expected_bson);
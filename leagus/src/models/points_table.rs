@@ -1,6 +1,11 @@
+use std::cmp::Reverse;
+
 use serde::{Deserialize, Serialize};
 
-use super::ParticipantId;
+use super::{
+    Match, MatchDetails, MatchOutcome, Participant, ParticipantId, RankedEntry, ScoringFormat,
+    Side, TieBreaker,
+};
 
 /// A points table for the season
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -15,6 +20,238 @@ impl PointsTable {
             entries: Vec::new(),
         }
     }
+
+    /// Build a table for the participants from the results of the matches.
+    ///
+    /// Undecided matches are ignored, as are any participants in a match who
+    /// are not in the table.
+    pub fn from_matches(
+        participants: &[Participant],
+        matches: &[Match],
+        scoring: &ScoringSystem,
+    ) -> PointsTable {
+        let mut table = PointsTable::new();
+
+        for participant in participants {
+            table.add_participant(participant);
+        }
+
+        for a_match in matches {
+            table.record(&a_match.scored_details(), scoring);
+        }
+
+        table.sort();
+        table
+    }
+
+    /// Add an entry, with no matches played, for the participant if they are
+    /// not already in the table.
+    pub fn add_participant(&mut self, participant: &Participant) {
+        if self.entry(&participant.id).is_none() {
+            self.entries.push(PointsTableEntry::new(participant));
+        }
+    }
+
+    /// A table of the same participants, in the same order, before any
+    /// matches are played.
+    pub fn cleared(&self) -> PointsTable {
+        PointsTable {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| PointsTableEntry {
+                    participant_name: entry.participant_name.clone(),
+                    participant_id: entry.participant_id,
+                    points: 0,
+                    wins: 0,
+                    draws: 0,
+                    losses: 0,
+                    score_for: 0,
+                    score_against: 0,
+                    sets_for: 0,
+                    sets_against: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Get the entry for a participant.
+    pub fn entry(&self, participant_id: &ParticipantId) -> Option<&PointsTableEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.participant_id == *participant_id)
+    }
+
+    /// Record the result of a match against every participant in it.
+    ///
+    /// In doubles matches both members of a pair share the result. Nothing is
+    /// recorded for matches which have not yet been decided.
+    pub fn record(&mut self, details: &MatchDetails, scoring: &ScoringSystem) {
+        let Some(outcome) = details.outcome() else {
+            return;
+        };
+        let totals = details.score_totals();
+        let sets = set_totals(details);
+
+        for entry in self.entries.iter_mut() {
+            let Some(side) = details.side_of(&entry.participant_id) else {
+                continue;
+            };
+
+            match outcome {
+                MatchOutcome::Win(winner) if winner == side => {
+                    entry.wins += 1;
+                    entry.points += scoring.win;
+                }
+                MatchOutcome::Win(_) => {
+                    entry.losses += 1;
+                    entry.points += scoring.loss;
+                }
+                MatchOutcome::Draw => {
+                    entry.draws += 1;
+                    entry.points += scoring.draw;
+                }
+            }
+
+            if let Some((home, away)) = totals {
+                let (scored, conceded) = match side {
+                    Side::Home => (home, away),
+                    Side::Away => (away, home),
+                };
+                entry.score_for += scored;
+                entry.score_against += conceded;
+            }
+
+            if let Some((home, away)) = sets {
+                let (won, lost) = match side {
+                    Side::Home => (home, away),
+                    Side::Away => (away, home),
+                };
+                entry.sets_for += won;
+                entry.sets_against += lost;
+            }
+        }
+    }
+
+    /// Order the entries by points, then by score difference.
+    pub fn sort(&mut self) {
+        self.entries.sort_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then(b.score_difference().cmp(&a.score_difference()))
+        });
+    }
+
+    /// Rank the entries by points, breaking ties with each of the
+    /// tie-breakers in turn.
+    ///
+    /// The matches are those the table was built from, they are needed for
+    /// the head-to-head tie-breaker. Participants still tied after every rule
+    /// share a position.
+    pub fn rank(
+        &self,
+        tie_breakers: &[TieBreaker],
+        scoring: &ScoringSystem,
+        matches: &[Match],
+    ) -> Vec<RankedEntry> {
+        let mut entries: Vec<&PointsTableEntry> = self.entries.iter().collect();
+        entries.sort_by_key(|entry| Reverse(entry.points));
+
+        let mut ranked = Vec::with_capacity(entries.len());
+        for group in entries.chunk_by(|a, b| a.points == b.points) {
+            break_ties(
+                group.to_vec(),
+                tie_breakers,
+                None,
+                scoring,
+                matches,
+                &mut ranked,
+            );
+        }
+
+        ranked
+    }
+}
+
+/// Order a group of tied entries by applying the tie-breakers in turn,
+/// appending them to `ranked`.
+///
+/// `decided_by` is the rule which separated this group from the entries
+/// around it, if any.
+fn break_ties(
+    group: Vec<&PointsTableEntry>,
+    tie_breakers: &[TieBreaker],
+    decided_by: Option<&TieBreaker>,
+    scoring: &ScoringSystem,
+    matches: &[Match],
+    ranked: &mut Vec<RankedEntry>,
+) {
+    if let [entry] = group[..] {
+        ranked.push(RankedEntry {
+            position: ranked.len() + 1,
+            entry: entry.clone(),
+            decided_by: decided_by.cloned(),
+        });
+        return;
+    }
+
+    let Some((rule, remaining)) = tie_breakers.split_first() else {
+        // Nothing left to split the tie, so everyone shares a position.
+        let position = ranked.len() + 1;
+        ranked.extend(group.into_iter().map(|entry| RankedEntry {
+            position,
+            entry: entry.clone(),
+            decided_by: None,
+        }));
+        return;
+    };
+
+    let tied: Vec<ParticipantId> = group.iter().map(|entry| entry.participant_id).collect();
+    let mut keyed: Vec<(i128, &PointsTableEntry)> = group
+        .into_iter()
+        .map(|entry| (rule.key(entry, &tied, matches, scoring), entry))
+        .collect();
+    keyed.sort_by_key(|(key, _)| Reverse(*key));
+
+    let subgroups: Vec<Vec<&PointsTableEntry>> = keyed
+        .chunk_by(|a, b| a.0 == b.0)
+        .map(|chunk| chunk.iter().map(|(_, entry)| *entry).collect())
+        .collect();
+
+    // The rule only decides positions when it actually splits the group.
+    let decided_by = if subgroups.len() > 1 {
+        Some(rule)
+    } else {
+        decided_by
+    };
+
+    for subgroup in subgroups {
+        break_ties(subgroup, remaining, decided_by, scoring, matches, ranked);
+    }
+}
+
+/// The sets won by each side of a set-based match.
+fn set_totals(details: &MatchDetails) -> Option<(u32, u32)> {
+    let format = details.sport()?.scoring_format();
+    if !matches!(format, ScoringFormat::Sets { .. }) {
+        return None;
+    }
+
+    let sets = details
+        .set_scores()?
+        .iter()
+        .filter_map(|score| format.set_winner(*score));
+
+    Some(sets.fold((0, 0), |(home, away), winner| match winner {
+        Side::Home => (home + 1, away),
+        Side::Away => (home, away + 1),
+    }))
+}
+
+impl Default for PointsTable {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// A single entry in the scoring table
@@ -24,5 +261,278 @@ pub struct PointsTableEntry {
     pub participant_id: ParticipantId,
     pub points: u32,
     pub wins: u32,
+    #[serde(default)]
+    pub draws: u32,
     pub losses: u32,
+    /// Goals (or points) scored, or games won in set-based sports.
+    #[serde(default)]
+    pub score_for: u32,
+    /// Goals (or points) conceded, or games lost in set-based sports.
+    #[serde(default)]
+    pub score_against: u32,
+    /// Sets won in set-based sports.
+    #[serde(default)]
+    pub sets_for: u32,
+    /// Sets lost in set-based sports.
+    #[serde(default)]
+    pub sets_against: u32,
+}
+
+impl PointsTableEntry {
+    /// Create an entry for a participant with no matches played.
+    pub fn new(participant: &Participant) -> PointsTableEntry {
+        PointsTableEntry {
+            participant_name: participant.name.clone(),
+            participant_id: participant.id,
+            points: 0,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            score_for: 0,
+            score_against: 0,
+            sets_for: 0,
+            sets_against: 0,
+        }
+    }
+
+    /// The number of decided matches played.
+    pub fn played(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// The score for minus the score against.
+    pub fn score_difference(&self) -> i64 {
+        i64::from(self.score_for) - i64::from(self.score_against)
+    }
+}
+
+/// The table points awarded for each result.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ScoringSystem {
+    pub win: u32,
+    pub draw: u32,
+    pub loss: u32,
+}
+
+impl ScoringSystem {
+    /// The points earned by one side of a match with the given outcome.
+    pub fn points(&self, outcome: MatchOutcome, side: Side) -> u32 {
+        match outcome {
+            MatchOutcome::Win(winner) if winner == side => self.win,
+            MatchOutcome::Win(_) => self.loss,
+            MatchOutcome::Draw => self.draw,
+        }
+    }
+}
+
+impl Default for ScoringSystem {
+    /// Three points for a win and one for a draw.
+    fn default() -> Self {
+        ScoringSystem {
+            win: 3,
+            draw: 1,
+            loss: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::doc;
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{RoundId, VenueId};
+
+    fn football(home: &Participant, away: &Participant, score: (u32, u32)) -> Match {
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::HeadToHeadScore {
+            players: (home.id, away.id),
+            score: Some(score),
+        };
+        a_match
+    }
+
+    #[test]
+    fn record_wins_draws_and_losses() {
+        let ann = Participant::new("Ann".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let cat = Participant::new("Cat".to_string());
+
+        let matches = vec![
+            football(&ann, &bob, (3, 1)),
+            football(&bob, &cat, (2, 2)),
+            football(&cat, &ann, (0, 0)),
+        ];
+
+        let table = PointsTable::from_matches(
+            &[bob.clone(), cat.clone(), ann.clone()],
+            &matches,
+            &ScoringSystem::default(),
+        );
+
+        let ann_entry = table.entry(&ann.id).unwrap();
+        assert_eq!(ann_entry.points, 4);
+        assert_eq!(
+            (ann_entry.wins, ann_entry.draws, ann_entry.losses),
+            (1, 1, 0)
+        );
+        assert_eq!((ann_entry.score_for, ann_entry.score_against), (3, 1));
+        assert_eq!(ann_entry.score_difference(), 2);
+
+        let bob_entry = table.entry(&bob.id).unwrap();
+        assert_eq!(bob_entry.points, 1);
+        assert_eq!(bob_entry.played(), 2);
+        assert_eq!(bob_entry.score_difference(), -2);
+
+        let order: Vec<&str> = table
+            .entries
+            .iter()
+            .map(|entry| entry.participant_name.as_str())
+            .collect();
+        assert_eq!(order, vec!["Ann", "Cat", "Bob"]);
+    }
+
+    #[test]
+    fn record_doubles_for_both_partners() {
+        let players: Vec<Participant> = ["A", "B", "C", "D"]
+            .into_iter()
+            .map(|name| Participant::new(name.to_string()))
+            .collect();
+
+        let mut table = PointsTable::new();
+        for participant in &players {
+            table.add_participant(participant);
+        }
+
+        let details = MatchDetails::TennisDoubles {
+            players: (
+                (players[0].id, players[1].id),
+                (players[2].id, players[3].id),
+            ),
+            scores: vec![(4, 6), (2, 6)],
+        };
+        table.record(&details, &ScoringSystem::default());
+
+        for winner in &players[2..] {
+            let entry = table.entry(&winner.id).unwrap();
+            assert_eq!(
+                (entry.wins, entry.score_for, entry.score_against),
+                (1, 12, 6)
+            );
+            assert_eq!((entry.sets_for, entry.sets_against), (2, 0));
+        }
+        for loser in &players[..2] {
+            assert_eq!(table.entry(&loser.id).unwrap().losses, 1);
+        }
+    }
+
+    fn tennis(home: &Participant, away: &Participant, scores: Vec<(u8, u8)>) -> Match {
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::TennisSingles {
+            players: (home.id, away.id),
+            scores,
+        };
+        a_match
+    }
+
+    fn names(ranked: &[RankedEntry]) -> Vec<(usize, &str)> {
+        ranked
+            .iter()
+            .map(|ranked| (ranked.position, ranked.entry.participant_name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn rank_with_tie_breaker_chain() {
+        let ann = Participant::new("Ann".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let cat = Participant::new("Cat".to_string());
+        let dan = Participant::new("Dan".to_string());
+
+        // Ann, Bob and Cat all win one match. Bob wins the fewest sets, while
+        // Ann and Cat win the same number of sets but Ann won more games.
+        let matches = vec![
+            tennis(&ann, &bob, vec![(6, 0), (6, 0)]),
+            tennis(&bob, &cat, vec![(6, 4), (4, 6), (6, 4)]),
+            tennis(&cat, &ann, vec![(6, 4), (4, 6), (6, 4)]),
+        ];
+        let scoring = ScoringSystem::default();
+        let table = PointsTable::from_matches(
+            &[ann.clone(), bob.clone(), cat.clone(), dan.clone()],
+            &matches,
+            &scoring,
+        );
+
+        let ranked = table.rank(
+            &[TieBreaker::SetsWon, TieBreaker::GameDifference],
+            &scoring,
+            &matches,
+        );
+
+        assert_eq!(
+            names(&ranked),
+            vec![(1, "Ann"), (2, "Cat"), (3, "Bob"), (4, "Dan")]
+        );
+        assert_eq!(ranked[0].decided_by, Some(TieBreaker::GameDifference));
+        assert_eq!(ranked[1].decided_by, Some(TieBreaker::GameDifference));
+        assert_eq!(ranked[2].decided_by, Some(TieBreaker::SetsWon));
+        assert_eq!(ranked[3].decided_by, None);
+    }
+
+    #[test]
+    fn rank_by_head_to_head() {
+        let ann = Participant::new("Ann".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let cat = Participant::new("Cat".to_string());
+
+        // Ann and Bob both win one match, but Bob beat Ann.
+        let matches = vec![
+            tennis(&bob, &ann, vec![(6, 4), (6, 4)]),
+            tennis(&ann, &cat, vec![(6, 0), (6, 0)]),
+        ];
+        let scoring = ScoringSystem::default();
+        let table =
+            PointsTable::from_matches(&[ann.clone(), bob.clone(), cat.clone()], &matches, &scoring);
+
+        let ranked = table.rank(&[TieBreaker::HeadToHead], &scoring, &matches);
+
+        assert_eq!(names(&ranked), vec![(1, "Bob"), (2, "Ann"), (3, "Cat")]);
+        assert_eq!(ranked[0].decided_by, Some(TieBreaker::HeadToHead));
+    }
+
+    #[test]
+    fn unbroken_ties_share_a_position() {
+        let ann = Participant::new("Ann".to_string());
+        let bob = Participant::new("Bob".to_string());
+        let cat = Participant::new("Cat".to_string());
+        let scoring = ScoringSystem::default();
+        let table = PointsTable::from_matches(&[ann, bob, cat], &[], &scoring);
+
+        let ranked = table.rank(&[TieBreaker::MatchesPlayed], &scoring, &[]);
+
+        assert!(ranked.iter().all(|ranked| ranked.position == 1));
+        assert!(ranked.iter().all(|ranked| ranked.decided_by.is_none()));
+
+        let ranked = table.rank(&[TieBreaker::Random { seed: 42 }], &scoring, &[]);
+        let positions: Vec<usize> = ranked.iter().map(|ranked| ranked.position).collect();
+        assert_eq!(positions, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn deserialize_entry_without_draws() {
+        let participant = Participant::new("Ann".to_string());
+        let bson = doc! {
+            "participant_name": "Ann",
+            "participant_id": bson::to_bson(&participant.id).unwrap(),
+            "points": 6,
+            "wins": 2,
+            "losses": 1,
+        };
+
+        let entry: PointsTableEntry = bson::from_document(bson).unwrap();
+
+        assert_eq!(entry.draws, 0);
+        assert_eq!(entry.score_difference(), 0);
+    }
 }
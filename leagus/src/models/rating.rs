@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{MatchId, ParticipantId};
+
+with_prefix!(prefix_match "match");
+
+/// The skill ratings of a participant, carried across seasons and leagues.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ParticipantRating {
+    // Flatten will inline this field into its parent, making the participant's
+    // id the id of their ratings.
+    // See https://serde.rs/attr-flatten.html
+    #[serde(flatten)]
+    pub participant_id: ParticipantId,
+    pub elo: EloRating,
+    #[serde(default)]
+    pub glicko: Glicko2Rating,
+    #[serde(default)]
+    pub trueskill: TrueSkillRating,
+}
+
+/// The rating systems which participants are rated by.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RatingSystem {
+    #[default]
+    Elo,
+    Glicko2,
+    TrueSkill,
+}
+
+/// An Elo rating along with how it changed over time.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EloRating {
+    pub rating: f64,
+    /// Every change to the rating, oldest first.
+    #[serde(default)]
+    pub history: Vec<RatingChange>,
+}
+
+/// A Glicko-2 rating. Along with the rating itself it tracks how reliable the
+/// rating is, through the deviation, and how erratic the participant's results
+/// are, through the volatility.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+/// A TrueSkill rating, a belief about the participant's skill with a mean
+/// `mu` and an uncertainty `sigma`. Doubles partners are rated individually.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TrueSkillRating {
+    pub mu: f64,
+    pub sigma: f64,
+}
+
+/// A change to a rating as the result of a match.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RatingChange {
+    #[serde(flatten, with = "prefix_match")]
+    pub match_id: MatchId,
+    pub date: DateTime<Utc>,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl ParticipantRating {
+    /// Create ratings for a participant starting from an initial Elo rating.
+    pub fn new(participant_id: ParticipantId, initial_elo: f64) -> ParticipantRating {
+        ParticipantRating {
+            participant_id,
+            elo: EloRating::new(initial_elo),
+            glicko: Glicko2Rating::default(),
+            trueskill: TrueSkillRating::default(),
+        }
+    }
+
+    /// The participant's rating in one of the rating systems.
+    pub fn skill(&self, system: RatingSystem) -> f64 {
+        match system {
+            RatingSystem::Elo => self.elo.rating,
+            RatingSystem::Glicko2 => self.glicko.rating,
+            RatingSystem::TrueSkill => self.trueskill.mu,
+        }
+    }
+}
+
+impl EloRating {
+    /// Create a rating with no history.
+    pub fn new(rating: f64) -> EloRating {
+        EloRating {
+            rating,
+            history: Vec::new(),
+        }
+    }
+
+    /// Move the rating by `delta` as the result of a match, recording the
+    /// change in the history.
+    pub fn apply(&mut self, match_id: MatchId, date: DateTime<Utc>, delta: f64) {
+        let before = self.rating;
+        self.rating += delta;
+        self.history.push(RatingChange {
+            match_id,
+            date,
+            before,
+            after: self.rating,
+        });
+    }
+}
+
+impl Glicko2Rating {
+    /// A rating the participant is very likely (about 95%) to be above, which
+    /// is useful for ranking participants with unreliable ratings.
+    pub fn conservative(&self) -> f64 {
+        self.rating - 2.0 * self.deviation
+    }
+}
+
+impl Default for Glicko2Rating {
+    /// The rating given to an unrated participant.
+    fn default() -> Self {
+        Glicko2Rating {
+            rating: 1500.0,
+            deviation: 350.0,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl TrueSkillRating {
+    /// A rating the participant is very likely (about 99%) to be above, which
+    /// is how TrueSkill leaderboards are usually ordered.
+    pub fn conservative(&self) -> f64 {
+        self.mu - 3.0 * self.sigma
+    }
+}
+
+impl Default for TrueSkillRating {
+    /// The rating given to an unrated participant.
+    fn default() -> Self {
+        TrueSkillRating {
+            mu: 25.0,
+            sigma: 25.0 / 3.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let match_uuid = Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let participant_id = ParticipantId::from(uuid);
+        let match_id = MatchId::from(match_uuid);
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+
+        let mut rating = ParticipantRating::new(participant_id, 1500.0);
+        rating.elo.apply(match_id, dt, 16.0);
+
+        let bson = bson::to_document(&rating).unwrap();
+
+        let expected_bson = doc! {
+            "_id": participant_id,
+            "elo": {
+                "rating": 1516.0,
+                "history": [{
+                    "match_id": match_id,
+                    "date": "2024-01-01T12:00:00Z",
+                    "before": 1500.0,
+                    "after": 1516.0,
+                }],
+            },
+            "glicko": {
+                "rating": 1500.0,
+                "deviation": 350.0,
+                "volatility": 0.06,
+            },
+            "trueskill": {
+                "mu": 25.0,
+                "sigma": 25.0 / 3.0,
+            },
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+
+    #[test]
+    fn deserialize_without_glicko() {
+        let participant_id = ParticipantId::new();
+        let bson = doc! {
+            "_id": participant_id,
+            "elo": { "rating": 1480.0 },
+        };
+
+        let rating: ParticipantRating = bson::from_document(bson).unwrap();
+
+        assert_eq!(rating.skill(RatingSystem::Elo), 1480.0);
+        assert_eq!(rating.glicko, Glicko2Rating::default());
+        assert_eq!(rating.trueskill, TrueSkillRating::default());
+    }
+}
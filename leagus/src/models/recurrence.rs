@@ -0,0 +1,136 @@
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::{Season, Session};
+
+/// When a season's sessions recur, e.g. every Tuesday at 19:00 except over
+/// the holidays. Times are in UTC.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+    /// Weeks between sessions, 1 for every week.
+    pub every: u32,
+    /// Dates on which there is no session.
+    #[serde(default)]
+    pub exclusions: Vec<NaiveDate>,
+}
+
+impl Recurrence {
+    /// Every week on the day at the time.
+    pub fn weekly(weekday: Weekday, time: NaiveTime) -> Recurrence {
+        Recurrence {
+            weekday,
+            time,
+            every: 1,
+            exclusions: Vec::new(),
+        }
+    }
+
+    /// The dates of the sessions from `start` up to and including `end`,
+    /// skipping any exclusions.
+    pub fn occurrences(&self, start: &DateTime<Utc>, end: &DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let ahead =
+            (7 + self.weekday.num_days_from_monday() - start.weekday().num_days_from_monday()) % 7;
+        let mut date = start.date_naive() + Days::new(ahead.into());
+        if date.and_time(self.time).and_utc() < *start {
+            date = date + Days::new(7);
+        }
+        let step = Days::new(7 * u64::from(self.every.max(1)));
+
+        let mut occurrences = Vec::new();
+        loop {
+            let occurrence = date.and_time(self.time).and_utc();
+            if occurrence > *end {
+                break;
+            }
+            if !self.exclusions.contains(&date) {
+                occurrences.push(occurrence);
+            }
+            date = date + step;
+        }
+        occurrences
+    }
+
+    /// Create the season's sessions for every occurrence which doesn't
+    /// already have one. Sessions which were rescheduled or cancelled still
+    /// count for the occurrence they were created for.
+    pub fn sessions(&self, season: &Season, existing: &[Session]) -> Vec<Session> {
+        self.occurrences(&season.start, &season.end)
+            .into_iter()
+            .filter(|occurrence| {
+                !existing
+                    .iter()
+                    .any(|session| session.occurrence == Some(*occurrence))
+            })
+            .map(|occurrence| {
+                let mut session = Session::new(&season.id, &occurrence);
+                session.occurrence = Some(occurrence);
+                session
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+
+    use crate::models::LeagueId;
+
+    fn tuesdays() -> Recurrence {
+        Recurrence::weekly(Weekday::Tue, NaiveTime::from_hms_opt(19, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn occur_on_the_weekday_except_exclusions() {
+        let mut recurrence = tuesdays();
+        recurrence.exclusions = vec![NaiveDate::from_ymd_opt(2024, 1, 16).unwrap()];
+        // Monday the 1st to Tuesday the 30th, ending before the session.
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 30, 12, 00, 00).unwrap();
+
+        let days: Vec<u32> = recurrence
+            .occurrences(&start, &end)
+            .iter()
+            .map(|occurrence| occurrence.day())
+            .collect();
+        assert_eq!(days, [2, 9, 23]);
+
+        // Starting on a Tuesday after the session begins waits a week, and
+        // fortnightly skips every other Tuesday.
+        recurrence.every = 2;
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 20, 00, 00).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 2, 29, 12, 00, 00).unwrap();
+        let days: Vec<(u32, u32)> = recurrence
+            .occurrences(&start, &end)
+            .iter()
+            .map(|occurrence| (occurrence.month(), occurrence.day()))
+            .collect();
+        assert_eq!(days, [(1, 9), (1, 23), (2, 6), (2, 20)]);
+    }
+
+    #[test]
+    fn only_create_sessions_for_new_occurrences() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 31, 12, 00, 00).unwrap();
+        let season = Season::new(&LeagueId::new(), &start, &end, "January");
+        let recurrence = tuesdays();
+
+        let mut sessions = recurrence.sessions(&season, &[]);
+        assert_eq!(sessions.len(), 5);
+
+        sessions[1].reschedule(&Utc.with_ymd_and_hms(2024, 1, 11, 19, 00, 00).unwrap());
+        sessions[2].cancel();
+        sessions.pop();
+
+        let added = recurrence.sessions(&season, &sessions);
+        let dates: Vec<DateTime<Utc>> = added.iter().map(|session| session.date).collect();
+        assert_eq!(
+            dates,
+            [Utc.with_ymd_and_hms(2024, 1, 30, 19, 00, 00).unwrap()]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use serde_with::with_prefix;
 
-use super::{ParticipantId, RoundId, SessionId};
+use chrono::NaiveTime;
+
+use super::{ParticipantId, RoundId, Session, SessionId};
 
 // Prefix the various IDs to avoid clashes
 with_prefix!(prefix_session "session");
@@ -22,7 +24,30 @@ pub struct Round {
     #[serde(flatten, with = "prefix_session")]
     pub session_id: SessionId,
     pub participants: Vec<ParticipantId>,
-    // TODO: add match making strategy
+    /// The participants who sat out the round, recorded as it is paired.
+    #[serde(default)]
+    pub sit_outs: Vec<ParticipantId>,
+    /// The most matches which can be played in the round, such as the number
+    /// of courts free, or `None` if there is no limit.
+    #[serde(default)]
+    pub capacity: Option<usize>,
+    /// How the round's participants are paired into matches.
+    #[serde(default)]
+    pub strategy: MatchMakingStrategy,
+}
+
+/// The algorithm used to pair the participants of a round into matches.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum MatchMakingStrategy {
+    /// Matches are entered by hand.
+    #[default]
+    Manual,
+    /// Swiss-system pairings, matching participants on equal or near-equal
+    /// points without rematches.
+    Swiss,
+    /// Doubles with partners rotated each round, avoiding repeated partners
+    /// and opponents and balancing the strength of the teams.
+    RotatingDoubles,
 }
 
 impl Round {
@@ -32,20 +57,40 @@ impl Round {
             id: RoundId::new(),
             session_id,
             participants: Vec::new(),
+            sit_outs: Vec::new(),
+            capacity: None,
+            strategy: MatchMakingStrategy::default(),
         }
     }
+
+    /// Add the session's confirmed attendees who will have arrived by the
+    /// time given, and aren't in the round already. Returns how many were
+    /// added.
+    pub fn add_attendees(&mut self, session: &Session, by: Option<NaiveTime>) -> usize {
+        let before = self.participants.len();
+        for participant_id in session.confirmed(by) {
+            if !self.participants.contains(&participant_id) {
+                self.participants.push(participant_id);
+            }
+        }
+        self.participants.len() - before
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use bson::{doc, Uuid};
+    use chrono::{TimeZone, Utc};
+
+    use crate::models::{RsvpAnswer, SeasonId};
     use pretty_assertions::assert_eq;
 
     #[test]
     fn serialize_as_bson() {
         let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
         let session_uuid = Uuid::parse_str("22222222-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let participant_uuid = Uuid::parse_str("33333333-2248-4345-80ec-b88499f9ff1e").unwrap();
         let id = RoundId::from(uuid);
         let session_id = SessionId::from(session_uuid);
 
@@ -53,6 +98,9 @@ mod tests {
             id,
             session_id,
             participants: Vec::new(),
+            sit_outs: vec![ParticipantId::from(participant_uuid)],
+            capacity: Some(3),
+            strategy: MatchMakingStrategy::Swiss,
         };
 
         let bson = bson::to_document(&round).unwrap();
@@ -61,8 +109,54 @@ mod tests {
             "_id": round.id,
             "session_id": round.session_id,
             "participants": round.participants,
+            "sit_outs": [{ "_id": participant_uuid }],
+            "capacity": bson::to_bson(&3usize).unwrap(),
+            "strategy": "Swiss",
         };
 
         assert_eq!(bson, expected_bson);
     }
+
+    #[test]
+    fn deserialize_without_strategy_or_sit_outs() {
+        let bson = doc! {
+            "_id": RoundId::new(),
+            "session_id": SessionId::new(),
+            "participants": [],
+        };
+
+        let round: Round = bson::from_document(bson).unwrap();
+
+        assert_eq!(round.strategy, MatchMakingStrategy::Manual);
+        assert!(round.sit_outs.is_empty());
+        assert_eq!(round.capacity, None);
+    }
+
+    #[test]
+    fn add_confirmed_attendees() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 19, 00, 00).unwrap();
+        let mut session = Session::new(&SeasonId::new(), &start);
+        let (ann, bob, cat) = (
+            ParticipantId::new(),
+            ParticipantId::new(),
+            ParticipantId::new(),
+        );
+        session.rsvp(&ann, RsvpAnswer::Yes, None, &start);
+        session.rsvp(
+            &bob,
+            RsvpAnswer::Yes,
+            NaiveTime::from_hms_opt(20, 0, 0),
+            &start,
+        );
+        session.rsvp(&cat, RsvpAnswer::Maybe, None, &start);
+
+        let mut round = Round::new(session.id);
+        assert_eq!(
+            round.add_attendees(&session, NaiveTime::from_hms_opt(19, 0, 0)),
+            1
+        );
+        assert_eq!(round.add_attendees(&session, None), 1);
+        assert_eq!(round.add_attendees(&session, None), 0);
+        assert_eq!(round.participants, [ann, bob]);
+    }
 }
@@ -1,12 +1,19 @@
+use std::fmt::Display;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::with_prefix;
 
-use super::{LeagueId, PointsTable, SeasonId, SessionId};
+use super::{
+    promote_and_relegate, Handicaps, League, LeagueId, Match, MatchId, Participant, ParticipantId,
+    PointsTable, RankedEntry, Recurrence, ScoringSystem, SeasonDivision, SeasonId, SessionId,
+    TieBreaker,
+};
 
 // Prefix the league _id with league
 with_prefix!(prefix_league "league");
 with_prefix!(prefix_active_session "active_session");
+with_prefix!(prefix_participant "participant");
 
 /// A season of a league.
 ///
@@ -23,11 +30,140 @@ pub struct Season {
     pub end: DateTime<Utc>,
     #[serde(default)]
     pub name: String,
+    #[serde(default)]
+    pub status: SeasonStatus,
     pub table: PointsTable,
+    /// The table points awarded for each result.
+    #[serde(default)]
+    pub scoring: ScoringSystem,
+    /// The ordered tie-breakers used to rank participants level on points.
+    #[serde(default)]
+    pub tie_breakers: Vec<TieBreaker>,
+    /// The divisions of the season, top first, each with their own table.
+    #[serde(default)]
+    pub divisions: Vec<SeasonDivision>,
+    /// When the season's sessions recur, if they are scheduled regularly.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// The participants registered to play in the season.
+    #[serde(default)]
+    pub roster: Vec<Registration>,
+    /// The participants' handicap starts, if the season is handicapped.
+    #[serde(default)]
+    pub handicaps: Option<Handicaps>,
     #[serde(default, flatten, with = "prefix_active_session")]
     pub active_session: Option<SessionId>,
-    // TODO: add scoring system
-    // TODO: add participants (pool of players available for the season)?
+}
+
+/// Where a season is in its life, from being set up to being kept for the
+/// record.
+#[derive(Debug, Default, Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Serialize, Deserialize)]
+pub enum SeasonStatus {
+    /// Being set up, and not yet shown to participants.
+    Draft,
+    /// Taking registrations.
+    Open,
+    /// Being played. Participants may withdraw, but no one new may join.
+    ///
+    /// Seasons from before statuses are taken to be running.
+    #[default]
+    Running,
+    /// Over, with its table frozen.
+    Finished,
+    /// Kept for the record, and read-only.
+    Archived,
+}
+
+/// Why a change to a season isn't allowed in its status.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum SeasonError {
+    /// The season cannot move from one status to the other.
+    InvalidTransition {
+        from: SeasonStatus,
+        to: SeasonStatus,
+    },
+    /// Participants cannot join or leave a season in this status.
+    RosterLocked(SeasonStatus),
+    /// The table cannot change once the season has finished.
+    TableFrozen,
+    /// Nothing about an archived season may change, nor the sessions and
+    /// matches of a finished one.
+    ReadOnly,
+    /// The participant isn't registered for the season.
+    NotRegistered(ParticipantId),
+    /// The season has no division with this name.
+    UnknownDivision(String),
+    /// The store failed to write the change, with the error it gave.
+    Storage(String),
+    /// A knockout match must be won, a draw would leave the bracket stuck.
+    KnockoutUndecided(MatchId),
+}
+
+impl SeasonStatus {
+    /// Whether a season in this status may move to the next. Seasons move
+    /// forward through each status in turn, but may go back to draft until
+    /// they start and be reopened after they finish to correct results.
+    pub fn can_become(&self, next: SeasonStatus) -> bool {
+        matches!(
+            (self, next),
+            (SeasonStatus::Draft, SeasonStatus::Open)
+                | (SeasonStatus::Open, SeasonStatus::Draft)
+                | (SeasonStatus::Open, SeasonStatus::Running)
+                | (SeasonStatus::Running, SeasonStatus::Finished)
+                | (SeasonStatus::Finished, SeasonStatus::Running)
+                | (SeasonStatus::Finished, SeasonStatus::Archived)
+        )
+    }
+}
+
+impl Display for SeasonStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeasonStatus::Draft => write!(f, "draft"),
+            SeasonStatus::Open => write!(f, "open"),
+            SeasonStatus::Running => write!(f, "running"),
+            SeasonStatus::Finished => write!(f, "finished"),
+            SeasonStatus::Archived => write!(f, "archived"),
+        }
+    }
+}
+
+impl Display for SeasonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeasonError::InvalidTransition { from, to } => {
+                write!(f, "a {from} season cannot become {to}")
+            }
+            SeasonError::RosterLocked(status) => {
+                write!(f, "the roster of a {status} season is locked")
+            }
+            SeasonError::TableFrozen => write!(f, "the table of a finished season is frozen"),
+            SeasonError::ReadOnly => write!(f, "the season is read-only"),
+            SeasonError::NotRegistered(id) => {
+                write!(f, "participant {id} isn't registered for the season")
+            }
+            SeasonError::UnknownDivision(name) => {
+                write!(f, "the season has no division \"{name}\"")
+            }
+            SeasonError::Storage(error) => write!(f, "the store failed, {error}"),
+            SeasonError::KnockoutUndecided(id) => {
+                write!(f, "match {id} is a knockout match and needs a winner")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SeasonError {}
+
+/// A participant registered to play in a season.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Registration {
+    #[serde(flatten, with = "prefix_participant")]
+    pub participant_id: ParticipantId,
+    pub joined: DateTime<Utc>,
+    /// When they pulled out of the rest of the season, if they have.
+    #[serde(default)]
+    pub withdrawn: Option<DateTime<Utc>>,
 }
 
 impl Season {
@@ -45,18 +181,392 @@ impl Season {
             start: *start,
             end: *end,
             name: name.to_string(),
+            status: SeasonStatus::Draft,
             table: PointsTable {
                 entries: Vec::new(),
             },
+            scoring: ScoringSystem::default(),
+            tie_breakers: Vec::new(),
+            divisions: Vec::new(),
+            recurrence: None,
+            roster: Vec::new(),
+            handicaps: None,
             active_session: None,
         }
     }
+
+    /// Create a [`Season`] with an empty division for each of the league's
+    /// divisions.
+    pub fn for_league(
+        league: &League,
+        start: &DateTime<Utc>,
+        end: &DateTime<Utc>,
+        name: &str,
+    ) -> Season {
+        let mut season = Season::new(&league.id, start, end, name);
+        season.divisions = league
+            .divisions
+            .iter()
+            .map(|division| SeasonDivision::new(&division.name))
+            .collect();
+        season
+    }
+
+    /// Move the season on to another status.
+    pub fn set_status(&mut self, status: SeasonStatus) -> Result<(), SeasonError> {
+        if !self.status.can_become(status) {
+            return Err(SeasonError::InvalidTransition {
+                from: self.status,
+                to: status,
+            });
+        }
+        self.status = status;
+        Ok(())
+    }
+
+    /// Check the season may be changed from how it is to `updated`.
+    ///
+    /// Any change of status must be allowed, the roster is locked once the
+    /// season is running, the table is frozen once it has finished and an
+    /// archived season cannot change at all. Withdrawals are not changes to
+    /// who is on the roster.
+    pub fn check_update(&self, updated: &Season) -> Result<(), SeasonError> {
+        if self == updated {
+            return Ok(());
+        }
+        if self.status == SeasonStatus::Archived {
+            return Err(SeasonError::ReadOnly);
+        }
+        if self.status != updated.status && !self.status.can_become(updated.status) {
+            return Err(SeasonError::InvalidTransition {
+                from: self.status,
+                to: updated.status,
+            });
+        }
+
+        let registered = |season: &Season| -> Vec<ParticipantId> {
+            season
+                .roster
+                .iter()
+                .map(|registration| registration.participant_id)
+                .collect()
+        };
+        if self.status >= SeasonStatus::Running && registered(self) != registered(updated) {
+            return Err(SeasonError::RosterLocked(self.status));
+        }
+        if self.status >= SeasonStatus::Finished
+            && updated.status >= SeasonStatus::Finished
+            && (self.table != updated.table || self.divisions != updated.divisions)
+        {
+            return Err(SeasonError::TableFrozen);
+        }
+        Ok(())
+    }
+
+    /// Check the season's sessions, rounds, matches and ties may change. Once
+    /// the season has finished they are read-only, unless it is reopened.
+    pub fn check_writable(&self) -> Result<(), SeasonError> {
+        match self.status {
+            SeasonStatus::Finished | SeasonStatus::Archived => Err(SeasonError::ReadOnly),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the roster may change, while the season takes registrations or,
+    /// for withdrawals, while it is being played.
+    fn check_roster(&self, withdrawing: bool) -> Result<(), SeasonError> {
+        match self.status {
+            SeasonStatus::Draft | SeasonStatus::Open => Ok(()),
+            SeasonStatus::Running if withdrawing => Ok(()),
+            SeasonStatus::Archived => Err(SeasonError::ReadOnly),
+            status => Err(SeasonError::RosterLocked(status)),
+        }
+    }
+
+    /// Register a participant for the season, giving them a place in the
+    /// points table before they've played. Registering again after
+    /// withdrawing rejoins the season.
+    pub fn register(
+        &mut self,
+        participant: &Participant,
+        joined: &DateTime<Utc>,
+    ) -> Result<(), SeasonError> {
+        self.check_roster(false)?;
+        match self
+            .roster
+            .iter_mut()
+            .find(|registration| registration.participant_id == participant.id)
+        {
+            Some(registration) => registration.withdrawn = None,
+            None => self.roster.push(Registration {
+                participant_id: participant.id,
+                joined: *joined,
+                withdrawn: None,
+            }),
+        }
+        self.table.add_participant(participant);
+        Ok(())
+    }
+
+    /// Register a participant for the season to play in one of its divisions,
+    /// moving them out of any other division they haven't played in yet.
+    pub fn register_in_division(
+        &mut self,
+        participant: &Participant,
+        division: &str,
+        joined: &DateTime<Utc>,
+    ) -> Result<(), SeasonError> {
+        if !self
+            .divisions
+            .iter()
+            .any(|season_division| season_division.name == division)
+        {
+            return Err(SeasonError::UnknownDivision(division.to_string()));
+        }
+        self.register(participant, joined)?;
+        for season_division in &mut self.divisions {
+            if season_division.name == division {
+                season_division.table.add_participant(participant);
+            } else {
+                season_division
+                    .table
+                    .entries
+                    .retain(|entry| entry.participant_id != participant.id || entry.played() > 0);
+            }
+        }
+        Ok(())
+    }
+
+    /// Withdraw a participant from the rest of the season. Their results so
+    /// far stay in the table.
+    pub fn withdraw(
+        &mut self,
+        participant_id: &ParticipantId,
+        at: &DateTime<Utc>,
+    ) -> Result<(), SeasonError> {
+        self.check_roster(true)?;
+        match self
+            .roster
+            .iter_mut()
+            .find(|registration| registration.participant_id == *participant_id)
+        {
+            Some(registration) => {
+                registration.withdrawn = Some(*at);
+                Ok(())
+            }
+            None => Err(SeasonError::NotRegistered(*participant_id)),
+        }
+    }
+
+    /// Remove a participant registered by mistake, along with their place in
+    /// the table if they haven't played.
+    pub fn unregister(&mut self, participant_id: &ParticipantId) -> Result<(), SeasonError> {
+        self.check_roster(false)?;
+        let registered = self.roster.len();
+        self.roster
+            .retain(|registration| registration.participant_id != *participant_id);
+        if self.roster.len() == registered {
+            return Err(SeasonError::NotRegistered(*participant_id));
+        }
+        self.table
+            .entries
+            .retain(|entry| entry.participant_id != *participant_id || entry.played() > 0);
+        for division in &mut self.divisions {
+            division
+                .table
+                .entries
+                .retain(|entry| entry.participant_id != *participant_id || entry.played() > 0);
+        }
+        Ok(())
+    }
+
+    /// The participants registered for the season who haven't withdrawn.
+    ///
+    /// Seasons from before registration have no roster, so everyone in their
+    /// points table is taken to be registered.
+    pub fn participants(&self) -> Vec<ParticipantId> {
+        if self.roster.is_empty() {
+            return self
+                .table
+                .entries
+                .iter()
+                .map(|entry| entry.participant_id)
+                .collect();
+        }
+        self.roster
+            .iter()
+            .filter(|registration| registration.withdrawn.is_none())
+            .map(|registration| registration.participant_id)
+            .collect()
+    }
+
+    /// Rank the season's participants using its tie-breakers.
+    ///
+    /// The table is rebuilt from the matches given, the season's confirmed
+    /// matches, which also settle head-to-head ties.
+    pub fn standings(&self, matches: &[Match]) -> Vec<RankedEntry> {
+        let participants: Vec<Participant> = self
+            .table
+            .entries
+            .iter()
+            .map(|entry| Participant {
+                id: entry.participant_id,
+                name: entry.participant_name.clone(),
+                team: None,
+            })
+            .collect();
+        PointsTable::from_matches(&participants, matches, &self.scoring).rank(
+            &self.tie_breakers,
+            &self.scoring,
+            matches,
+        )
+    }
+
+    /// Record the result of a confirmed match in the season's table, and in
+    /// the table of the division it was played in.
+    pub fn record(&mut self, a_match: &Match) {
+        let details = a_match.scored_details();
+        self.table.record(&details, &self.scoring);
+        self.table.sort();
+
+        let participants = details.participants();
+        for division in &mut self.divisions {
+            if participants
+                .iter()
+                .all(|id| division.table.entry(id).is_some())
+            {
+                division.table.record(&details, &self.scoring);
+                division.table.sort();
+            }
+        }
+    }
+
+    /// Rank each division's table using the season's tie-breakers.
+    pub fn division_standings(&self, matches: &[Match]) -> Vec<Vec<RankedEntry>> {
+        self.divisions
+            .iter()
+            .map(|division| division.standings(&self.tie_breakers, &self.scoring, matches))
+            .collect()
+    }
+
+    /// Create the league's next season once this one is over, promoting and
+    /// relegating participants between the divisions.
+    ///
+    /// The next season keeps this season's scoring, tie-breakers and
+    /// handicaps.
+    pub fn next_season(
+        &self,
+        league: &League,
+        matches: &[Match],
+        start: &DateTime<Utc>,
+        end: &DateTime<Utc>,
+        name: &str,
+    ) -> Season {
+        let participants =
+            promote_and_relegate(&league.divisions, &self.division_standings(matches));
+
+        let mut season = self.successor(league, start, end, name);
+        for (division, participants) in season.divisions.iter_mut().zip(participants) {
+            for participant in &participants {
+                division.table.add_participant(participant);
+                season.roster.push(Registration {
+                    participant_id: participant.id,
+                    joined: *start,
+                    withdrawn: None,
+                });
+            }
+        }
+        season
+    }
+
+    /// Create a new season using this one as a template.
+    ///
+    /// The new season keeps this season's scoring, tie-breakers, handicaps
+    /// and recurrence, along with everyone still registered, in the same
+    /// divisions. When seeding, they are listed in the new tables in the order
+    /// they finished this season, given its matches, rather than the order
+    /// they joined.
+    pub fn from_previous(
+        &self,
+        league: &League,
+        matches: &[Match],
+        start: &DateTime<Utc>,
+        end: &DateTime<Utc>,
+        name: &str,
+        seed: bool,
+    ) -> Season {
+        let participants = self.participants();
+
+        let mut season = self.successor(league, start, end, name);
+        season.recurrence = self.recurrence.clone();
+        season.table = carried_over(
+            &self.table,
+            seed.then(|| self.standings(matches)),
+            &participants,
+        );
+        for (division, previous) in season.divisions.iter_mut().zip(&self.divisions) {
+            let standings =
+                seed.then(|| previous.standings(&self.tie_breakers, &self.scoring, matches));
+            division.table = carried_over(&previous.table, standings, &participants);
+        }
+        season.roster = participants
+            .into_iter()
+            .map(|participant_id| Registration {
+                participant_id,
+                joined: *start,
+                withdrawn: None,
+            })
+            .collect();
+        season
+    }
+
+    /// A new season of the league keeping this season's scoring,
+    /// tie-breakers and handicaps.
+    fn successor(
+        &self,
+        league: &League,
+        start: &DateTime<Utc>,
+        end: &DateTime<Utc>,
+        name: &str,
+    ) -> Season {
+        let mut season = Season::for_league(league, start, end, name);
+        season.scoring = self.scoring.clone();
+        season.tie_breakers = self.tie_breakers.clone();
+        season.handicaps = self.handicaps.clone().map(|mut handicaps| {
+            handicaps.adjusted.clear();
+            handicaps
+        });
+        season
+    }
+}
+
+/// The table of the participants carried over to a new season, before any
+/// matches are played, in the order they finished if the standings are given.
+fn carried_over(
+    table: &PointsTable,
+    standings: Option<Vec<RankedEntry>>,
+    participants: &[ParticipantId],
+) -> PointsTable {
+    let entries = match standings {
+        Some(standings) => standings.into_iter().map(|ranked| ranked.entry).collect(),
+        None => table.entries.clone(),
+    };
+    PointsTable {
+        entries: entries
+            .into_iter()
+            .filter(|entry| participants.contains(&entry.participant_id))
+            .collect(),
+    }
+    .cleared()
 }
 
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::models::{
+        Division, MatchDetails, MatchOutcome, Participant, RoundId, Side, VenueId,
+    };
     use bson::{doc, Uuid};
     use chrono::TimeZone;
     use pretty_assertions::{assert_eq, assert_str_eq};
@@ -77,7 +587,14 @@ mod tests {
             name,
             start: dt,
             end: dt,
+            status: SeasonStatus::Running,
             table: PointsTable::new(),
+            scoring: ScoringSystem::default(),
+            tie_breakers: Vec::new(),
+            divisions: Vec::new(),
+            recurrence: None,
+            roster: Vec::new(),
+            handicaps: None,
             active_session: None,
         };
 
@@ -89,7 +606,14 @@ mod tests {
             "start": "2024-01-01T12:00:00Z",
             "end": "2024-01-01T12:00:00Z",
             "name": "Test Season",
-            "table": { "entries":[] }
+            "status": "Running",
+            "table": { "entries":[] },
+            "scoring": { "win": 3, "draw": 1, "loss": 0 },
+            "tie_breakers": [],
+            "divisions": [],
+            "recurrence": null,
+            "roster": [],
+            "handicaps": null
         });
 
         assert_str_eq!(json, expected_json.to_string());
@@ -110,7 +634,14 @@ mod tests {
             name,
             start: dt,
             end: dt,
+            status: SeasonStatus::Running,
             table: PointsTable::new(),
+            scoring: ScoringSystem::default(),
+            tie_breakers: Vec::new(),
+            divisions: Vec::new(),
+            recurrence: None,
+            roster: Vec::new(),
+            handicaps: None,
             active_session: None,
         };
 
@@ -122,7 +653,14 @@ mod tests {
             "start": "2024-01-01T12:00:00Z",
             "end": "2024-01-01T12:00:00Z",
             "name": "Test Season",
-            "table": { "entries":[] }
+            "status": "Running",
+            "table": { "entries":[] },
+            "scoring": bson::to_bson(&season.scoring).unwrap(),
+            "tie_breakers": [],
+            "divisions": [],
+            "recurrence": null,
+            "roster": [],
+            "handicaps": null,
         };
 
         assert_eq!(bson, expected_bson);
@@ -145,7 +683,14 @@ mod tests {
             name,
             start: dt,
             end: dt,
+            status: SeasonStatus::Running,
             table: PointsTable::new(),
+            scoring: ScoringSystem::default(),
+            tie_breakers: Vec::new(),
+            divisions: Vec::new(),
+            recurrence: None,
+            roster: Vec::new(),
+            handicaps: None,
             active_session: Some(session_id),
         };
 
@@ -157,10 +702,266 @@ mod tests {
             "start": "2024-01-01T12:00:00Z",
             "end": "2024-01-01T12:00:00Z",
             "name": "Test Season",
+            "status": "Running",
             "table": { "entries":[] },
+            "scoring": bson::to_bson(&season.scoring).unwrap(),
+            "tie_breakers": [],
+            "divisions": [],
+            "recurrence": null,
+            "roster": [],
+            "handicaps": null,
             "active_session_id": session_id,
         };
 
         assert_eq!(bson, expected_bson);
     }
+
+    #[test]
+    fn next_season_promotes_and_relegates() {
+        let mut league = League::new("Test League", "", crate::models::Sport::Chess);
+        league.divisions = vec![Division::new("Top", 1), Division::new("Bottom", 0)];
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+
+        let mut season = Season::for_league(&league, &dt, &dt, "2024");
+        season.tie_breakers = vec![TieBreaker::HeadToHead];
+        let [ann, bob, cat, dan] =
+            ["Ann", "Bob", "Cat", "Dan"].map(|name| Participant::new(name.to_string()));
+        for (division, participant) in [(0, &ann), (0, &bob), (1, &cat), (1, &dan)] {
+            season.divisions[division]
+                .table
+                .add_participant(participant);
+        }
+
+        // Bob tops the top division and Dan the bottom one.
+        let matches = [(bob.id, ann.id), (dan.id, cat.id)].map(|players| {
+            let mut a_match = Match::new(RoundId::new(), VenueId::new());
+            a_match.details = MatchDetails::Chess {
+                players,
+                result: Some(MatchOutcome::Win(Side::Home)),
+            };
+            a_match
+        });
+
+        let next = season.next_season(&league, &matches, &dt, &dt, "2025");
+
+        assert_eq!(next.name, "2025");
+        assert_eq!(next.tie_breakers, season.tie_breakers);
+        let names: Vec<Vec<&str>> = next
+            .divisions
+            .iter()
+            .map(|division| {
+                division
+                    .table
+                    .entries
+                    .iter()
+                    .map(|entry| entry.participant_name.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(names, [vec!["Bob", "Dan"], vec!["Ann", "Cat"]]);
+        assert_eq!(next.participants(), [bob.id, dan.id, ann.id, cat.id]);
+    }
+
+    #[test]
+    fn register_withdraw_and_unregister() {
+        let league = League::new("Test League", "", crate::models::Sport::Chess);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let later = Utc.with_ymd_and_hms(2024, 2, 1, 12, 00, 00).unwrap();
+        let mut season = Season::new(&league.id, &start, &later, "2024");
+        let [ann, bob, cat] = ["Ann", "Bob", "Cat"].map(|name| Participant::new(name.to_string()));
+
+        for participant in [&ann, &bob, &cat] {
+            season.register(participant, &start).unwrap();
+        }
+        // Registered players are in the table before playing a match.
+        assert_eq!(season.table.entries.len(), 3);
+        assert_eq!(season.table.entry(&cat.id).unwrap().played(), 0);
+
+        assert_eq!(season.withdraw(&bob.id, &later), Ok(()));
+        assert_eq!(season.unregister(&cat.id), Ok(()));
+        assert_eq!(
+            season.unregister(&cat.id),
+            Err(SeasonError::NotRegistered(cat.id))
+        );
+        assert_eq!(season.participants(), [ann.id]);
+        // Bob's results stand, Cat never played.
+        assert!(season.table.entry(&bob.id).is_some());
+        assert!(season.table.entry(&cat.id).is_none());
+
+        season.register(&bob, &later).unwrap();
+        assert_eq!(season.participants(), [ann.id, bob.id]);
+        assert_eq!(season.roster[1].joined, start);
+    }
+
+    #[test]
+    fn from_previous_carries_over_the_roster_and_settings() {
+        let mut league = League::new("Test League", "", crate::models::Sport::Chess);
+        league.divisions = vec![Division::new("Top", 1), Division::new("Bottom", 0)];
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let next_year = Utc.with_ymd_and_hms(2025, 1, 1, 12, 00, 00).unwrap();
+
+        let mut season = Season::for_league(&league, &dt, &dt, "2024");
+        season.scoring.draw = 2;
+        season.recurrence = Some(Recurrence::weekly(
+            chrono::Weekday::Tue,
+            chrono::NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+        ));
+        let [ann, bob, cat] = ["Ann", "Bob", "Cat"].map(|name| Participant::new(name.to_string()));
+        for participant in [&ann, &bob, &cat] {
+            season.register(participant, &dt).unwrap();
+            season.divisions[0].table.add_participant(participant);
+        }
+        season.withdraw(&cat.id, &dt).unwrap();
+
+        // Bob beat Ann, so tops the table when seeding.
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::Chess {
+            players: (bob.id, ann.id),
+            result: Some(MatchOutcome::Win(Side::Home)),
+        };
+        let matches = [a_match];
+
+        let next = season.from_previous(&league, &matches, &next_year, &next_year, "2025", false);
+        assert_eq!(next.status, SeasonStatus::Draft);
+        assert_eq!(next.scoring, season.scoring);
+        assert_eq!(next.recurrence, season.recurrence);
+        assert_eq!(next.participants(), [ann.id, bob.id]);
+        assert_eq!(next.roster[0].joined, next_year);
+        let first = &next.divisions[0].table.entries[0];
+        assert_eq!((first.participant_id, first.played()), (ann.id, 0));
+
+        let seeded = season.from_previous(&league, &matches, &next_year, &next_year, "2025", true);
+        assert_eq!(seeded.table.entries[0].participant_id, bob.id);
+        assert_eq!(seeded.divisions[0].table.entries[0].participant_id, bob.id);
+        assert_eq!(seeded.table.entries[0].played(), 0);
+        assert!(seeded.divisions[1].table.entries.is_empty());
+    }
+
+    #[test]
+    fn register_in_divisions_then_promote() {
+        let mut league = League::new("Test League", "", crate::models::Sport::Chess);
+        league.divisions = vec![Division::new("Top", 1), Division::new("Bottom", 0)];
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let next_year = Utc.with_ymd_and_hms(2025, 1, 1, 12, 00, 00).unwrap();
+        let mut season = Season::for_league(&league, &dt, &dt, "2024");
+        let [ann, bob, cat, dan] =
+            ["Ann", "Bob", "Cat", "Dan"].map(|name| Participant::new(name.to_string()));
+        for (participant, division) in [(&ann, "Top"), (&bob, "Top"), (&cat, "Top")] {
+            season
+                .register_in_division(participant, division, &dt)
+                .unwrap();
+        }
+        // Cat was put in the wrong division, and Dan asks for one there isn't.
+        season.register_in_division(&cat, "Bottom", &dt).unwrap();
+        season.register_in_division(&dan, "Bottom", &dt).unwrap();
+        assert_eq!(
+            season.register_in_division(&dan, "Middle", &dt),
+            Err(SeasonError::UnknownDivision("Middle".to_string()))
+        );
+        assert_eq!(season.divisions[0].table.entries.len(), 2);
+        assert_eq!(season.divisions[1].table.entries.len(), 2);
+        assert_eq!(season.participants().len(), 4);
+
+        let result = |home: &Participant, away: &Participant| {
+            let mut a_match = Match::new(RoundId::new(), VenueId::new());
+            a_match.details = MatchDetails::Chess {
+                players: (home.id, away.id),
+                result: Some(MatchOutcome::Win(Side::Home)),
+            };
+            a_match
+        };
+        let matches = [result(&bob, &ann), result(&cat, &dan)];
+
+        let next = season.next_season(&league, &matches, &next_year, &next_year, "2025");
+        let names = |division: &SeasonDivision| -> Vec<String> {
+            let mut names: Vec<String> = division
+                .table
+                .entries
+                .iter()
+                .map(|entry| entry.participant_name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&next.divisions[0]), ["Bob", "Cat"]);
+        assert_eq!(names(&next.divisions[1]), ["Ann", "Dan"]);
+        assert_eq!(next.roster.len(), 4);
+    }
+
+    #[test]
+    fn standings_are_rebuilt_from_the_matches() {
+        let mut league = League::new("Test League", "", crate::models::Sport::Chess);
+        league.divisions = vec![Division::new("Top", 1)];
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let mut season = Season::for_league(&league, &dt, &dt, "2024");
+        let [ann, bob] = ["Ann", "Bob"].map(|name| Participant::new(name.to_string()));
+        for participant in [&ann, &bob] {
+            season.register(participant, &dt).unwrap();
+            season.divisions[0].table.add_participant(participant);
+        }
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::Chess {
+            players: (bob.id, ann.id),
+            result: Some(MatchOutcome::Win(Side::Home)),
+        };
+
+        let standings = season.standings(&[a_match.clone()]);
+        assert_eq!(standings[0].entry.participant_id, bob.id);
+        assert_eq!(standings[0].entry.points, season.scoring.win);
+        assert_eq!(standings[1].entry.played(), 1);
+        assert!(season
+            .standings(&[])
+            .iter()
+            .all(|ranked| ranked.entry.played() == 0));
+
+        season.record(&a_match);
+        assert_eq!(season.table.entries[0].participant_id, bob.id);
+        assert_eq!(season.table.entries[0].wins, 1);
+        assert_eq!(season.divisions[0].table.entries[1].losses, 1);
+    }
+
+    #[test]
+    fn lock_the_roster_and_table_as_the_season_moves_on() {
+        let league = League::new("Test League", "", crate::models::Sport::Chess);
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+        let mut season = Season::new(&league.id, &dt, &dt, "2024");
+        let [ann, bob] = ["Ann", "Bob"].map(|name| Participant::new(name.to_string()));
+
+        assert_eq!(
+            season.set_status(SeasonStatus::Running),
+            Err(SeasonError::InvalidTransition {
+                from: SeasonStatus::Draft,
+                to: SeasonStatus::Running
+            })
+        );
+        season.set_status(SeasonStatus::Open).unwrap();
+        season.register(&ann, &dt).unwrap();
+        season.set_status(SeasonStatus::Running).unwrap();
+
+        assert_eq!(
+            season.register(&bob, &dt),
+            Err(SeasonError::RosterLocked(SeasonStatus::Running))
+        );
+        let mut updated = season.clone();
+        updated.withdraw(&ann.id, &dt).unwrap();
+        assert_eq!(season.check_update(&updated), Ok(()));
+        updated.roster.clear();
+        assert_eq!(
+            season.check_update(&updated),
+            Err(SeasonError::RosterLocked(SeasonStatus::Running))
+        );
+
+        assert_eq!(season.check_writable(), Ok(()));
+
+        season.set_status(SeasonStatus::Finished).unwrap();
+        let mut updated = season.clone();
+        updated.table.entries.clear();
+        assert_eq!(season.check_update(&updated), Err(SeasonError::TableFrozen));
+        assert_eq!(season.check_writable(), Err(SeasonError::ReadOnly));
+
+        season.set_status(SeasonStatus::Archived).unwrap();
+        let mut updated = season.clone();
+        updated.name = "Renamed".to_string();
+        assert_eq!(season.check_update(&updated), Err(SeasonError::ReadOnly));
+    }
 }
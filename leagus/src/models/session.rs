@@ -1,10 +1,11 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
 use serde_with::with_prefix;
 
-use super::{SeasonId, SessionId};
+use super::{ParticipantId, SeasonId, SessionId};
 
 with_prefix!(prefix_season "season");
+with_prefix!(prefix_participant "participant");
 
 /// A session of a season.
 ///
@@ -20,6 +21,76 @@ pub struct Session {
     #[serde(flatten, with = "prefix_season")]
     pub season_id: SeasonId,
     pub date: DateTime<Utc>,
+    #[serde(default)]
+    pub status: SessionStatus,
+    /// The date the season's recurrence scheduled the session for, which
+    /// stays the same should the session be rescheduled.
+    #[serde(default)]
+    pub occurrence: Option<DateTime<Utc>>,
+    /// Whether participants are coming, one reply each.
+    #[serde(default)]
+    pub rsvps: Vec<Rsvp>,
+    /// The most participants who can attend, or `None` if there is no limit.
+    #[serde(default)]
+    pub capacity: Option<usize>,
+    /// Participants who want to come once there's room, first in line first.
+    #[serde(default)]
+    pub waitlist: Vec<ParticipantId>,
+    /// Participants who were attending but pulled out.
+    #[serde(default)]
+    pub withdrawals: Vec<Withdrawal>,
+}
+
+/// Whether a session is going ahead.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum SessionStatus {
+    #[default]
+    Scheduled,
+    Cancelled,
+}
+
+/// A participant's reply to whether they'll be at a session.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Rsvp {
+    #[serde(flatten, with = "prefix_participant")]
+    pub participant_id: ParticipantId,
+    pub answer: RsvpAnswer,
+    /// When they expect to arrive (UTC), if not for the start.
+    #[serde(default)]
+    pub arrival: Option<NaiveTime>,
+}
+
+/// Whether a participant is coming to a session.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RsvpAnswer {
+    Yes,
+    No,
+    Maybe,
+}
+
+/// A participant pulling out of a session they were attending.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Withdrawal {
+    #[serde(flatten, with = "prefix_participant")]
+    pub participant_id: ParticipantId,
+    pub at: DateTime<Utc>,
+    /// Whether it was within [`late_withdrawal_notice`] of the session.
+    pub late: bool,
+}
+
+/// How many participants have replied each way to a session. Those waiting
+/// for a place aren't counted as coming.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct Attendance {
+    pub yes: usize,
+    pub no: usize,
+    pub maybe: usize,
+    pub waitlisted: usize,
+}
+
+/// Withdrawing with less notice than this before a session is late.
+pub fn late_withdrawal_notice() -> TimeDelta {
+    TimeDelta::hours(24)
 }
 
 impl Session {
@@ -28,7 +99,127 @@ impl Session {
             id: SessionId::new(),
             season_id: *season_id,
             date: *date,
+            status: SessionStatus::default(),
+            occurrence: None,
+            rsvps: Vec::new(),
+            capacity: None,
+            waitlist: Vec::new(),
+            withdrawals: Vec::new(),
+        }
+    }
+
+    /// Move the session to another date, going ahead even if it had been
+    /// cancelled.
+    pub fn reschedule(&mut self, date: &DateTime<Utc>) {
+        self.date = *date;
+        self.status = SessionStatus::Scheduled;
+    }
+
+    /// Call off the session.
+    pub fn cancel(&mut self) {
+        self.status = SessionStatus::Cancelled;
+    }
+
+    /// Record whether a participant is coming, replacing any earlier reply,
+    /// at the time given.
+    ///
+    /// Someone saying yes to a full session joins the waitlist. Someone who
+    /// was attending and changes their answer is recorded as withdrawing, and
+    /// the first on the waitlist takes their place. Returns the participants
+    /// promoted from the waitlist.
+    pub fn rsvp(
+        &mut self,
+        participant_id: &ParticipantId,
+        answer: RsvpAnswer,
+        arrival: Option<NaiveTime>,
+        at: &DateTime<Utc>,
+    ) -> Vec<ParticipantId> {
+        let attending = self.is_attending(participant_id);
+        let waitlisted = self.waitlist.contains(participant_id);
+
+        if answer == RsvpAnswer::Yes {
+            if !attending && !waitlisted && self.is_full() {
+                self.waitlist.push(*participant_id);
+            }
+        } else {
+            self.waitlist.retain(|id| id != participant_id);
+            if attending {
+                self.withdrawals.push(Withdrawal {
+                    participant_id: *participant_id,
+                    at: *at,
+                    late: self.date - *at < late_withdrawal_notice(),
+                });
+            }
         }
+
+        self.rsvps
+            .retain(|rsvp| rsvp.participant_id != *participant_id);
+        self.rsvps.push(Rsvp {
+            participant_id: *participant_id,
+            answer,
+            arrival,
+        });
+        self.promote()
+    }
+
+    /// Change how many participants can attend. Raising it promotes from the
+    /// waitlist, which are returned; lowering it doesn't turn anyone away.
+    pub fn set_capacity(&mut self, capacity: Option<usize>) -> Vec<ParticipantId> {
+        self.capacity = capacity;
+        self.promote()
+    }
+
+    /// Whether the participant said yes and has a place.
+    pub fn is_attending(&self, participant_id: &ParticipantId) -> bool {
+        !self.waitlist.contains(participant_id)
+            && self.rsvps.iter().any(|rsvp| {
+                rsvp.participant_id == *participant_id && rsvp.answer == RsvpAnswer::Yes
+            })
+    }
+
+    /// Whether there's no room for anyone else.
+    pub fn is_full(&self) -> bool {
+        self.capacity
+            .is_some_and(|capacity| self.confirmed(None).len() >= capacity)
+    }
+
+    /// Move participants off the front of the waitlist while there's room.
+    fn promote(&mut self) -> Vec<ParticipantId> {
+        let mut promoted = Vec::new();
+        while !self.waitlist.is_empty() && !self.is_full() {
+            promoted.push(self.waitlist.remove(0));
+        }
+        promoted
+    }
+
+    /// Count the replies.
+    pub fn attendance(&self) -> Attendance {
+        self.rsvps
+            .iter()
+            .fold(Attendance::default(), |mut attendance, rsvp| {
+                match rsvp.answer {
+                    _ if self.waitlist.contains(&rsvp.participant_id) => attendance.waitlisted += 1,
+                    RsvpAnswer::Yes => attendance.yes += 1,
+                    RsvpAnswer::No => attendance.no += 1,
+                    RsvpAnswer::Maybe => attendance.maybe += 1,
+                }
+                attendance
+            })
+    }
+
+    /// The participants with a place who will have arrived by the time
+    /// given, or everyone with a place if there's no time.
+    pub fn confirmed(&self, by: Option<NaiveTime>) -> Vec<ParticipantId> {
+        self.rsvps
+            .iter()
+            .filter(|rsvp| rsvp.answer == RsvpAnswer::Yes)
+            .filter(|rsvp| !self.waitlist.contains(&rsvp.participant_id))
+            .filter(|rsvp| match (rsvp.arrival, by) {
+                (Some(arrival), Some(by)) => arrival <= by,
+                _ => true,
+            })
+            .map(|rsvp| rsvp.participant_id)
+            .collect()
     }
 }
 
@@ -46,12 +237,28 @@ mod tests {
         let session_uuid = Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap();
         let season_id = SeasonId::from(season_uuid);
         let session_id = SessionId::from(session_uuid);
+        let participant_id =
+            ParticipantId::from(Uuid::parse_str("22222222-2248-4345-80ec-b88499f9ff1e").unwrap());
         let dt = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
 
         let session = Session {
             id: session_id,
             season_id,
             date: dt,
+            status: SessionStatus::Cancelled,
+            occurrence: Some(dt),
+            rsvps: vec![Rsvp {
+                participant_id,
+                answer: RsvpAnswer::Maybe,
+                arrival: NaiveTime::from_hms_opt(19, 30, 0),
+            }],
+            capacity: Some(8),
+            waitlist: vec![participant_id],
+            withdrawals: vec![Withdrawal {
+                participant_id,
+                at: dt,
+                late: true,
+            }],
         };
 
         let bson = bson::to_document(&session).unwrap();
@@ -60,8 +267,95 @@ mod tests {
             "_id": session_id,
             "season_id": season_id,
             "date": "2024-01-01T12:00:00Z",
+            "status": "Cancelled",
+            "occurrence": "2024-01-01T12:00:00Z",
+            "rsvps": [{
+                "participant_id": participant_id,
+                "answer": "Maybe",
+                "arrival": "19:30:00",
+            }],
+            "capacity": bson::to_bson(&8usize).unwrap(),
+            "waitlist": [{ "_id": participant_id }],
+            "withdrawals": [{
+                "participant_id": participant_id,
+                "at": "2024-01-01T12:00:00Z",
+                "late": true,
+            }],
         };
 
         assert_eq!(bson, expected_bson);
     }
+
+    #[test]
+    fn count_replies_and_confirm_arrivals() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 19, 00, 00).unwrap();
+        let mut session = Session::new(&SeasonId::new(), &start);
+        let players: Vec<ParticipantId> = (0..4).map(|_| ParticipantId::new()).collect();
+        let late = NaiveTime::from_hms_opt(20, 0, 0);
+        let now = start - TimeDelta::days(7);
+
+        session.rsvp(&players[0], RsvpAnswer::Yes, None, &now);
+        session.rsvp(&players[1], RsvpAnswer::Yes, late, &now);
+        session.rsvp(&players[2], RsvpAnswer::No, None, &now);
+        session.rsvp(&players[3], RsvpAnswer::Yes, None, &now);
+        // Changing their mind replaces the earlier reply.
+        session.rsvp(&players[3], RsvpAnswer::Maybe, None, &now);
+
+        assert_eq!(
+            session.attendance(),
+            Attendance {
+                yes: 2,
+                no: 1,
+                maybe: 1,
+                waitlisted: 0,
+            }
+        );
+        assert_eq!(session.confirmed(None), [players[0], players[1]]);
+        assert_eq!(
+            session.confirmed(NaiveTime::from_hms_opt(19, 0, 0)),
+            [players[0]]
+        );
+        assert_eq!(session.confirmed(late), [players[0], players[1]]);
+    }
+
+    #[test]
+    fn waitlist_beyond_capacity_and_promote_on_withdrawal() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 2, 19, 00, 00).unwrap();
+        let mut session = Session::new(&SeasonId::new(), &start);
+        session.capacity = Some(2);
+        let players: Vec<ParticipantId> = (0..5).map(|_| ParticipantId::new()).collect();
+        let early = start - TimeDelta::days(3);
+        let late = start - TimeDelta::hours(2);
+
+        for player in &players[..4] {
+            assert!(session
+                .rsvp(player, RsvpAnswer::Yes, None, &early)
+                .is_empty());
+        }
+        assert_eq!(session.confirmed(None), [players[0], players[1]]);
+        assert_eq!(session.waitlist, [players[2], players[3]]);
+
+        // Leaving the waitlist isn't a withdrawal, and frees no place.
+        assert!(session
+            .rsvp(&players[3], RsvpAnswer::No, None, &early)
+            .is_empty());
+        assert!(session.withdrawals.is_empty());
+
+        let promoted = session.rsvp(&players[0], RsvpAnswer::No, None, &late);
+        assert_eq!(promoted, [players[2]]);
+        assert_eq!(session.confirmed(None), [players[1], players[2]]);
+        assert_eq!(
+            session.withdrawals,
+            [Withdrawal {
+                participant_id: players[0],
+                at: late,
+                late: true,
+            }]
+        );
+
+        session.rsvp(&players[4], RsvpAnswer::Yes, None, &late);
+        assert_eq!(session.attendance().waitlisted, 1);
+        assert_eq!(session.set_capacity(None), [players[4]]);
+        assert!(session.is_attending(&players[4]));
+    }
 }
@@ -0,0 +1,563 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{MatchDetails, MatchOutcome, ParticipantId, Side};
+
+/// A sport which a league is played in.
+///
+/// Each sport defines the shape of its [`MatchDetails`], the format used to
+/// score a match and, through that format, how the winner of a match is
+/// decided.
+#[derive(Debug, Default, Eq, Hash, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Sport {
+    #[default]
+    Tennis,
+    Padel,
+    Badminton,
+    Squash,
+    TableTennis,
+    Pickleball,
+    Chess,
+    Football,
+}
+
+/// How the score of a match is kept.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ScoringFormat {
+    /// A match is made up of sets (or games) and the first side to win the
+    /// majority of `best_of` sets wins the match.
+    ///
+    /// A set is won by the first side to reach `target` with a lead of at
+    /// least `win_by`. When a `cap` is given the set ends as soon as a side
+    /// reaches it, regardless of the lead. E.g. a tennis set is to 6, won by 2
+    /// and capped at 7 by the tie-break.
+    Sets {
+        best_of: u8,
+        target: u8,
+        win_by: u8,
+        cap: Option<u8>,
+    },
+
+    /// A match has a single result, a win for either side or a draw.
+    Result,
+
+    /// A match is decided by a single score, such as goals, with the highest
+    /// scoring side winning. Level scores are a draw.
+    Score,
+}
+
+/// The reasons a [`MatchDetails`] may be rejected.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum MatchDetailsError {
+    /// The details describe a match of a different sport.
+    WrongSport {
+        expected: Sport,
+        found: Option<Sport>,
+    },
+
+    /// The sport cannot be recorded with this kind of generic head-to-head
+    /// details.
+    IncompatibleDetails(Sport),
+
+    /// The same participant appears more than once in the match.
+    DuplicateParticipant(ParticipantId),
+
+    /// The score of a set is not a valid, completed set. Sets are numbered
+    /// from 1.
+    InvalidSetScore { set: usize, score: (u8, u8) },
+
+    /// A set was recorded after the match had already been decided. Sets are
+    /// numbered from 1.
+    SetAfterMatchDecided { set: usize },
+
+    /// A side scored less in a set than the handicap start they played from.
+    /// Sets are numbered from 1.
+    BelowStart { set: usize, score: (u8, u8) },
+}
+
+impl Display for MatchDetailsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchDetailsError::WrongSport { expected, found } => match found {
+                Some(found) => write!(f, "expected a {expected} match but found {found}"),
+                None => write!(f, "expected a {expected} match but found no details"),
+            },
+            MatchDetailsError::IncompatibleDetails(sport) => {
+                write!(f, "{sport} matches cannot be recorded with these details")
+            }
+            MatchDetailsError::DuplicateParticipant(id) => {
+                write!(f, "participant {id} appears more than once")
+            }
+            MatchDetailsError::InvalidSetScore { set, score } => {
+                write!(f, "set {set} has an invalid score {}-{}", score.0, score.1)
+            }
+            MatchDetailsError::SetAfterMatchDecided { set } => {
+                write!(f, "set {set} was played after the match was decided")
+            }
+            MatchDetailsError::BelowStart { set, score } => {
+                write!(
+                    f,
+                    "set {set} has a score {}-{} below the handicap starts",
+                    score.0, score.1
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MatchDetailsError {}
+
+impl Sport {
+    /// All the supported sports.
+    pub const ALL: [Sport; 8] = [
+        Sport::Tennis,
+        Sport::Padel,
+        Sport::Badminton,
+        Sport::Squash,
+        Sport::TableTennis,
+        Sport::Pickleball,
+        Sport::Chess,
+        Sport::Football,
+    ];
+
+    /// The format used to score a match of this sport.
+    pub fn scoring_format(&self) -> ScoringFormat {
+        match self {
+            Sport::Tennis | Sport::Padel => ScoringFormat::Sets {
+                best_of: 3,
+                target: 6,
+                win_by: 2,
+                cap: Some(7),
+            },
+            Sport::Badminton => ScoringFormat::Sets {
+                best_of: 3,
+                target: 21,
+                win_by: 2,
+                cap: Some(30),
+            },
+            Sport::Squash | Sport::TableTennis => ScoringFormat::Sets {
+                best_of: 5,
+                target: 11,
+                win_by: 2,
+                cap: None,
+            },
+            Sport::Pickleball => ScoringFormat::Sets {
+                best_of: 3,
+                target: 11,
+                win_by: 2,
+                cap: None,
+            },
+            Sport::Chess => ScoringFormat::Result,
+            Sport::Football => ScoringFormat::Score,
+        }
+    }
+
+    /// Whether the sport is played as singles. Padel is only ever doubles.
+    pub fn has_singles(&self) -> bool {
+        *self != Sport::Padel
+    }
+
+    /// Create the details of a yet to be played singles match, or `None` if
+    /// the sport is not played as singles.
+    pub fn singles(&self, players: (ParticipantId, ParticipantId)) -> Option<MatchDetails> {
+        let scores = Vec::new();
+
+        match self {
+            Sport::Tennis => Some(MatchDetails::TennisSingles { players, scores }),
+            Sport::Badminton => Some(MatchDetails::BadmintonSingles { players, scores }),
+            Sport::Squash => Some(MatchDetails::SquashSingles { players, scores }),
+            Sport::TableTennis => Some(MatchDetails::TableTennisSingles { players, scores }),
+            Sport::Pickleball => Some(MatchDetails::PickleballSingles { players, scores }),
+            Sport::Chess => Some(MatchDetails::Chess {
+                players,
+                result: None,
+            }),
+            Sport::Football => Some(MatchDetails::HeadToHeadScore {
+                players,
+                score: None,
+            }),
+            Sport::Padel => None,
+        }
+    }
+
+    /// Create the details of a yet to be played doubles match, or `None` if
+    /// the sport is not played as doubles.
+    #[allow(clippy::type_complexity)]
+    pub fn doubles(
+        &self,
+        players: (
+            (ParticipantId, ParticipantId),
+            (ParticipantId, ParticipantId),
+        ),
+    ) -> Option<MatchDetails> {
+        let scores = Vec::new();
+
+        match self {
+            Sport::Tennis => Some(MatchDetails::TennisDoubles { players, scores }),
+            Sport::Padel => Some(MatchDetails::PadelDoubles { players, scores }),
+            Sport::Badminton => Some(MatchDetails::BadmintonDoubles { players, scores }),
+            Sport::TableTennis => Some(MatchDetails::TableTennisDoubles { players, scores }),
+            Sport::Pickleball => Some(MatchDetails::PickleballDoubles { players, scores }),
+            Sport::Squash | Sport::Chess | Sport::Football => None,
+        }
+    }
+
+    /// Check the details describe a valid match of this sport.
+    ///
+    /// Matches which have not yet been decided are valid, but every recorded
+    /// set must be complete and no set may follow the deciding set.
+    ///
+    /// Generic head-to-head details are accepted by any sport whose format
+    /// they can express: scores for score-based sports and results for any
+    /// sport which allows draws.
+    pub fn validate(&self, details: &MatchDetails) -> Result<(), MatchDetailsError> {
+        let format = self.scoring_format();
+
+        match details {
+            MatchDetails::HeadToHeadScore { .. } if format != ScoringFormat::Score => {
+                return Err(MatchDetailsError::IncompatibleDetails(*self));
+            }
+            MatchDetails::HeadToHeadResult { .. } if !format.allows_draws() => {
+                return Err(MatchDetailsError::IncompatibleDetails(*self));
+            }
+            MatchDetails::HeadToHeadScore { .. } | MatchDetails::HeadToHeadResult { .. } => {}
+            _ => {
+                let found = details.sport();
+                if found != Some(*self) {
+                    return Err(MatchDetailsError::WrongSport {
+                        expected: *self,
+                        found,
+                    });
+                }
+            }
+        }
+
+        if let Some((home, away)) = details.sides() {
+            let mut seen = Vec::new();
+            for id in home.into_iter().chain(away) {
+                if seen.contains(&id) {
+                    return Err(MatchDetailsError::DuplicateParticipant(id));
+                }
+                seen.push(id);
+            }
+        }
+
+        match (format, details.set_scores()) {
+            (format @ ScoringFormat::Sets { .. }, Some(scores)) => format.validate_sets(scores),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decide the outcome of a match of this sport, or `None` if it is not
+    /// yet decided.
+    pub fn outcome(&self, details: &MatchDetails) -> Option<MatchOutcome> {
+        match details.set_scores() {
+            Some(scores) => self.scoring_format().outcome(scores),
+            None => details.outcome(),
+        }
+    }
+}
+
+impl ScoringFormat {
+    /// The number of sets a side must win to win the match.
+    pub fn sets_to_win(&self) -> Option<u8> {
+        match self {
+            ScoringFormat::Sets { best_of, .. } => Some(best_of / 2 + 1),
+            ScoringFormat::Result | ScoringFormat::Score => None,
+        }
+    }
+
+    /// Whether a match in this format can end in a draw.
+    pub fn allows_draws(&self) -> bool {
+        match self {
+            ScoringFormat::Sets { .. } => false,
+            ScoringFormat::Result | ScoringFormat::Score => true,
+        }
+    }
+
+    /// Decide which side won a completed set, or `None` if the score is not a
+    /// valid, completed set in this format.
+    pub fn set_winner(&self, score: (u8, u8)) -> Option<Side> {
+        let ScoringFormat::Sets {
+            target,
+            win_by,
+            cap,
+            ..
+        } = *self
+        else {
+            return None;
+        };
+
+        let (won, lost) = (score.0.max(score.1), score.0.min(score.1));
+        let lead = won - lost;
+
+        let complete = if won < target || lead == 0 || cap.is_some_and(|cap| won > cap) {
+            false
+        } else if won == target {
+            lead >= win_by
+        } else if cap == Some(won) {
+            // Reaching the cap ends the set with any lead, but only from a
+            // position where the set could not already have been won.
+            lead <= win_by && lost + 1 >= target
+        } else {
+            lead == win_by
+        };
+
+        match (complete, score.0 > score.1) {
+            (false, _) => None,
+            (true, true) => Some(Side::Home),
+            (true, false) => Some(Side::Away),
+        }
+    }
+
+    /// Decide the outcome of a match from its set scores, or `None` if the
+    /// match is not yet decided.
+    pub fn outcome(&self, scores: &[(u8, u8)]) -> Option<MatchOutcome> {
+        let sets_to_win = self.sets_to_win()?;
+        let (mut home, mut away) = (0, 0);
+
+        for score in scores {
+            match self.set_winner(*score)? {
+                Side::Home => home += 1,
+                Side::Away => away += 1,
+            }
+
+            if home == sets_to_win {
+                return Some(MatchOutcome::Win(Side::Home));
+            }
+            if away == sets_to_win {
+                return Some(MatchOutcome::Win(Side::Away));
+            }
+        }
+
+        None
+    }
+
+    fn validate_sets(&self, scores: &[(u8, u8)]) -> Result<(), MatchDetailsError> {
+        for (index, score) in scores.iter().enumerate() {
+            let set = index + 1;
+
+            if self.outcome(&scores[..index]).is_some() {
+                return Err(MatchDetailsError::SetAfterMatchDecided { set });
+            }
+
+            if self.set_winner(*score).is_none() {
+                return Err(MatchDetailsError::InvalidSetScore { set, score: *score });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Sport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Sport::Tennis => "tennis",
+            Sport::Padel => "padel",
+            Sport::Badminton => "badminton",
+            Sport::Squash => "squash",
+            Sport::TableTennis => "table tennis",
+            Sport::Pickleball => "pickleball",
+            Sport::Chess => "chess",
+            Sport::Football => "football",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Sport {
+    type Err = String;
+
+    /// Parse a sport from its name, ignoring case, spaces, dashes and
+    /// underscores. E.g. "Table Tennis" and "table-tennis" are both accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalise = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let name = normalise(s);
+
+        Sport::ALL
+            .into_iter()
+            .find(|sport| normalise(&sport.to_string()) == name)
+            .ok_or_else(|| format!("unknown sport \"{s}\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn singles(sport: Sport, scores: Vec<(u8, u8)>) -> MatchDetails {
+        let players = (ParticipantId::new(), ParticipantId::new());
+        let mut details = sport.singles(players).unwrap();
+        if let Some(existing) = details.set_scores_mut() {
+            *existing = scores;
+        }
+        details
+    }
+
+    #[test]
+    fn tennis_set_scores() {
+        let format = Sport::Tennis.scoring_format();
+
+        assert_eq!(format.set_winner((6, 4)), Some(Side::Home));
+        assert_eq!(format.set_winner((5, 7)), Some(Side::Away));
+        assert_eq!(format.set_winner((7, 6)), Some(Side::Home));
+        assert_eq!(format.set_winner((6, 5)), None);
+        assert_eq!(format.set_winner((7, 4)), None);
+        assert_eq!(format.set_winner((8, 6)), None);
+    }
+
+    #[test]
+    fn badminton_set_scores() {
+        let format = Sport::Badminton.scoring_format();
+
+        assert_eq!(format.set_winner((21, 19)), Some(Side::Home));
+        assert_eq!(format.set_winner((24, 22)), Some(Side::Home));
+        assert_eq!(format.set_winner((29, 30)), Some(Side::Away));
+        assert_eq!(format.set_winner((21, 20)), None);
+        assert_eq!(format.set_winner((25, 20)), None);
+        assert_eq!(format.set_winner((31, 29)), None);
+    }
+
+    #[test]
+    fn squash_is_best_of_five() {
+        let details = singles(Sport::Squash, vec![(11, 9), (8, 11), (12, 10), (11, 3)]);
+
+        assert_eq!(Sport::Squash.validate(&details), Ok(()));
+        assert_eq!(
+            Sport::Squash.outcome(&details),
+            Some(MatchOutcome::Win(Side::Home))
+        );
+    }
+
+    #[test]
+    fn undecided_match_is_valid() {
+        let details = singles(Sport::Tennis, vec![(6, 4)]);
+
+        assert_eq!(Sport::Tennis.validate(&details), Ok(()));
+        assert_eq!(Sport::Tennis.outcome(&details), None);
+    }
+
+    #[test]
+    fn reject_set_after_match_decided() {
+        let details = singles(Sport::Pickleball, vec![(11, 5), (11, 7), (5, 11)]);
+
+        assert_eq!(
+            Sport::Pickleball.validate(&details),
+            Err(MatchDetailsError::SetAfterMatchDecided { set: 3 })
+        );
+    }
+
+    #[test]
+    fn reject_invalid_set_score() {
+        let details = singles(Sport::TableTennis, vec![(11, 10)]);
+
+        assert_eq!(
+            Sport::TableTennis.validate(&details),
+            Err(MatchDetailsError::InvalidSetScore {
+                set: 1,
+                score: (11, 10)
+            })
+        );
+    }
+
+    #[test]
+    fn reject_wrong_sport() {
+        let details = singles(Sport::Tennis, Vec::new());
+
+        assert_eq!(
+            Sport::Badminton.validate(&details),
+            Err(MatchDetailsError::WrongSport {
+                expected: Sport::Badminton,
+                found: Some(Sport::Tennis)
+            })
+        );
+    }
+
+    #[test]
+    fn reject_duplicate_participant() {
+        let player = ParticipantId::new();
+        let other = ParticipantId::new();
+        let details = Sport::Padel
+            .doubles(((player, other), (ParticipantId::new(), player)))
+            .unwrap();
+
+        assert_eq!(
+            Sport::Padel.validate(&details),
+            Err(MatchDetailsError::DuplicateParticipant(player))
+        );
+    }
+
+    #[test]
+    fn chess_result() {
+        let players = (ParticipantId::new(), ParticipantId::new());
+        let details = MatchDetails::Chess {
+            players,
+            result: Some(MatchOutcome::Draw),
+        };
+
+        assert_eq!(Sport::Chess.validate(&details), Ok(()));
+        assert_eq!(Sport::Chess.outcome(&details), Some(MatchOutcome::Draw));
+    }
+
+    #[test]
+    fn football_uses_head_to_head_scores() {
+        let players = (ParticipantId::new(), ParticipantId::new());
+        let mut details = Sport::Football.singles(players).unwrap();
+        assert_eq!(Sport::Football.validate(&details), Ok(()));
+
+        details = MatchDetails::HeadToHeadScore {
+            players,
+            score: Some((4, 4)),
+        };
+        assert_eq!(Sport::Football.validate(&details), Ok(()));
+        assert_eq!(Sport::Football.outcome(&details), Some(MatchOutcome::Draw));
+
+        assert_eq!(
+            Sport::Squash.validate(&details),
+            Err(MatchDetailsError::IncompatibleDetails(Sport::Squash))
+        );
+    }
+
+    #[test]
+    fn head_to_head_results_need_draws() {
+        let details = MatchDetails::HeadToHeadResult {
+            players: (ParticipantId::new(), ParticipantId::new()),
+            result: Some(MatchOutcome::Draw),
+        };
+
+        assert_eq!(Sport::Chess.validate(&details), Ok(()));
+        assert_eq!(Sport::Football.validate(&details), Ok(()));
+        assert_eq!(
+            Sport::Tennis.validate(&details),
+            Err(MatchDetailsError::IncompatibleDetails(Sport::Tennis))
+        );
+    }
+
+    #[test]
+    fn parse_sport_names() {
+        assert_eq!("Tennis".parse(), Ok(Sport::Tennis));
+        assert_eq!("table-tennis".parse(), Ok(Sport::TableTennis));
+        assert_eq!("Table Tennis".parse(), Ok(Sport::TableTennis));
+        assert!("curling".parse::<Sport>().is_err());
+    }
+
+    #[test]
+    fn not_every_sport_has_singles_and_doubles() {
+        let id = ParticipantId::new;
+
+        assert!(Sport::Padel.singles((id(), id())).is_none());
+        assert!(Sport::Squash
+            .doubles(((id(), id()), (id(), id())))
+            .is_none());
+        assert!(Sport::Chess.doubles(((id(), id()), (id(), id()))).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::ParticipantId;
+
+with_prefix!(prefix_participant "participant");
+with_prefix!(prefix_team "team");
+
+/// The members of a participant which is a team, and the rules on who may
+/// play for it.
+///
+/// The team plays its matches, and earns its table points, as one
+/// participant. Which members played is recorded in each match's lineups.
+#[derive(Debug, Default, Eq, Hash, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Team {
+    #[serde(default)]
+    pub members: Vec<Member>,
+    #[serde(default)]
+    pub eligibility: Eligibility,
+}
+
+/// A participant who may play for a team.
+#[derive(Debug, Eq, Hash, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Member {
+    #[serde(flatten, with = "prefix_participant")]
+    pub participant_id: ParticipantId,
+    #[serde(default)]
+    pub captain: bool,
+}
+
+/// Limits on a team's members and how many of them play in each match.
+#[derive(Debug, Default, Eq, Hash, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Eligibility {
+    /// The most members the team may have.
+    #[serde(default)]
+    pub max_members: Option<usize>,
+    /// The fewest members who must play in a match.
+    #[serde(default)]
+    pub min_players: Option<usize>,
+    /// The most members who may play in a match.
+    #[serde(default)]
+    pub max_players: Option<usize>,
+}
+
+/// The members who played for a team in a match.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Lineup {
+    #[serde(flatten, with = "prefix_team")]
+    pub team_id: ParticipantId,
+    pub members: Vec<ParticipantId>,
+}
+
+/// Why a member or lineup breaks a team's rules.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum TeamError {
+    /// The participant is not a team.
+    NotATeam,
+    /// The team is not playing in the match.
+    NotInMatch,
+    /// The participant is already a member of the team.
+    AlreadyMember(ParticipantId),
+    /// The participant is not a member of the team.
+    NotAMember(ParticipantId),
+    /// The member is already playing for the other side of the match.
+    PlayingForOpponent(ParticipantId),
+    /// The team already has the most members allowed.
+    TooManyMembers { max: usize },
+    /// Fewer members played than the team must field.
+    TooFewPlayers { min: usize, played: usize },
+    /// More members played than the team may field.
+    TooManyPlayers { max: usize, played: usize },
+}
+
+impl Display for TeamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TeamError::NotATeam => write!(f, "not a team"),
+            TeamError::NotInMatch => write!(f, "the team isn't playing in the match"),
+            TeamError::AlreadyMember(id) => write!(f, "{id} is already a member"),
+            TeamError::NotAMember(id) => write!(f, "{id} isn't a member of the team"),
+            TeamError::PlayingForOpponent(id) => {
+                write!(f, "{id} is already playing for the other side")
+            }
+            TeamError::TooManyMembers { max } => write!(f, "teams may have at most {max} members"),
+            TeamError::TooFewPlayers { min, played } => {
+                write!(f, "{played} played but at least {min} must")
+            }
+            TeamError::TooManyPlayers { max, played } => {
+                write!(f, "{played} played but at most {max} may")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TeamError {}
+
+impl Team {
+    /// Add a member, within the most members allowed.
+    pub fn add_member(
+        &mut self,
+        participant_id: &ParticipantId,
+        captain: bool,
+    ) -> Result<(), TeamError> {
+        if self.is_member(participant_id) {
+            return Err(TeamError::AlreadyMember(*participant_id));
+        }
+        if let Some(max) = self.eligibility.max_members {
+            if self.members.len() >= max {
+                return Err(TeamError::TooManyMembers { max });
+            }
+        }
+        self.members.push(Member {
+            participant_id: *participant_id,
+            captain,
+        });
+        Ok(())
+    }
+
+    /// Remove a member. Returns `false` if they weren't a member.
+    pub fn remove_member(&mut self, participant_id: &ParticipantId) -> bool {
+        let members = self.members.len();
+        self.members
+            .retain(|member| member.participant_id != *participant_id);
+        self.members.len() < members
+    }
+
+    /// Whether the participant is a member of the team.
+    pub fn is_member(&self, participant_id: &ParticipantId) -> bool {
+        self.members
+            .iter()
+            .any(|member| member.participant_id == *participant_id)
+    }
+
+    /// The members who captain the team.
+    pub fn captains(&self) -> Vec<ParticipantId> {
+        self.members
+            .iter()
+            .filter(|member| member.captain)
+            .map(|member| member.participant_id)
+            .collect()
+    }
+
+    /// Check the members who played in a match are all members, and that
+    /// there were neither too few nor too many of them.
+    pub fn check_lineup(&self, members: &[ParticipantId]) -> Result<(), TeamError> {
+        if let Some(id) = members.iter().find(|id| !self.is_member(id)) {
+            return Err(TeamError::NotAMember(*id));
+        }
+        let played = members.len();
+        if let Some(min) = self.eligibility.min_players {
+            if played < min {
+                return Err(TeamError::TooFewPlayers { min, played });
+            }
+        }
+        if let Some(max) = self.eligibility.max_players {
+            if played > max {
+                return Err(TeamError::TooManyPlayers { max, played });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use pretty_assertions::assert_eq;
+
+    use crate::models::Participant;
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let member_uuid = Uuid::parse_str("11111111-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let mut team = Participant::new_team("Rovers".to_string());
+        team.id = ParticipantId::from(uuid);
+        let rules = team.team.as_mut().unwrap();
+        rules.eligibility.max_players = Some(4);
+        rules
+            .add_member(&ParticipantId::from(member_uuid), true)
+            .unwrap();
+
+        let bson = bson::to_document(&team).unwrap();
+
+        let expected_bson = doc! {
+            "_id": uuid,
+            "name": "Rovers",
+            "team": {
+                "members": [{ "participant_id": member_uuid, "captain": true }],
+                "eligibility": {
+                    "max_members": null,
+                    "min_players": null,
+                    "max_players": bson::to_bson(&4usize).unwrap(),
+                },
+            },
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+
+    #[test]
+    fn limit_members_and_captains() {
+        let mut team = Team::default();
+        team.eligibility.max_members = Some(2);
+        let (ann, bob, cat) = (
+            ParticipantId::new(),
+            ParticipantId::new(),
+            ParticipantId::new(),
+        );
+
+        assert_eq!(team.add_member(&ann, true), Ok(()));
+        assert_eq!(
+            team.add_member(&ann, false),
+            Err(TeamError::AlreadyMember(ann))
+        );
+        assert_eq!(team.add_member(&bob, false), Ok(()));
+        assert_eq!(
+            team.add_member(&cat, false),
+            Err(TeamError::TooManyMembers { max: 2 })
+        );
+        assert_eq!(team.captains(), [ann]);
+
+        assert!(team.remove_member(&bob));
+        assert!(!team.remove_member(&bob));
+        assert_eq!(team.add_member(&cat, false), Ok(()));
+    }
+}
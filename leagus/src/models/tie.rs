@@ -0,0 +1,564 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use serde_with::with_prefix;
+
+use super::{
+    Lineup, Match, MatchDetails, MatchId, MatchOutcome, Participant, ParticipantId, RoundId, Side,
+    Sport, TieId, VenueId,
+};
+
+with_prefix!(prefix_round "round");
+with_prefix!(prefix_home "home");
+with_prefix!(prefix_away "away");
+with_prefix!(prefix_team "team");
+
+/// A fixture between two teams made up of several rubbers, each a singles or
+/// doubles [`Match`] between their members.
+///
+/// The team winning the most rubbers wins the tie. The tie is scored in the
+/// teams' points table as one match, see [`Tie::details`].
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Tie {
+    // Flatten will inline this field into its parent.
+    // See https://serde.rs/attr-flatten.html
+    #[serde(flatten)]
+    pub id: TieId,
+    #[serde(flatten, with = "prefix_round")]
+    pub round_id: RoundId,
+    #[serde(flatten, with = "prefix_home")]
+    pub home: ParticipantId,
+    #[serde(flatten, with = "prefix_away")]
+    pub away: ParticipantId,
+    pub format: TieFormat,
+    /// The lineups submitted by each team.
+    #[serde(default)]
+    pub lineups: Vec<TieLineup>,
+    /// The match of each rubber in order of play, once both lineups are in.
+    #[serde(default)]
+    pub rubbers: Vec<MatchId>,
+}
+
+/// The rubbers of a tie and the rules on who may play them.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TieFormat {
+    /// The rubbers in order of play.
+    pub rubbers: Vec<RubberKind>,
+    /// The most rubbers any one member may play.
+    #[serde(default)]
+    pub max_rubbers_per_player: Option<usize>,
+    /// Whether singles players must be listed strongest first, by rating.
+    #[serde(default)]
+    pub singles_in_rating_order: bool,
+}
+
+/// Whether a rubber is played as singles or doubles.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RubberKind {
+    Singles,
+    Doubles,
+}
+
+/// The members a team has put forward for each rubber of a tie, in order of
+/// play.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct TieLineup {
+    #[serde(flatten, with = "prefix_team")]
+    pub team_id: ParticipantId,
+    pub rubbers: Vec<Vec<ParticipantId>>,
+}
+
+/// Why a lineup can't be accepted or the rubbers can't be played.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TieError {
+    /// The participant is not a team.
+    NotATeam,
+    /// The team is not playing in the tie.
+    NotInTie,
+    /// The rubbers have been made, so lineups can no longer change.
+    AlreadyStarted,
+    /// The lineup doesn't name players for every rubber.
+    WrongRubberCount { expected: usize, got: usize },
+    /// A rubber has the wrong number of players for singles or doubles.
+    WrongPlayerCount { rubber: usize, expected: usize },
+    /// The player is not a member of the team.
+    NotAMember(ParticipantId),
+    /// The player is named more than once in the same rubber.
+    DuplicatePlayer {
+        rubber: usize,
+        player: ParticipantId,
+    },
+    /// The player is down for more rubbers than allowed.
+    TooManyRubbers { player: ParticipantId, max: usize },
+    /// A singles player is rated above the player listed before them.
+    OutOfOrder { rubber: usize },
+    /// A team hasn't submitted its lineup yet.
+    LineupMissing(ParticipantId),
+    /// The sport isn't played as the rubber needs.
+    NotPlayable(RubberKind),
+}
+
+impl Display for TieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TieError::NotATeam => write!(f, "not a team"),
+            TieError::NotInTie => write!(f, "the team isn't playing in the tie"),
+            TieError::AlreadyStarted => write!(f, "the rubbers have already been made"),
+            TieError::WrongRubberCount { expected, got } => {
+                write!(f, "{got} rubbers named but the tie has {expected}")
+            }
+            TieError::WrongPlayerCount { rubber, expected } => {
+                write!(f, "rubber {} needs {expected} players", rubber + 1)
+            }
+            TieError::NotAMember(id) => write!(f, "{id} isn't a member of the team"),
+            TieError::DuplicatePlayer { rubber, player } => {
+                write!(f, "{player} is named twice in rubber {}", rubber + 1)
+            }
+            TieError::TooManyRubbers { player, max } => {
+                write!(f, "{player} may play at most {max} rubbers")
+            }
+            TieError::OutOfOrder { rubber } => {
+                write!(
+                    f,
+                    "rubber {} is rated above an earlier singles player",
+                    rubber + 1
+                )
+            }
+            TieError::LineupMissing(id) => write!(f, "{id} hasn't submitted a lineup"),
+            TieError::NotPlayable(kind) => write!(f, "the sport isn't played as {kind:?}"),
+        }
+    }
+}
+
+impl std::error::Error for TieError {}
+
+impl RubberKind {
+    /// How many players each side puts forward.
+    pub fn players(&self) -> usize {
+        match self {
+            RubberKind::Singles => 1,
+            RubberKind::Doubles => 2,
+        }
+    }
+}
+
+impl Tie {
+    /// Create a new tie between two teams, with no lineups (yet).
+    pub fn new(
+        round_id: &RoundId,
+        home: &ParticipantId,
+        away: &ParticipantId,
+        format: TieFormat,
+    ) -> Tie {
+        Tie {
+            id: TieId::new(),
+            round_id: *round_id,
+            home: *home,
+            away: *away,
+            format,
+            lineups: Vec::new(),
+            rubbers: Vec::new(),
+        }
+    }
+
+    /// Submit the members a team puts forward for each rubber, replacing any
+    /// lineup submitted before.
+    ///
+    /// The ratings are used to check singles players are listed strongest
+    /// first, if the format requires it. Unrated players aren't checked.
+    pub fn submit_lineup(
+        &mut self,
+        team: &Participant,
+        rubbers: Vec<Vec<ParticipantId>>,
+        ratings: &HashMap<ParticipantId, f64>,
+    ) -> Result<(), TieError> {
+        let members = team.team.as_ref().ok_or(TieError::NotATeam)?;
+        if team.id != self.home && team.id != self.away {
+            return Err(TieError::NotInTie);
+        }
+        if !self.rubbers.is_empty() {
+            return Err(TieError::AlreadyStarted);
+        }
+        if rubbers.len() != self.format.rubbers.len() {
+            return Err(TieError::WrongRubberCount {
+                expected: self.format.rubbers.len(),
+                got: rubbers.len(),
+            });
+        }
+
+        let mut played: HashMap<ParticipantId, usize> = HashMap::new();
+        for (rubber, (kind, players)) in self.format.rubbers.iter().zip(&rubbers).enumerate() {
+            if players.len() != kind.players() {
+                return Err(TieError::WrongPlayerCount {
+                    rubber,
+                    expected: kind.players(),
+                });
+            }
+            for (index, player) in players.iter().enumerate() {
+                if !members.is_member(player) {
+                    return Err(TieError::NotAMember(*player));
+                }
+                if players[..index].contains(player) {
+                    return Err(TieError::DuplicatePlayer {
+                        rubber,
+                        player: *player,
+                    });
+                }
+                *played.entry(*player).or_default() += 1;
+            }
+        }
+        if let Some(max) = self.format.max_rubbers_per_player {
+            if let Some((player, _)) = played.iter().find(|(_, count)| **count > max) {
+                return Err(TieError::TooManyRubbers {
+                    player: *player,
+                    max,
+                });
+            }
+        }
+        if self.format.singles_in_rating_order {
+            let singles = self
+                .format
+                .rubbers
+                .iter()
+                .zip(&rubbers)
+                .enumerate()
+                .filter(|(_, (kind, _))| **kind == RubberKind::Singles)
+                .filter_map(|(rubber, (_, players))| Some((rubber, *ratings.get(&players[0])?)));
+            let mut above = f64::INFINITY;
+            for (rubber, rating) in singles {
+                if rating > above {
+                    return Err(TieError::OutOfOrder { rubber });
+                }
+                above = rating;
+            }
+        }
+
+        self.lineups.retain(|lineup| lineup.team_id != team.id);
+        self.lineups.push(TieLineup {
+            team_id: team.id,
+            rubbers,
+        });
+        Ok(())
+    }
+
+    /// The lineup a team has submitted.
+    pub fn lineup(&self, team_id: &ParticipantId) -> Option<&TieLineup> {
+        self.lineups
+            .iter()
+            .find(|lineup| lineup.team_id == *team_id)
+    }
+
+    /// Make the match of each rubber, in order of play, once both teams have
+    /// submitted their lineups. The home team's players are the home side of
+    /// every rubber, and each match records who played for each team.
+    pub fn matches(&mut self, sport: Sport, venue_id: &VenueId) -> Result<Vec<Match>, TieError> {
+        if !self.rubbers.is_empty() {
+            return Err(TieError::AlreadyStarted);
+        }
+        let home = self
+            .lineup(&self.home)
+            .ok_or(TieError::LineupMissing(self.home))?;
+        let away = self
+            .lineup(&self.away)
+            .ok_or(TieError::LineupMissing(self.away))?;
+
+        let mut matches = Vec::new();
+        for ((kind, home_players), away_players) in self
+            .format
+            .rubbers
+            .iter()
+            .zip(&home.rubbers)
+            .zip(&away.rubbers)
+        {
+            let details = match kind {
+                RubberKind::Singles => sport.singles((home_players[0], away_players[0])),
+                RubberKind::Doubles => sport.doubles((
+                    (home_players[0], home_players[1]),
+                    (away_players[0], away_players[1]),
+                )),
+            };
+            let mut a_match = Match::new(self.round_id, *venue_id);
+            a_match.details = details.ok_or(TieError::NotPlayable(*kind))?;
+            a_match.lineups = vec![
+                Lineup {
+                    team_id: self.home,
+                    members: home_players.clone(),
+                },
+                Lineup {
+                    team_id: self.away,
+                    members: away_players.clone(),
+                },
+            ];
+            matches.push(a_match);
+        }
+
+        self.rubbers = matches.iter().map(|a_match| a_match.id).collect();
+        Ok(matches)
+    }
+
+    /// The rubbers won by the home and away teams. Drawn and undecided
+    /// rubbers count for neither.
+    pub fn rubbers_won(&self, matches: &[Match]) -> (u32, u32) {
+        self.rubber_outcomes(matches).flatten().fold(
+            (0, 0),
+            |(home, away), outcome| match outcome {
+                MatchOutcome::Win(Side::Home) => (home + 1, away),
+                MatchOutcome::Win(Side::Away) => (home, away + 1),
+                MatchOutcome::Draw => (home, away),
+            },
+        )
+    }
+
+    /// The tie as a match between the two teams, scored by rubbers won, to
+    /// record in the teams' points table. Undecided until every rubber is.
+    pub fn details(&self, matches: &[Match]) -> MatchDetails {
+        let decided =
+            !self.rubbers.is_empty() && self.rubber_outcomes(matches).all(|o| o.is_some());
+        MatchDetails::HeadToHeadScore {
+            players: (self.home, self.away),
+            score: decided.then(|| self.rubbers_won(matches)),
+        }
+    }
+
+    /// The outcome of each rubber, `None` if it is undecided or missing.
+    fn rubber_outcomes<'a>(
+        &'a self,
+        matches: &'a [Match],
+    ) -> impl Iterator<Item = Option<MatchOutcome>> + 'a {
+        self.rubbers.iter().map(|match_id| {
+            matches
+                .iter()
+                .find(|a_match| a_match.id == *match_id)
+                .and_then(|a_match| a_match.scored_details().outcome())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::{doc, Uuid};
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{PointsTable, ScoringSystem};
+
+    struct Fixture {
+        tie: Tie,
+        home: Participant,
+        away: Participant,
+        /// Four members of each team, home first.
+        players: Vec<ParticipantId>,
+    }
+
+    /// Two singles then a doubles, with nobody playing more than twice.
+    fn fixture() -> Fixture {
+        let mut home = Participant::new_team("Rovers".to_string());
+        let mut away = Participant::new_team("United".to_string());
+        let players: Vec<ParticipantId> = (0..8).map(|_| ParticipantId::new()).collect();
+        for player in &players[..4] {
+            home.team
+                .as_mut()
+                .unwrap()
+                .add_member(player, false)
+                .unwrap();
+        }
+        for player in &players[4..] {
+            away.team
+                .as_mut()
+                .unwrap()
+                .add_member(player, false)
+                .unwrap();
+        }
+        let format = TieFormat {
+            rubbers: vec![
+                RubberKind::Singles,
+                RubberKind::Singles,
+                RubberKind::Doubles,
+            ],
+            max_rubbers_per_player: Some(2),
+            singles_in_rating_order: true,
+        };
+        let tie = Tie::new(&RoundId::new(), &home.id, &away.id, format);
+
+        Fixture {
+            tie,
+            home,
+            away,
+            players,
+        }
+    }
+
+    #[test]
+    fn serialize_as_bson() {
+        let uuid = Uuid::parse_str("00000000-2248-4345-80ec-b88499f9ff1e").unwrap();
+        let mut fixture = fixture();
+        fixture.tie.id = TieId::from(uuid);
+        let tie = &fixture.tie;
+
+        let bson = bson::to_document(tie).unwrap();
+
+        let expected_bson = doc! {
+            "_id": uuid,
+            "round_id": tie.round_id,
+            "home_id": tie.home,
+            "away_id": tie.away,
+            "format": {
+                "rubbers": ["Singles", "Singles", "Doubles"],
+                "max_rubbers_per_player": bson::to_bson(&2usize).unwrap(),
+                "singles_in_rating_order": true,
+            },
+            "lineups": [],
+            "rubbers": [],
+        };
+
+        assert_eq!(bson, expected_bson);
+    }
+
+    #[test]
+    fn check_lineups_against_the_rules() {
+        let Fixture {
+            mut tie,
+            home,
+            away,
+            players,
+        } = fixture();
+        let p = |index: usize| players[index];
+        let ratings = HashMap::from([(p(0), 1800.0), (p(1), 1900.0)]);
+
+        assert_eq!(
+            tie.submit_lineup(&home, vec![vec![p(0)], vec![p(1)]], &ratings),
+            Err(TieError::WrongRubberCount {
+                expected: 3,
+                got: 2
+            })
+        );
+        assert_eq!(
+            tie.submit_lineup(&home, vec![vec![p(0)], vec![p(1)], vec![p(2)]], &ratings),
+            Err(TieError::WrongPlayerCount {
+                rubber: 2,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            tie.submit_lineup(
+                &home,
+                vec![vec![p(0)], vec![p(4)], vec![p(2), p(3)]],
+                &ratings
+            ),
+            Err(TieError::NotAMember(p(4)))
+        );
+        assert_eq!(
+            tie.submit_lineup(
+                &home,
+                vec![vec![p(0)], vec![p(0)], vec![p(0), p(1)]],
+                &ratings
+            ),
+            Err(TieError::TooManyRubbers {
+                player: p(0),
+                max: 2
+            })
+        );
+        assert_eq!(
+            tie.submit_lineup(
+                &home,
+                vec![vec![p(0)], vec![p(1)], vec![p(2), p(3)]],
+                &ratings
+            ),
+            Err(TieError::OutOfOrder { rubber: 1 })
+        );
+        assert_eq!(
+            tie.submit_lineup(
+                &home,
+                vec![vec![p(1)], vec![p(0)], vec![p(1), p(2)]],
+                &ratings
+            ),
+            Ok(())
+        );
+
+        assert_eq!(
+            tie.matches(Sport::Tennis, &VenueId::new()),
+            Err(TieError::LineupMissing(away.id))
+        );
+    }
+
+    #[test]
+    fn reject_a_player_twice_in_a_rubber() {
+        let Fixture {
+            mut tie,
+            home,
+            players,
+            ..
+        } = fixture();
+        let p = |index: usize| players[index];
+
+        assert_eq!(
+            tie.submit_lineup(
+                &home,
+                vec![vec![p(0)], vec![p(1)], vec![p(2), p(2)]],
+                &HashMap::new()
+            ),
+            Err(TieError::DuplicatePlayer {
+                rubber: 2,
+                player: p(2)
+            })
+        );
+        assert!(tie.lineup(&home.id).is_none());
+    }
+
+    #[test]
+    fn rubbers_decide_the_tie_and_the_table() {
+        let Fixture {
+            mut tie,
+            home,
+            away,
+            players,
+        } = fixture();
+        let none = HashMap::new();
+        tie.submit_lineup(
+            &home,
+            vec![
+                vec![players[0]],
+                vec![players[1]],
+                vec![players[0], players[1]],
+            ],
+            &none,
+        )
+        .unwrap();
+        tie.submit_lineup(
+            &away,
+            vec![
+                vec![players[4]],
+                vec![players[5]],
+                vec![players[6], players[7]],
+            ],
+            &none,
+        )
+        .unwrap();
+
+        let mut matches = tie.matches(Sport::Tennis, &VenueId::new()).unwrap();
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[2].lineup(&away.id).unwrap().members, &players[6..]);
+        assert_eq!(
+            tie.submit_lineup(&home, Vec::new(), &none),
+            Err(TieError::AlreadyStarted)
+        );
+
+        // Home win the first singles and the doubles, the second is to play.
+        *matches[0].details.set_scores_mut().unwrap() = vec![(6, 3), (6, 4)];
+        *matches[2].details.set_scores_mut().unwrap() = vec![(6, 3), (3, 6), (7, 5)];
+        assert_eq!(tie.rubbers_won(&matches), (2, 0));
+        assert_eq!(tie.details(&matches).outcome(), None);
+
+        *matches[1].details.set_scores_mut().unwrap() = vec![(2, 6), (4, 6)];
+        let details = tie.details(&matches);
+        assert_eq!(details.outcome(), Some(MatchOutcome::Win(Side::Home)));
+
+        let mut table = PointsTable::new();
+        table.add_participant(&home);
+        table.add_participant(&away);
+        table.record(&details, &ScoringSystem::default());
+        assert_eq!(table.entry(&home.id).unwrap().points, 3);
+        assert_eq!(table.entry(&away.id).unwrap().score_for, 1);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Match, MatchOutcome, ParticipantId, PointsTableEntry, ScoringSystem, Side};
+
+/// A rule used to order participants which are level on points.
+///
+/// A season holds an ordered chain of tie-breakers, each one only applied to
+/// the participants the previous rules left tied.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub enum TieBreaker {
+    /// Points earned in the matches between the tied participants.
+    HeadToHead,
+    /// Sets won minus sets lost.
+    SetDifference,
+    /// Games won minus games lost, which is the score difference of set-based
+    /// sports.
+    GameDifference,
+    /// The most sets won.
+    SetsWon,
+    /// The fewest matches played, favouring those who earned the same points
+    /// from fewer matches.
+    MatchesPlayed,
+    /// The sum of the points of every opponent faced, rewarding those who
+    /// played the stronger field.
+    Buchholz,
+    /// The points of every opponent beaten plus half the points of every
+    /// opponent drawn with.
+    SonnebornBerger,
+    /// A coin toss, seeded so the same seed always gives the same order.
+    Random { seed: u64 },
+}
+
+/// A position in a ranked points table.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RankedEntry {
+    /// The position in the table, starting at 1. Participants still tied after
+    /// every tie-breaker share a position.
+    pub position: usize,
+    pub entry: PointsTableEntry,
+    /// The tie-breaker which separated this participant from those level on
+    /// points, if any.
+    pub decided_by: Option<TieBreaker>,
+}
+
+impl TieBreaker {
+    /// The key used to order the tied entries, larger keys rank higher.
+    ///
+    /// `tied` are the participants which are still tied, used to limit the
+    /// head-to-head comparison to matches between them.
+    pub(crate) fn key(
+        &self,
+        entry: &PointsTableEntry,
+        tied: &[ParticipantId],
+        matches: &[Match],
+        scoring: &ScoringSystem,
+    ) -> i128 {
+        match self {
+            TieBreaker::HeadToHead => head_to_head_points(entry, tied, matches, scoring).into(),
+            TieBreaker::SetDifference => {
+                i128::from(entry.sets_for) - i128::from(entry.sets_against)
+            }
+            TieBreaker::GameDifference => entry.score_difference().into(),
+            TieBreaker::SetsWon => entry.sets_for.into(),
+            TieBreaker::MatchesPlayed => -i128::from(entry.played()),
+            TieBreaker::Buchholz => buchholz(&entry.participant_id, matches, scoring).into(),
+            TieBreaker::SonnebornBerger => {
+                sonneborn_berger(&entry.participant_id, matches, scoring).into()
+            }
+            TieBreaker::Random { seed } => coin_toss(*seed, &entry.participant_id).into(),
+        }
+    }
+}
+
+impl RankedEntry {
+    /// Describe how this participant's position was decided.
+    pub fn explanation(&self) -> Option<String> {
+        self.decided_by
+            .as_ref()
+            .map(|rule| format!("Tie broken by {rule}"))
+    }
+}
+
+impl Display for TieBreaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TieBreaker::HeadToHead => write!(f, "head-to-head"),
+            TieBreaker::SetDifference => write!(f, "set difference"),
+            TieBreaker::GameDifference => write!(f, "game difference"),
+            TieBreaker::SetsWon => write!(f, "sets won"),
+            TieBreaker::MatchesPlayed => write!(f, "matches played"),
+            TieBreaker::Buchholz => write!(f, "Buchholz"),
+            TieBreaker::SonnebornBerger => write!(f, "Sonneborn-Berger"),
+            TieBreaker::Random { seed } => write!(f, "coin toss (seed {seed})"),
+        }
+    }
+}
+
+/// The points an entry earned from matches against the other tied
+/// participants.
+fn head_to_head_points(
+    entry: &PointsTableEntry,
+    tied: &[ParticipantId],
+    matches: &[Match],
+    scoring: &ScoringSystem,
+) -> u32 {
+    opponents(&entry.participant_id, matches)
+        .iter()
+        .filter(|(opponents, _, _)| opponents.iter().any(|opponent| tied.contains(opponent)))
+        .map(|(_, outcome, side)| scoring.points(*outcome, *side))
+        .sum()
+}
+
+/// The opponents a participant faced in each decided match, along with the
+/// outcome and the participant's side.
+fn opponents(
+    participant_id: &ParticipantId,
+    matches: &[Match],
+) -> Vec<(Vec<ParticipantId>, MatchOutcome, Side)> {
+    matches
+        .iter()
+        .filter_map(|a_match| {
+            let details = &a_match.scored_details();
+            let side = details.side_of(participant_id)?;
+            let outcome = details.outcome()?;
+            let (home, away) = details.sides()?;
+            let opponents = match side {
+                Side::Home => away,
+                Side::Away => home,
+            };
+            Some((opponents, outcome, side))
+        })
+        .collect()
+}
+
+/// The points every participant earned from the decided matches.
+fn points_by_participant(
+    matches: &[Match],
+    scoring: &ScoringSystem,
+) -> HashMap<ParticipantId, u32> {
+    let mut points = HashMap::new();
+
+    for a_match in matches {
+        let details = &a_match.scored_details();
+        let (Some((home, away)), Some(outcome)) = (details.sides(), details.outcome()) else {
+            continue;
+        };
+        for (team, side) in [(home, Side::Home), (away, Side::Away)] {
+            for participant_id in team {
+                *points.entry(participant_id).or_insert(0) += scoring.points(outcome, side);
+            }
+        }
+    }
+
+    points
+}
+
+/// The sum of the points earned by each opponent the participant faced,
+/// counting an opponent again for every match against them.
+pub(crate) fn buchholz(
+    participant_id: &ParticipantId,
+    matches: &[Match],
+    scoring: &ScoringSystem,
+) -> u32 {
+    let points = points_by_participant(matches, scoring);
+
+    opponents(participant_id, matches)
+        .iter()
+        .flat_map(|(opponents, _, _)| opponents)
+        .map(|opponent| points.get(opponent).copied().unwrap_or_default())
+        .sum()
+}
+
+/// The Sonneborn-Berger score of a participant, doubled so that the half
+/// points from draws stay whole.
+pub(crate) fn sonneborn_berger(
+    participant_id: &ParticipantId,
+    matches: &[Match],
+    scoring: &ScoringSystem,
+) -> u32 {
+    let points = points_by_participant(matches, scoring);
+
+    opponents(participant_id, matches)
+        .iter()
+        .map(|(opponents, outcome, side)| {
+            let weight = match outcome {
+                MatchOutcome::Win(winner) if winner == side => 2,
+                MatchOutcome::Win(_) => 0,
+                MatchOutcome::Draw => 1,
+            };
+            opponents
+                .iter()
+                .map(|opponent| weight * points.get(opponent).copied().unwrap_or_default())
+                .sum::<u32>()
+        })
+        .sum()
+}
+
+/// A deterministic pseudo-random value for the participant, using the
+/// SplitMix64 mixing function so the order only depends on the seed and the
+/// participant's id.
+fn coin_toss(seed: u64, participant_id: &ParticipantId) -> u64 {
+    let mix = |mut z: u64| {
+        z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+
+    participant_id
+        .id
+        .bytes()
+        .chunks(8)
+        .fold(mix(seed), |state, chunk| {
+            let word = u64::from_le_bytes(chunk.try_into().expect("uuid is 16 bytes"));
+            mix(state ^ word)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{MatchDetails, Participant, RoundId, VenueId};
+
+    #[test]
+    fn coin_toss_is_deterministic() {
+        let participant = Participant::new("Ann".to_string());
+        let entry = PointsTableEntry::new(&participant);
+        let rule = TieBreaker::Random { seed: 7 };
+        let scoring = ScoringSystem::default();
+
+        assert_eq!(
+            rule.key(&entry, &[], &[], &scoring),
+            rule.key(&entry, &[], &[], &scoring)
+        );
+        assert_ne!(
+            rule.key(&entry, &[], &[], &scoring),
+            TieBreaker::Random { seed: 8 }.key(&entry, &[], &[], &scoring)
+        );
+    }
+
+    #[test]
+    fn buchholz_and_sonneborn_berger() {
+        let (ann, bob, cat) = (
+            ParticipantId::new(),
+            ParticipantId::new(),
+            ParticipantId::new(),
+        );
+        let chess = |home, away, result| {
+            let mut a_match = Match::new(RoundId::new(), VenueId::new());
+            a_match.details = MatchDetails::Chess {
+                players: (home, away),
+                result: Some(result),
+            };
+            a_match
+        };
+        let matches = [
+            chess(ann, bob, MatchOutcome::Draw),
+            chess(ann, cat, MatchOutcome::Win(Side::Home)),
+            chess(cat, bob, MatchOutcome::Win(Side::Away)),
+        ];
+        let scoring = ScoringSystem::default();
+
+        // Ann and Bob have 4 points each and Cat has none.
+        assert_eq!(buchholz(&ann, &matches, &scoring), 4);
+        assert_eq!(buchholz(&cat, &matches, &scoring), 8);
+        // Half of Bob's points for the draw, doubled, and nothing for
+        // beating Cat.
+        assert_eq!(sonneborn_berger(&ann, &matches, &scoring), 4);
+        assert_eq!(sonneborn_berger(&cat, &matches, &scoring), 0);
+    }
+
+    #[test]
+    fn explain_decided_entries() {
+        let participant = Participant::new("Ann".to_string());
+        let mut ranked = RankedEntry {
+            position: 1,
+            entry: PointsTableEntry::new(&participant),
+            decided_by: Some(TieBreaker::SetDifference),
+        };
+
+        assert_eq!(
+            ranked.explanation(),
+            Some("Tie broken by set difference".to_string())
+        );
+
+        ranked.decided_by = None;
+        assert_eq!(ranked.explanation(), None);
+    }
+}
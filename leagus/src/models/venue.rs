@@ -1,3 +1,4 @@
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 
 use super::VenueId;
@@ -10,6 +11,43 @@ pub struct Venue {
     #[serde(flatten)]
     pub id: VenueId,
     pub name: String,
+    /// The courts, or tables, matches are played on. A venue without any is
+    /// taken to have room for every match.
+    #[serde(default)]
+    pub courts: Vec<Court>,
+}
+
+/// A court, or table or board, within a venue which one match is played on
+/// at a time.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Court {
+    /// The name of the court, unique within its venue.
+    pub name: String,
+    #[serde(default)]
+    pub surface: Option<Surface>,
+    /// When the court can be played on each week. A court without any
+    /// windows is always available.
+    #[serde(default)]
+    pub availability: Vec<Availability>,
+}
+
+/// What a court is made of.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Surface {
+    Hard,
+    Clay,
+    Grass,
+    ArtificialGrass,
+    Carpet,
+    Wood,
+}
+
+/// A weekly window when a court is available, from `start` up to `end`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Availability {
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
 }
 
 impl Venue {
@@ -17,8 +55,53 @@ impl Venue {
         Venue {
             id: VenueId::new(),
             name,
+            courts: Vec::new(),
+        }
+    }
+
+    /// Get a court by name.
+    pub fn court(&self, name: &str) -> Option<&Court> {
+        self.courts.iter().find(|court| court.name == name)
+    }
+
+    /// The courts which can be played on at the time.
+    pub fn courts_available(&self, at: &DateTime<Utc>) -> Vec<&Court> {
+        self.courts
+            .iter()
+            .filter(|court| court.is_available(at))
+            .collect()
+    }
+
+    /// How many matches can be played at once at the time, or `None` if the
+    /// venue has no courts to limit it.
+    pub fn capacity(&self, at: &DateTime<Utc>) -> Option<usize> {
+        match self.courts.is_empty() {
+            true => None,
+            false => Some(self.courts_available(at).len()),
+        }
+    }
+}
+
+impl Court {
+    pub fn new(name: &str) -> Court {
+        Court {
+            name: name.to_string(),
+            surface: None,
+            availability: Vec::new(),
         }
     }
+
+    /// Whether the court can be played on at the time.
+    pub fn is_available(&self, at: &DateTime<Utc>) -> bool {
+        self.availability.is_empty() || self.availability.iter().any(|window| window.contains(at))
+    }
+}
+
+impl Availability {
+    /// Whether the time falls within the window.
+    pub fn contains(&self, at: &DateTime<Utc>) -> bool {
+        at.weekday() == self.weekday && self.start <= at.time() && at.time() < self.end
+    }
 }
 
 #[cfg(test)]
@@ -26,6 +109,7 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
     use bson::{doc, Uuid};
+    use chrono::TimeZone;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -37,6 +121,15 @@ mod tests {
         let session = Venue {
             id,
             name: name.clone(),
+            courts: vec![Court {
+                name: "Court 1".to_string(),
+                surface: Some(Surface::Clay),
+                availability: vec![Availability {
+                    weekday: Weekday::Tue,
+                    start: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+                    end: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+                }],
+            }],
         };
 
         let bson = bson::to_document(&session).unwrap();
@@ -44,8 +137,45 @@ mod tests {
         let expected_bson = doc! {
             "_id": id,
             "name": &name,
+            "courts": [{
+                "name": "Court 1",
+                "surface": "Clay",
+                "availability": [{
+                    "weekday": "Tue",
+                    "start": "18:00:00",
+                    "end": "22:00:00",
+                }],
+            }],
         };
 
         assert_eq!(bson, expected_bson);
     }
+
+    #[test]
+    fn capacity_counts_the_courts_available() {
+        let mut venue = Venue::new("Club".to_string());
+        // A Tuesday evening and a Wednesday morning.
+        let evening = Utc.with_ymd_and_hms(2024, 1, 2, 19, 30, 00).unwrap();
+        let morning = Utc.with_ymd_and_hms(2024, 1, 3, 10, 00, 00).unwrap();
+        assert_eq!(venue.capacity(&evening), None);
+
+        let mut floodlit = Court::new("Court 1");
+        floodlit.availability.push(Availability {
+            weekday: Weekday::Tue,
+            start: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+        });
+        venue.courts = vec![floodlit, Court::new("Court 2")];
+
+        assert_eq!(venue.capacity(&evening), Some(2));
+        assert_eq!(venue.capacity(&morning), Some(1));
+        assert_eq!(
+            venue
+                .courts_available(&morning)
+                .iter()
+                .map(|court| court.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Court 2"]
+        );
+    }
 }
@@ -1,13 +1,19 @@
 pub mod mongo_store;
 pub mod sync;
 
+use crate::fixtures::Fixtures;
 use crate::models::{
-    League, LeagueId, Match, Participant, ParticipantId, Round, RoundId, Season, SeasonId, Session,
-    SessionId, Venue,
+    Bracket, BracketId, Challenge, GroupStage, GroupStageId, League, LeagueId, Match, MatchId,
+    Participant, ParticipantId, ParticipantRating, Round, RoundId, Season, SeasonError, SeasonId,
+    Session, SessionId, Venue,
 };
+use crate::ratings::PlayedMatch;
 
 /// Defines interactions with a write store.
 ///
+/// Writes to the sessions, rounds and matches of a season which has finished
+/// fail with [`SeasonError::ReadOnly`], see [`Season::check_writable`].
+///
 // See https://doc.rust-lang.org/rustc/lints/listing/warn-by-default.html?highlight=async%20fn#async-fn-in-trait
 #[allow(async_fn_in_trait)]
 pub trait WriteableStore {
@@ -16,17 +22,33 @@ pub trait WriteableStore {
     /// Create a new [`League`].
     async fn create_league(&self, league: League);
 
-    /// Create a new [`Season`].
+    /// Create a new [`Season`], making it the active season of its league
+    /// if asked to and it is running.
     async fn create_season(&self, season: &Season, make_active: bool);
 
-    /// Create a new [`Session`].
-    async fn create_session(&self, session: &Session);
+    /// Remove a [`Season`], e.g. when creating its sessions fails.
+    async fn delete_season(&self, season_id: &SeasonId);
+
+    /// Replace a [`Season`], e.g. after changing its status.
+    ///
+    /// The change must be allowed in the season's status, see
+    /// [`Season::check_update`]. A season which starts running becomes the
+    /// active season of its league.
+    async fn update_season(&self, season: &Season) -> Result<(), SeasonError>;
+
+    /// Create a new [`Session`], making it the active session of its season.
+    async fn create_session(&self, session: &Session) -> Result<(), SeasonError>;
+
+    /// Create several [`Session`]s together without making any of them
+    /// active, e.g. those scheduled for a season which hasn't started. None
+    /// of them are left behind if any fail.
+    async fn create_sessions(&self, sessions: &[Session]) -> Result<(), SeasonError>;
 
     /// Create a new [`Round`].
-    async fn create_round(&self, round: &Round);
+    async fn create_round(&self, round: &Round) -> Result<(), SeasonError>;
 
     /// Create a new [`Match`].
-    async fn create_match(&self, a_match: &Match);
+    async fn create_match(&self, a_match: &Match) -> Result<(), SeasonError>;
 
     /// Create a new [`Venue`].
     async fn create_venue(&self, venue: &Venue);
@@ -34,8 +56,33 @@ pub trait WriteableStore {
     /// Create a new [`Participant`].
     async fn create_participant(&self, participant: &Participant);
 
+    /// Create all the sessions, rounds and matches of generated [`Fixtures`]
+    /// together, leaving none of them behind if any fail.
+    async fn create_fixtures(&self, fixtures: &Fixtures) -> Result<(), SeasonError>;
+
     /// Add a [`Participant`] to a [`Round`].
-    async fn add_participant_to_round(&self, participant_id: &ParticipantId, round_id: &RoundId);
+    async fn add_participant_to_round(
+        &self,
+        participant_id: &ParticipantId,
+        round_id: &RoundId,
+    ) -> Result<(), SeasonError>;
+
+    /// Replace a [`Round`], e.g. to record who sat out once it is paired.
+    async fn update_round(&self, round: &Round) -> Result<(), SeasonError>;
+
+    /// Confirm the result of a [`Match`], advancing the winner of a knockout
+    /// [`Bracket`] and creating any matches which are now ready, or recording
+    /// the result in the table of its [`GroupStage`] group.
+    async fn confirm_match(&self, match_id: &MatchId) -> Result<(), SeasonError>;
+
+    /// Create a new knockout [`Bracket`].
+    async fn create_bracket(&self, bracket: &Bracket);
+
+    /// Create a new [`GroupStage`].
+    async fn create_group_stage(&self, group_stage: &GroupStage);
+
+    /// Replace the ratings of every participant, e.g. after recalculating them.
+    async fn replace_ratings(&self, ratings: &[ParticipantRating]);
 
     /// Get the [`League`] from the store with the matching ID.
     async fn get_league(&self, league_id: &LeagueId) -> Option<League>;
@@ -52,9 +99,21 @@ pub trait WriteableStore {
     /// Get [`Round`] by id
     async fn get_round(&self, round_id: &RoundId) -> Option<Round>;
 
+    /// Get [`Match`] by id
+    async fn get_match(&self, match_id: &MatchId) -> Option<Match>;
+
+    /// Get [`Bracket`] by id
+    async fn get_bracket(&self, bracket_id: &BracketId) -> Option<Bracket>;
+
+    /// Get [`GroupStage`] by id
+    async fn get_group_stage(&self, group_stage_id: &GroupStageId) -> Option<GroupStage>;
+
     /// Get [`Participant`] by id
     async fn get_participant(&self, participant_id: &ParticipantId) -> Option<Participant>;
 
+    /// Get the [`ParticipantRating`] of a participant
+    async fn get_rating(&self, participant_id: &ParticipantId) -> Option<ParticipantRating>;
+
     /// List all the leagues
     async fn list_leagues(&self) -> Vec<League>;
 
@@ -62,13 +121,31 @@ pub trait WriteableStore {
     async fn list_seasons(&self) -> Vec<Season>;
     async fn list_seasons_for_league(&self, league_id: &LeagueId) -> Vec<Season>;
 
+    /// List the challenges of a ladder league, oldest first.
+    async fn list_challenges_for_league(&self, league_id: &LeagueId) -> Vec<Challenge>;
+
     async fn list_sessions(&self) -> Vec<Session>;
     async fn list_sessions_for_season(&self, season_id: &SeasonId) -> Vec<Session>;
 
     async fn list_rounds_for_session(&self, session_id: &SessionId) -> Vec<Round>;
 
+    /// List every [`Round`] of a season's sessions.
+    async fn list_rounds_for_season(&self, season_id: &SeasonId) -> Vec<Round>;
+
+    /// List every [`Match`] played, or to be played, in a season.
+    async fn list_matches_for_season(&self, season_id: &SeasonId) -> Vec<Match>;
+
+    async fn list_brackets_for_season(&self, season_id: &SeasonId) -> Vec<Bracket>;
+
+    async fn list_group_stages_for_season(&self, season_id: &SeasonId) -> Vec<GroupStage>;
+
     async fn list_venues(&self) -> Vec<Venue>;
 
     async fn list_participants(&self, query_name: Option<String>) -> Vec<Participant>;
     async fn list_participants_for_round(&self, round_id: &RoundId) -> Vec<Participant>;
+
+    /// List every confirmed [`Match`] with the date of its session, oldest first.
+    async fn list_match_history(&self) -> Vec<PlayedMatch>;
+
+    async fn list_ratings(&self) -> Vec<ParticipantRating>;
 }
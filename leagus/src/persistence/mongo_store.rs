@@ -1,14 +1,20 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use futures::stream::StreamExt;
 use itertools::Itertools;
 use mongodb::error::Result;
-use mongodb::options::ClientOptions;
+use mongodb::options::{ClientOptions, FindOptions};
 use mongodb::{bson::doc, options::IndexOptions, Client, Collection, IndexModel};
 
+use crate::fixtures::Fixtures;
 use crate::models::{
-    League, LeagueId, Match, Participant, ParticipantId, Round, RoundId, Season, SeasonId, Session,
-    SessionId, Venue,
+    Bracket, BracketId, Challenge, GroupStage, GroupStageId, League, LeagueId, Match, MatchId,
+    Participant, ParticipantId, ParticipantRating, Round, RoundId, Season, SeasonError, SeasonId,
+    SeasonStatus, Session, SessionId, Venue,
 };
 use crate::persistence::WriteableStore;
+use crate::ratings::PlayedMatch;
 
 /// Name of the MongoDB Database
 const DB_NAME: &str = "leagus";
@@ -21,6 +27,10 @@ const COLLECTION_ROUNDS: &str = "rounds";
 const COLLECTION_MATCHES: &str = "matches";
 const COLLECTION_VENUES: &str = "venues";
 const COLLECTION_PARTICIPANTS: &str = "participants";
+const COLLECTION_RATINGS: &str = "ratings";
+const COLLECTION_BRACKETS: &str = "brackets";
+const COLLECTION_GROUP_STAGES: &str = "group_stages";
+const COLLECTION_CHALLENGES: &str = "challenges";
 
 #[derive(Clone)]
 pub struct MongoStore {
@@ -46,9 +56,21 @@ impl MongoStore {
         let sessions = self.bootstrap_sessions();
         let rounds = self.bootstrap_rounds();
         let matches = self.bootstrap_matches();
+        let brackets = self.bootstrap_brackets();
+        let group_stages = self.bootstrap_group_stages();
+        let challenges = self.bootstrap_challenges();
 
         // advance all asynchronously then wait for them to complete
-        futures::join!(league, seasons, sessions, rounds, matches);
+        futures::join!(
+            league,
+            seasons,
+            sessions,
+            rounds,
+            matches,
+            brackets,
+            group_stages,
+            challenges
+        );
     }
 
     /// Bootstrap the leagues collection
@@ -93,6 +115,68 @@ impl MongoStore {
         let index = IndexModel::builder().keys(doc! {"round_id": 1}).build();
         let _ = collection.create_index(index, None).await;
     }
+
+    /// Bootstrap the brackets collection
+    async fn bootstrap_brackets(&self) {
+        let collection = brackets_collection(self);
+
+        // Brackets are found by season and by the matches they hold, to
+        // advance them when a result is confirmed.
+        let season_index = IndexModel::builder().keys(doc! {"season_id": 1}).build();
+        let match_index = IndexModel::builder()
+            .keys(doc! {"matches.match_id": 1})
+            .build();
+        let _ = collection
+            .create_indexes([season_index, match_index], None)
+            .await;
+    }
+
+    /// Bootstrap the group stages collection
+    async fn bootstrap_group_stages(&self) {
+        let collection = group_stages_collection(self);
+
+        // Group stages are found by season and by the rounds of their groups,
+        // to record results when they are confirmed.
+        let season_index = IndexModel::builder().keys(doc! {"season_id": 1}).build();
+        let round_index = IndexModel::builder()
+            .keys(doc! {"groups.round_ids._id": 1})
+            .build();
+        let _ = collection
+            .create_indexes([season_index, round_index], None)
+            .await;
+    }
+
+    /// Bootstrap the challenges collection
+    async fn bootstrap_challenges(&self) {
+        let collection = challenges_collection(self);
+        let index = IndexModel::builder().keys(doc! {"league_id": 1}).build();
+        let _ = collection.create_index(index, None).await;
+    }
+
+    /// Check the season may still have its sessions, rounds and matches
+    /// written. Seasons which can't be found are left to the write itself.
+    async fn check_season(&self, season_id: &SeasonId) -> std::result::Result<(), SeasonError> {
+        match self.get_season(season_id).await {
+            Some(season) => season.check_writable(),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the season of a session may still be written.
+    async fn check_session(&self, session_id: &SessionId) -> std::result::Result<(), SeasonError> {
+        match self.get_session(session_id).await {
+            Some(session) => self.check_season(&session.season_id).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Check the season of a round may still be written.
+    async fn check_round(&self, round_id: &RoundId) -> std::result::Result<(), SeasonError> {
+        match self.get_round(round_id).await {
+            Some(round) => self.check_session(&round.session_id).await,
+            None => Ok(()),
+        }
+    }
 }
 
 impl WriteableStore for MongoStore {
@@ -108,7 +192,7 @@ impl WriteableStore for MongoStore {
         let seasons = seasons_collection(self);
         let _ = seasons.insert_one(season, None).await;
 
-        if make_active {
+        if make_active && season.status == SeasonStatus::Running {
             let leagues = leagues_collection(self);
             let _update_result = leagues
                 .update_one(
@@ -124,7 +208,40 @@ impl WriteableStore for MongoStore {
         }
     }
 
-    async fn create_session(&self, session: &Session) {
+    async fn delete_season(&self, season_id: &SeasonId) {
+        let seasons = seasons_collection(self);
+        if let Err(error) = seasons.delete_one(doc! { "_id": season_id }, None).await {
+            tracing::error!("Error removing season, {:?}", error);
+        }
+    }
+
+    async fn update_season(&self, season: &Season) -> std::result::Result<(), SeasonError> {
+        let current = self.get_season(&season.id).await;
+        if let Some(current) = &current {
+            current.check_update(season)?;
+        }
+
+        let seasons = seasons_collection(self);
+        let _ = seasons
+            .replace_one(doc! { "_id": season.id }, season, None)
+            .await;
+
+        let started = current.is_some_and(|current| current.status != SeasonStatus::Running);
+        if started && season.status == SeasonStatus::Running {
+            let leagues = leagues_collection(self);
+            let _ = leagues
+                .update_one(
+                    doc! { "_id": season.league_id },
+                    doc! { "$set": { "active_season_id": season.id } },
+                    None,
+                )
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn create_session(&self, session: &Session) -> std::result::Result<(), SeasonError> {
+        self.check_season(&session.season_id).await?;
         let sessions = sessions_collection(self);
         let _ = sessions.insert_one(session, None).await;
 
@@ -141,16 +258,43 @@ impl WriteableStore for MongoStore {
                 None,
             )
             .await;
+        Ok(())
+    }
+
+    async fn create_sessions(&self, sessions: &[Session]) -> std::result::Result<(), SeasonError> {
+        let Some(first) = sessions.first() else {
+            return Ok(());
+        };
+        self.check_season(&first.season_id).await?;
+
+        let collection = sessions_collection(self);
+        if let Err(error) = collection.insert_many(sessions, None).await {
+            tracing::error!("Error creating sessions, {:?}", error);
+
+            let session_ids: Vec<SessionId> = sessions.iter().map(|session| session.id).collect();
+            if let Err(error) = collection
+                .delete_many(doc! { "_id": { "$in": session_ids } }, None)
+                .await
+            {
+                tracing::error!("Error removing sessions, {:?}", error);
+            }
+            return Err(SeasonError::Storage(error.to_string()));
+        }
+        Ok(())
     }
 
-    async fn create_round(&self, round: &Round) {
+    async fn create_round(&self, round: &Round) -> std::result::Result<(), SeasonError> {
+        self.check_session(&round.session_id).await?;
         let rounds = rounds_collection(self);
         let _ = rounds.insert_one(round, None).await;
+        Ok(())
     }
 
-    async fn create_match(&self, a_match: &Match) {
+    async fn create_match(&self, a_match: &Match) -> std::result::Result<(), SeasonError> {
+        self.check_round(&a_match.round_id).await?;
         let matches = matches_collection(self);
         let _ = matches.insert_one(a_match, None).await;
+        Ok(())
     }
 
     async fn create_venue(&self, venue: &Venue) {
@@ -163,18 +307,81 @@ impl WriteableStore for MongoStore {
         let _ = participants.insert_one(participant, None).await;
     }
 
-    async fn add_participant_to_round(&self, participant_id: &ParticipantId, round_id: &RoundId) {
+    async fn create_fixtures(&self, fixtures: &Fixtures) -> std::result::Result<(), SeasonError> {
+        let sessions = sessions_collection(self);
+        let rounds = rounds_collection(self);
+        let matches = matches_collection(self);
+
+        let Some(first) = fixtures.sessions.first() else {
+            return Ok(());
+        };
+        self.check_season(&first.season_id).await?;
+
+        // Each collection is written in a single batch. Should any batch fail
+        // everything is removed, including whatever part of the failed batch
+        // was written, so no partial fixtures remain.
+        let mut result = sessions
+            .insert_many(&fixtures.sessions, None)
+            .await
+            .map(|_| ());
+        if result.is_ok() {
+            result = rounds.insert_many(&fixtures.rounds, None).await.map(|_| ());
+        }
+        if result.is_ok() && !fixtures.matches.is_empty() {
+            result = matches
+                .insert_many(&fixtures.matches, None)
+                .await
+                .map(|_| ());
+        }
+
+        if let Err(error) = result {
+            tracing::error!("Error creating fixtures, {:?}", error);
+
+            let match_ids: Vec<MatchId> =
+                fixtures.matches.iter().map(|a_match| a_match.id).collect();
+            let round_ids: Vec<RoundId> = fixtures.rounds.iter().map(|round| round.id).collect();
+            let session_ids: Vec<SessionId> =
+                fixtures.sessions.iter().map(|session| session.id).collect();
+            if let Err(error) = matches
+                .delete_many(doc! { "_id": { "$in": match_ids } }, None)
+                .await
+            {
+                tracing::error!("Error removing fixture matches, {:?}", error);
+            }
+            if let Err(error) = rounds
+                .delete_many(doc! { "_id": { "$in": round_ids } }, None)
+                .await
+            {
+                tracing::error!("Error removing fixture rounds, {:?}", error);
+            }
+            if let Err(error) = sessions
+                .delete_many(doc! { "_id": { "$in": session_ids } }, None)
+                .await
+            {
+                tracing::error!("Error removing fixture sessions, {:?}", error);
+            }
+            return Err(SeasonError::Storage(error.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn add_participant_to_round(
+        &self,
+        participant_id: &ParticipantId,
+        round_id: &RoundId,
+    ) -> std::result::Result<(), SeasonError> {
         let round = self.get_round(round_id).await;
 
         if round.is_none() {
             // TODO: Maybe return error?
-            return;
+            return Ok(());
         }
 
         let mut round = round.expect("Round now exists.");
+        self.check_session(&round.session_id).await?;
 
         if round.participants.contains(participant_id) {
-            return;
+            return Ok(());
         }
 
         round.participants.push(*participant_id);
@@ -194,6 +401,115 @@ impl WriteableStore for MongoStore {
                 None,
             )
             .await;
+        Ok(())
+    }
+
+    async fn update_round(&self, round: &Round) -> std::result::Result<(), SeasonError> {
+        self.check_session(&round.session_id).await?;
+        let rounds = rounds_collection(self);
+        let _ = rounds
+            .replace_one(doc! { "_id": round.id }, round, None)
+            .await;
+        Ok(())
+    }
+
+    async fn confirm_match(&self, match_id: &MatchId) -> std::result::Result<(), SeasonError> {
+        let Some(mut a_match) = self.get_match(match_id).await else {
+            return Ok(());
+        };
+        // Confirming a result twice would count it twice in a group's table.
+        if a_match.confirmed {
+            return Ok(());
+        }
+        self.check_round(&a_match.round_id).await?;
+
+        let brackets = brackets_collection(self);
+        let mut bracket = brackets
+            .find_one(doc! { "matches.match_id": match_id }, None)
+            .await
+            .ok()
+            .flatten();
+        let group_stages = group_stages_collection(self);
+        let mut group_stage = group_stages
+            .find_one(doc! { "groups.round_ids._id": a_match.round_id }, None)
+            .await
+            .ok()
+            .flatten();
+        let mut season = match self.get_round(&a_match.round_id).await {
+            Some(round) => match self.get_session(&round.session_id).await {
+                Some(session) => self.get_season(&session.season_id).await,
+                None => None,
+            },
+            None => None,
+        };
+        let scoring = season
+            .as_ref()
+            .map(|season| season.scoring.clone())
+            .unwrap_or_default();
+        let next = a_match.confirm(bracket.as_mut(), group_stage.as_mut(), &scoring)?;
+        if let Some(season) = &mut season {
+            season.record(&a_match);
+        }
+
+        let matches = matches_collection(self);
+        let _update_result = matches
+            .update_one(
+                doc! {
+                    "_id": match_id
+                },
+                doc! {
+                    "$set": { "confirmed": true }
+                },
+                None,
+            )
+            .await;
+
+        if let Some(bracket) = bracket {
+            if !next.is_empty() {
+                let _ = matches.insert_many(&next, None).await;
+            }
+            let _ = brackets
+                .replace_one(doc! { "_id": bracket.id }, &bracket, None)
+                .await;
+        }
+
+        if let Some(group_stage) = group_stage {
+            let _ = group_stages
+                .replace_one(doc! { "_id": group_stage.id }, &group_stage, None)
+                .await;
+        }
+        if let Some(season) = season {
+            let seasons = seasons_collection(self);
+            let _ = seasons
+                .replace_one(doc! { "_id": season.id }, &season, None)
+                .await;
+        }
+        Ok(())
+    }
+
+    async fn create_bracket(&self, bracket: &Bracket) {
+        let brackets = brackets_collection(self);
+        let _ = brackets.insert_one(bracket, None).await;
+    }
+
+    async fn create_group_stage(&self, group_stage: &GroupStage) {
+        let group_stages = group_stages_collection(self);
+        let _ = group_stages.insert_one(group_stage, None).await;
+    }
+
+    async fn replace_ratings(&self, ratings: &[ParticipantRating]) {
+        let collection = ratings_collection(self);
+
+        // Ratings are always recalculated from scratch so the old ones are
+        // simply thrown away.
+        if let Err(error) = collection.delete_many(doc! {}, None).await {
+            tracing::error!("Error clearing ratings, {:?}", error);
+            return;
+        }
+
+        if !ratings.is_empty() {
+            let _ = collection.insert_many(ratings, None).await;
+        }
     }
 
     async fn get_league(&self, league_id: &LeagueId) -> Option<League> {
@@ -251,6 +567,50 @@ impl WriteableStore for MongoStore {
         result.await.ok().unwrap_or_default()
     }
 
+    async fn get_match(&self, match_id: &MatchId) -> Option<Match> {
+        let matches = matches_collection(self);
+        let result = matches.find_one(
+            doc! {
+                "_id": match_id
+            },
+            None,
+        );
+        result.await.ok().unwrap_or_default()
+    }
+
+    async fn get_rating(&self, participant_id: &ParticipantId) -> Option<ParticipantRating> {
+        let ratings = ratings_collection(self);
+        let result = ratings.find_one(
+            doc! {
+                "_id": participant_id
+            },
+            None,
+        );
+        result.await.ok().unwrap_or_default()
+    }
+
+    async fn get_bracket(&self, bracket_id: &BracketId) -> Option<Bracket> {
+        let brackets = brackets_collection(self);
+        let result = brackets.find_one(
+            doc! {
+                "_id": bracket_id
+            },
+            None,
+        );
+        result.await.ok().unwrap_or_default()
+    }
+
+    async fn get_group_stage(&self, group_stage_id: &GroupStageId) -> Option<GroupStage> {
+        let group_stages = group_stages_collection(self);
+        let result = group_stages.find_one(
+            doc! {
+                "_id": group_stage_id
+            },
+            None,
+        );
+        result.await.ok().unwrap_or_default()
+    }
+
     async fn get_participant(&self, parcipant_id: &ParticipantId) -> Option<Participant> {
         let participants = participants_collection(self);
         let result = participants.find_one(
@@ -321,6 +681,34 @@ impl WriteableStore for MongoStore {
         }
     }
 
+    async fn list_challenges_for_league(&self, league_id: &LeagueId) -> Vec<Challenge> {
+        let collection = challenges_collection(self);
+        let options = FindOptions::builder().sort(doc! { "issued": 1 }).build();
+        let result = collection
+            .find(
+                doc! {
+                    "league_id": league_id
+                },
+                options,
+            )
+            .await;
+
+        match result {
+            Ok(cursor) => (cursor.collect::<Vec<Result<Challenge>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::warn!(
+                    "Error finding challenges for league '{:?}', {:?}",
+                    league_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
     async fn list_sessions(&self) -> Vec<Session> {
         let collection = sessions_collection(self);
         let result = collection.find(None, None).await;
@@ -364,6 +752,60 @@ impl WriteableStore for MongoStore {
         }
     }
 
+    async fn list_brackets_for_season(&self, season_id: &SeasonId) -> Vec<Bracket> {
+        let collection = brackets_collection(self);
+        let result = collection
+            .find(
+                doc! {
+                    "season_id": season_id
+                },
+                None,
+            )
+            .await;
+
+        match result {
+            Ok(cursor) => (cursor.collect::<Vec<Result<Bracket>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::warn!(
+                    "Error finding brackets for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    async fn list_group_stages_for_season(&self, season_id: &SeasonId) -> Vec<GroupStage> {
+        let collection = group_stages_collection(self);
+        let result = collection
+            .find(
+                doc! {
+                    "season_id": season_id
+                },
+                None,
+            )
+            .await;
+
+        match result {
+            Ok(cursor) => (cursor.collect::<Vec<Result<GroupStage>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::warn!(
+                    "Error finding group stages for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
     async fn list_rounds_for_session(&self, session_id: &SessionId) -> Vec<Round> {
         let collection = rounds_collection(self);
         let result = collection
@@ -391,6 +833,77 @@ impl WriteableStore for MongoStore {
         }
     }
 
+    async fn list_rounds_for_season(&self, season_id: &SeasonId) -> Vec<Round> {
+        let session_ids: Vec<SessionId> = self
+            .list_sessions_for_season(season_id)
+            .await
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+
+        let collection = rounds_collection(self);
+        let result = collection
+            .find(
+                doc! {
+                    "session_id": { "$in": session_ids }
+                },
+                None,
+            )
+            .await;
+
+        match result {
+            Ok(cursor) => (cursor.collect::<Vec<Result<Round>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::warn!(
+                    "Error finding rounds for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    async fn list_matches_for_season(&self, season_id: &SeasonId) -> Vec<Match> {
+        let round_ids: Vec<RoundId> = self
+            .list_rounds_for_season(season_id)
+            .await
+            .into_iter()
+            .map(|round| round.id)
+            .collect();
+        if round_ids.is_empty() {
+            return Vec::new();
+        }
+
+        let collection = matches_collection(self);
+        let result = collection
+            .find(
+                doc! {
+                    "round_id": { "$in": round_ids }
+                },
+                None,
+            )
+            .await;
+
+        match result {
+            Ok(cursor) => (cursor.collect::<Vec<Result<Match>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::warn!(
+                    "Error finding matches for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
     async fn list_venues(&self) -> Vec<Venue> {
         let collection = venues_collection(self);
         let result = collection.find(None, None).await;
@@ -486,6 +999,76 @@ impl WriteableStore for MongoStore {
             }
         }
     }
+    async fn list_match_history(&self) -> Vec<PlayedMatch> {
+        let session_dates: HashMap<SessionId, DateTime<Utc>> = self
+            .list_sessions()
+            .await
+            .into_iter()
+            .map(|session| (session.id, session.date))
+            .collect();
+
+        let rounds = rounds_collection(self).find(None, None).await;
+        let round_dates: HashMap<RoundId, DateTime<Utc>> = match rounds {
+            Ok(cursor) => (cursor.collect::<Vec<Result<Round>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .filter_map(|round| {
+                    let date = session_dates.get(&round.session_id)?;
+                    Some((round.id, *date))
+                })
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding rounds, {:?}", error);
+                return Vec::new();
+            }
+        };
+
+        let matches = matches_collection(self)
+            .find(
+                doc! {
+                    "confirmed": true
+                },
+                None,
+            )
+            .await;
+
+        let mut history: Vec<PlayedMatch> = match matches {
+            Ok(cursor) => (cursor.collect::<Vec<Result<Match>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .filter_map(|a_match| {
+                    let date = round_dates.get(&a_match.round_id)?;
+                    Some(PlayedMatch {
+                        date: *date,
+                        a_match,
+                    })
+                })
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding confirmed matches, {:?}", error);
+                Vec::new()
+            }
+        };
+
+        history.sort_by_key(|played| played.date);
+        history
+    }
+
+    async fn list_ratings(&self) -> Vec<ParticipantRating> {
+        let collection = ratings_collection(self);
+        let result = collection.find(None, None).await;
+
+        match result {
+            Ok(cursor) => (cursor.collect::<Vec<Result<ParticipantRating>>>().await)
+                .into_iter()
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding ratings, {:?}", error);
+                Vec::new()
+            }
+        }
+    }
 }
 
 // TODO: Check if this is needed with the async client?
@@ -538,3 +1121,27 @@ fn participants_collection(store: &MongoStore) -> Collection<Participant> {
     let db = store.client.database(DB_NAME);
     db.collection::<Participant>(COLLECTION_PARTICIPANTS)
 }
+
+/// Return a handle to the MongoDB Ratings Collection
+fn ratings_collection(store: &MongoStore) -> Collection<ParticipantRating> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<ParticipantRating>(COLLECTION_RATINGS)
+}
+
+/// Return a handle to the MongoDB Brackets Collection
+fn brackets_collection(store: &MongoStore) -> Collection<Bracket> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<Bracket>(COLLECTION_BRACKETS)
+}
+
+/// Return a handle to the MongoDB Group Stages Collection
+fn group_stages_collection(store: &MongoStore) -> Collection<GroupStage> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<GroupStage>(COLLECTION_GROUP_STAGES)
+}
+
+/// Return a handle to the MongoDB Challenges Collection
+fn challenges_collection(store: &MongoStore) -> Collection<Challenge> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<Challenge>(COLLECTION_CHALLENGES)
+}
@@ -1,26 +1,110 @@
 pub mod mongo_store;
 
-use crate::models::{League, LeagueId, Match, Round, Season, SeasonId, Session, Venue};
+use crate::fixtures::Fixtures;
+use crate::models::{
+    Bracket, Challenge, ChallengeId, GroupStage, GroupStageId, League, LeagueId, Match, MatchId,
+    Participant, ParticipantId, ParticipantRating, Round, RoundId, Season, SeasonError, SeasonId,
+    Session, SessionId, Tie, TieId, Venue, VenueId,
+};
+use crate::ratings::PlayedMatch;
 
 /// Defines interactions with a write store.
+///
+/// Writes to the sessions, rounds, matches and ties of a season which has
+/// finished fail with [`SeasonError::ReadOnly`], see
+/// [`Season::check_writable`].
 pub trait WriteableStore {
     // TODO: These should return an option in case of failure.
 
     /// Create a new League.
     fn create_league(&mut self, league: League);
 
+    /// Replace a [`League`], e.g. after its ladder changes.
+    fn update_league(&mut self, league: &League);
+
     /// Create a new [`Season`] season
     fn create_season(&mut self, season: &Season);
 
-    /// .
-    fn create_session(&mut self, session: &Session);
+    /// Replace a [`Season`], e.g. after setting its recurrence.
+    ///
+    /// The change must be allowed in the season's status, see
+    /// [`Season::check_update`]. A season which starts running becomes the
+    /// active season of its league.
+    fn update_season(&mut self, season: &Season) -> Result<(), SeasonError>;
+
+    /// Remove a [`Season`], e.g. when creating its sessions fails.
+    fn delete_season(&mut self, season_id: &SeasonId);
+
+    /// Create a new [`Session`].
+    fn create_session(&mut self, session: &Session) -> Result<(), SeasonError>;
+
+    /// Create several [`Session`]s together, leaving none of them behind if
+    /// any fail.
+    fn create_sessions(&mut self, sessions: &[Session]) -> Result<(), SeasonError>;
+
+    /// Replace a [`Session`], e.g. once it is rescheduled or cancelled.
+    fn update_session(&mut self, session: &Session) -> Result<(), SeasonError>;
+
+    /// Create a new [`Round`].
+    fn create_round(&mut self, round: &Round) -> Result<(), SeasonError>;
+
+    /// Replace a [`Round`], e.g. once it is paired and its sit-outs recorded.
+    fn update_round(&mut self, round: &Round) -> Result<(), SeasonError>;
+
+    /// Create a new [`Match`].
+    fn create_match(&mut self, a_match: &Match) -> Result<(), SeasonError>;
+
+    /// Replace a [`Match`], e.g. after recording a team's lineup.
+    fn update_match(&mut self, a_match: &Match) -> Result<(), SeasonError>;
+
+    /// Confirm the result of a [`Match`], so it counts towards ratings and the
+    /// table of its [`GroupStage`], and schedule the matches of its
+    /// [`Bracket`] which are now ready.
+    fn confirm_match(&mut self, match_id: &MatchId) -> Result<(), SeasonError>;
 
-    fn create_round(&mut self, round: &Round);
+    /// Create a new [`Participant`], an individual or a team.
+    fn create_participant(&mut self, participant: &Participant);
 
-    fn create_match(&mut self, a_match: &Match);
+    /// Replace a [`Participant`], e.g. after a team's members change.
+    fn update_participant(&mut self, participant: &Participant);
 
     fn create_venue(&mut self, venue: &Venue);
 
+    /// Replace a [`Venue`], e.g. after its courts change.
+    fn update_venue(&mut self, venue: &Venue);
+
+    /// Create all the sessions, rounds and matches of generated [`Fixtures`]
+    /// together, leaving none of them behind if any fail.
+    fn create_fixtures(&mut self, fixtures: &Fixtures) -> Result<(), SeasonError>;
+
+    /// Create a new knockout [`Bracket`].
+    fn create_bracket(&mut self, bracket: &Bracket);
+
+    /// Create a new [`GroupStage`].
+    fn create_group_stage(&mut self, group_stage: &GroupStage);
+
+    /// Replace a [`GroupStage`], e.g. once its knockout has been drawn.
+    fn update_group_stage(&mut self, group_stage: &GroupStage);
+
+    /// Create a new team [`Tie`].
+    fn create_tie(&mut self, tie: &Tie) -> Result<(), SeasonError>;
+
+    /// Replace a [`Tie`], e.g. after a team submits its lineup.
+    fn update_tie(&mut self, tie: &Tie) -> Result<(), SeasonError>;
+
+    /// Create the rubber matches of a started [`Tie`] and save the tie with
+    /// them, leaving none of the rubbers behind if any fail.
+    fn start_tie(&mut self, tie: &Tie, rubbers: &[Match]) -> Result<(), SeasonError>;
+
+    /// Create a new ladder [`Challenge`].
+    fn create_challenge(&mut self, challenge: &Challenge);
+
+    /// Replace a [`Challenge`], e.g. once it has been answered or played.
+    fn update_challenge(&mut self, challenge: &Challenge);
+
+    /// Replace the ratings of every participant, e.g. after recalculating them.
+    fn replace_ratings(&mut self, ratings: &[ParticipantRating]);
+
     /// Get the League from the store with the matching ID.
     fn get_league(&self, league_id: &LeagueId) -> Option<League>;
 
@@ -30,6 +114,30 @@ pub trait WriteableStore {
     /// Get [`Season`] by id
     fn get_season(&self, season_id: &SeasonId) -> Option<Season>;
 
+    /// Get [`Session`] by id
+    fn get_session(&self, session_id: &SessionId) -> Option<Session>;
+
+    /// Get [`Round`] by id
+    fn get_round(&self, round_id: &RoundId) -> Option<Round>;
+
+    /// Get [`Venue`] by id
+    fn get_venue(&self, venue_id: &VenueId) -> Option<Venue>;
+
+    /// Get [`Match`] by id
+    fn get_match(&self, match_id: &MatchId) -> Option<Match>;
+
+    /// Get [`Participant`] by id
+    fn get_participant(&self, participant_id: &ParticipantId) -> Option<Participant>;
+
+    /// Get [`GroupStage`] by id
+    fn get_group_stage(&self, group_stage_id: &GroupStageId) -> Option<GroupStage>;
+
+    /// Get [`Tie`] by id
+    fn get_tie(&self, tie_id: &TieId) -> Option<Tie>;
+
+    /// Get [`Challenge`] by id
+    fn get_challenge(&self, challenge_id: &ChallengeId) -> Option<Challenge>;
+
     /// List all the leagues
     fn list_leagues(&self) -> Vec<League>;
 
@@ -37,6 +145,29 @@ pub trait WriteableStore {
     fn list_seasons(&self) -> Vec<Season>;
     fn list_seasons_for_league(&self, league_id: &LeagueId) -> Vec<Season>;
 
+    /// List the challenges of a ladder league, oldest first.
+    fn list_challenges_for_league(&self, league_id: &LeagueId) -> Vec<Challenge>;
+
+    /// List all the venues
+    fn list_venues(&self) -> Vec<Venue>;
+
     fn list_sessions(&self) -> Vec<Session>;
     fn list_sessions_for_season(&self, season_id: &SeasonId) -> Vec<Session>;
+
+    /// List every [`Round`] of a season's sessions.
+    fn list_rounds_for_season(&self, season_id: &SeasonId) -> Vec<Round>;
+
+    /// List every [`Match`] played, or to be played, in a season.
+    fn list_matches_for_season(&self, season_id: &SeasonId) -> Vec<Match>;
+
+    /// List every [`Tie`] between teams in a season.
+    fn list_ties_for_season(&self, season_id: &SeasonId) -> Vec<Tie>;
+
+    /// List every confirmed [`Match`] with the date of its session, oldest first.
+    fn list_match_history(&self) -> Vec<PlayedMatch>;
+
+    fn list_ratings(&self) -> Vec<ParticipantRating>;
+
+    /// List all the participants
+    fn list_participants(&self) -> Vec<Participant>;
 }
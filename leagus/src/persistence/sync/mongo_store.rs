@@ -1,12 +1,21 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use mongodb::{
     bson::doc,
-    options::IndexOptions,
+    options::{FindOptions, IndexOptions},
     sync::{Client, Collection},
     IndexModel,
 };
 
-use crate::models::{League, LeagueId, Match, Round, Season, SeasonId, Session, Venue};
+use crate::fixtures::Fixtures;
+use crate::models::{
+    Bracket, Challenge, ChallengeId, GroupStage, GroupStageId, League, LeagueId, Match, MatchId,
+    Participant, ParticipantId, ParticipantRating, Round, RoundId, Season, SeasonError, SeasonId,
+    SeasonStatus, Session, SessionId, Tie, TieId, Venue, VenueId,
+};
 use crate::persistence::sync::WriteableStore;
+use crate::ratings::PlayedMatch;
 
 /// Name of the MongoDB Database
 const DB_NAME: &str = "leagus";
@@ -18,6 +27,12 @@ const COLLECTION_SESSIONS: &str = "sessions";
 const COLLECTION_ROUNDS: &str = "rounds";
 const COLLECTION_MATCHES: &str = "matches";
 const COLLECTION_VENUES: &str = "venues";
+const COLLECTION_PARTICIPANTS: &str = "participants";
+const COLLECTION_RATINGS: &str = "ratings";
+const COLLECTION_BRACKETS: &str = "brackets";
+const COLLECTION_GROUP_STAGES: &str = "group_stages";
+const COLLECTION_CHALLENGES: &str = "challenges";
+const COLLECTION_TIES: &str = "ties";
 
 pub struct MongoStore {
     client: Client,
@@ -85,6 +100,37 @@ impl MongoStore {
         let index = IndexModel::builder().keys(doc! {"round_id": 1}).build();
         let _ = collection.create_index(index, None);
     }
+
+    /// Check the season may still have its sessions, rounds and matches
+    /// written. Seasons which can't be found are left to the write itself.
+    fn check_season(&self, season_id: &SeasonId) -> Result<(), SeasonError> {
+        match self.get_season(season_id) {
+            Some(season) => season.check_writable(),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the season of a session may still be written.
+    fn check_session(&self, session_id: &SessionId) -> Result<(), SeasonError> {
+        match self.get_session(session_id) {
+            Some(session) => self.check_season(&session.season_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the season of a round may still be written.
+    fn check_round(&self, round_id: &RoundId) -> Result<(), SeasonError> {
+        match self.get_round(round_id) {
+            Some(round) => self.check_session(&round.session_id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for MongoStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WriteableStore for MongoStore {
@@ -96,6 +142,11 @@ impl WriteableStore for MongoStore {
         let _ = collection.insert_one(league, None);
     }
 
+    fn update_league(&mut self, league: &League) {
+        let leagues = leagues_collection(self);
+        let _ = leagues.replace_one(doc! { "_id": league.id }, league, None);
+    }
+
     fn create_season(&mut self, season: &Season) {
         let seasons = seasons_collection(self);
         let _ = seasons.insert_one(season, None);
@@ -116,19 +167,159 @@ impl WriteableStore for MongoStore {
         // );
     }
 
-    fn create_session(&mut self, session: &Session) {
+    fn update_season(&mut self, season: &Season) -> Result<(), SeasonError> {
+        let current = self.get_season(&season.id);
+        if let Some(current) = &current {
+            current.check_update(season)?;
+        }
+
+        let seasons = seasons_collection(self);
+        let _ = seasons.replace_one(doc! { "_id": season.id }, season, None);
+
+        let started = current.is_some_and(|current| current.status != SeasonStatus::Running);
+        if started && season.status == SeasonStatus::Running {
+            let leagues = leagues_collection(self);
+            let _ = leagues.update_one(
+                doc! { "_id": season.league_id },
+                doc! { "$set": { "active_season_id": season.id } },
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    fn delete_season(&mut self, season_id: &SeasonId) {
+        let seasons = seasons_collection(self);
+        if let Err(error) = seasons.delete_one(doc! { "_id": season_id }, None) {
+            tracing::error!("Error removing season, {:?}", error);
+        }
+    }
+
+    fn create_session(&mut self, session: &Session) -> Result<(), SeasonError> {
+        self.check_season(&session.season_id)?;
         let sessions = sessions_collection(self);
         let _ = sessions.insert_one(session, None);
+        Ok(())
     }
 
-    fn create_round(&mut self, round: &Round) {
+    fn create_sessions(&mut self, sessions: &[Session]) -> Result<(), SeasonError> {
+        let Some(first) = sessions.first() else {
+            return Ok(());
+        };
+        self.check_season(&first.season_id)?;
+
+        let collection = sessions_collection(self);
+        if let Err(error) = collection.insert_many(sessions, None) {
+            tracing::error!("Error creating sessions, {:?}", error);
+
+            let session_ids: Vec<SessionId> = sessions.iter().map(|session| session.id).collect();
+            if let Err(error) = collection.delete_many(doc! { "_id": { "$in": session_ids } }, None)
+            {
+                tracing::error!("Error removing sessions, {:?}", error);
+            }
+            return Err(SeasonError::Storage(error.to_string()));
+        }
+        Ok(())
+    }
+
+    fn update_session(&mut self, session: &Session) -> Result<(), SeasonError> {
+        self.check_season(&session.season_id)?;
+        let sessions = sessions_collection(self);
+        let _ = sessions.replace_one(doc! { "_id": session.id }, session, None);
+        Ok(())
+    }
+
+    fn create_round(&mut self, round: &Round) -> Result<(), SeasonError> {
+        self.check_session(&round.session_id)?;
         let rounds = round_collection(self);
         let _ = rounds.insert_one(round, None);
+        Ok(())
     }
 
-    fn create_match(&mut self, a_match: &Match) {
+    fn update_round(&mut self, round: &Round) -> Result<(), SeasonError> {
+        self.check_session(&round.session_id)?;
+        let rounds = round_collection(self);
+        let _ = rounds.replace_one(doc! { "_id": round.id }, round, None);
+        Ok(())
+    }
+
+    fn create_match(&mut self, a_match: &Match) -> Result<(), SeasonError> {
+        self.check_round(&a_match.round_id)?;
         let matches = match_collection(self);
         let _ = matches.insert_one(a_match, None);
+        Ok(())
+    }
+
+    fn update_match(&mut self, a_match: &Match) -> Result<(), SeasonError> {
+        self.check_round(&a_match.round_id)?;
+        let matches = match_collection(self);
+        let _ = matches.replace_one(doc! { "_id": a_match.id }, a_match, None);
+        Ok(())
+    }
+
+    fn confirm_match(&mut self, match_id: &MatchId) -> Result<(), SeasonError> {
+        let Some(mut a_match) = self.get_match(match_id) else {
+            return Ok(());
+        };
+        if a_match.confirmed {
+            return Ok(());
+        }
+        self.check_round(&a_match.round_id)?;
+
+        let brackets = brackets_collection(self);
+        let mut bracket = brackets
+            .find_one(doc! { "matches.match_id": match_id }, None)
+            .ok()
+            .flatten();
+        let group_stages = group_stages_collection(self);
+        let mut group_stage = group_stages
+            .find_one(doc! { "groups.round_ids._id": a_match.round_id }, None)
+            .ok()
+            .flatten();
+        let mut season = self
+            .get_round(&a_match.round_id)
+            .and_then(|round| self.get_session(&round.session_id))
+            .and_then(|session| self.get_season(&session.season_id));
+        let scoring = season
+            .as_ref()
+            .map(|season| season.scoring.clone())
+            .unwrap_or_default();
+        let next = a_match.confirm(bracket.as_mut(), group_stage.as_mut(), &scoring)?;
+        if let Some(season) = &mut season {
+            season.record(&a_match);
+        }
+
+        let matches = match_collection(self);
+        let _ = matches.update_one(
+            doc! { "_id": match_id },
+            doc! { "$set": { "confirmed": true } },
+            None,
+        );
+
+        if let Some(bracket) = bracket {
+            if !next.is_empty() {
+                let _ = matches.insert_many(&next, None);
+            }
+            let _ = brackets.replace_one(doc! { "_id": bracket.id }, &bracket, None);
+        }
+        if let Some(group_stage) = group_stage {
+            let _ = group_stages.replace_one(doc! { "_id": group_stage.id }, &group_stage, None);
+        }
+        if let Some(season) = season {
+            let seasons = seasons_collection(self);
+            let _ = seasons.replace_one(doc! { "_id": season.id }, &season, None);
+        }
+        Ok(())
+    }
+
+    fn create_participant(&mut self, participant: &Participant) {
+        let participants = participants_collection(self);
+        let _ = participants.insert_one(participant, None);
+    }
+
+    fn update_participant(&mut self, participant: &Participant) {
+        let participants = participants_collection(self);
+        let _ = participants.replace_one(doc! { "_id": participant.id }, participant, None);
     }
 
     fn create_venue(&mut self, venue: &Venue) {
@@ -136,6 +327,134 @@ impl WriteableStore for MongoStore {
         let _ = venues.insert_one(venue, None);
     }
 
+    fn update_venue(&mut self, venue: &Venue) {
+        let venues = venue_collection(self);
+        let _ = venues.replace_one(doc! { "_id": venue.id }, venue, None);
+    }
+
+    fn create_fixtures(&mut self, fixtures: &Fixtures) -> Result<(), SeasonError> {
+        let sessions = sessions_collection(self);
+        let rounds = round_collection(self);
+        let matches = match_collection(self);
+
+        let Some(first) = fixtures.sessions.first() else {
+            return Ok(());
+        };
+        self.check_season(&first.season_id)?;
+
+        // Each collection is written in a single batch. Should any batch fail
+        // everything is removed, including whatever part of the failed batch
+        // was written, so no partial fixtures remain.
+        let mut result = sessions.insert_many(&fixtures.sessions, None).map(|_| ());
+        if result.is_ok() {
+            result = rounds.insert_many(&fixtures.rounds, None).map(|_| ());
+        }
+        if result.is_ok() && !fixtures.matches.is_empty() {
+            result = matches.insert_many(&fixtures.matches, None).map(|_| ());
+        }
+
+        if let Err(error) = result {
+            tracing::error!("Error creating fixtures, {:?}", error);
+
+            let match_ids: Vec<MatchId> =
+                fixtures.matches.iter().map(|a_match| a_match.id).collect();
+            let round_ids: Vec<RoundId> = fixtures.rounds.iter().map(|round| round.id).collect();
+            let session_ids: Vec<SessionId> =
+                fixtures.sessions.iter().map(|session| session.id).collect();
+            if let Err(error) = matches.delete_many(doc! { "_id": { "$in": match_ids } }, None) {
+                tracing::error!("Error removing fixture matches, {:?}", error);
+            }
+            if let Err(error) = rounds.delete_many(doc! { "_id": { "$in": round_ids } }, None) {
+                tracing::error!("Error removing fixture rounds, {:?}", error);
+            }
+            if let Err(error) = sessions.delete_many(doc! { "_id": { "$in": session_ids } }, None) {
+                tracing::error!("Error removing fixture sessions, {:?}", error);
+            }
+            return Err(SeasonError::Storage(error.to_string()));
+        }
+        Ok(())
+    }
+
+    fn create_bracket(&mut self, bracket: &Bracket) {
+        let brackets = brackets_collection(self);
+        let _ = brackets.insert_one(bracket, None);
+    }
+
+    fn create_group_stage(&mut self, group_stage: &GroupStage) {
+        let group_stages = group_stages_collection(self);
+        let _ = group_stages.insert_one(group_stage, None);
+    }
+
+    fn update_group_stage(&mut self, group_stage: &GroupStage) {
+        let group_stages = group_stages_collection(self);
+        let _ = group_stages.replace_one(doc! { "_id": group_stage.id }, group_stage, None);
+    }
+
+    fn create_tie(&mut self, tie: &Tie) -> Result<(), SeasonError> {
+        self.check_round(&tie.round_id)?;
+        let ties = ties_collection(self);
+        let _ = ties.insert_one(tie, None);
+        Ok(())
+    }
+
+    fn update_tie(&mut self, tie: &Tie) -> Result<(), SeasonError> {
+        self.check_round(&tie.round_id)?;
+        let ties = ties_collection(self);
+        let _ = ties.replace_one(doc! { "_id": tie.id }, tie, None);
+        Ok(())
+    }
+
+    fn start_tie(&mut self, tie: &Tie, rubbers: &[Match]) -> Result<(), SeasonError> {
+        self.check_round(&tie.round_id)?;
+        let matches = match_collection(self);
+        let ties = ties_collection(self);
+
+        // The rubbers are written in a single batch, and removed again should
+        // it or saving the tie fail.
+        let mut result = matches.insert_many(rubbers, None).map(|_| ());
+        if result.is_ok() {
+            result = ties
+                .replace_one(doc! { "_id": tie.id }, tie, None)
+                .map(|_| ());
+        }
+
+        if let Err(error) = result {
+            tracing::error!("Error starting tie, {:?}", error);
+
+            let match_ids: Vec<MatchId> = rubbers.iter().map(|a_match| a_match.id).collect();
+            if let Err(error) = matches.delete_many(doc! { "_id": { "$in": match_ids } }, None) {
+                tracing::error!("Error removing tie rubbers, {:?}", error);
+            }
+            return Err(SeasonError::Storage(error.to_string()));
+        }
+        Ok(())
+    }
+
+    fn create_challenge(&mut self, challenge: &Challenge) {
+        let challenges = challenges_collection(self);
+        let _ = challenges.insert_one(challenge, None);
+    }
+
+    fn update_challenge(&mut self, challenge: &Challenge) {
+        let challenges = challenges_collection(self);
+        let _ = challenges.replace_one(doc! { "_id": challenge.id }, challenge, None);
+    }
+
+    fn replace_ratings(&mut self, ratings: &[ParticipantRating]) {
+        let collection = ratings_collection(self);
+
+        // Ratings are always recalculated from scratch so the old ones are
+        // simply thrown away.
+        if let Err(error) = collection.delete_many(doc! {}, None) {
+            tracing::error!("Error clearing ratings, {:?}", error);
+            return;
+        }
+
+        if !ratings.is_empty() {
+            let _ = collection.insert_many(ratings, None);
+        }
+    }
+
     fn get_league(&self, league_id: &LeagueId) -> Option<League> {
         let leagues = leagues_collection(self);
         let result = leagues.find_one(
@@ -169,6 +488,94 @@ impl WriteableStore for MongoStore {
         result.unwrap()
     }
 
+    fn get_session(&self, session_id: &SessionId) -> Option<Session> {
+        let sessions = sessions_collection(self);
+        let result = sessions.find_one(
+            doc! {
+                "_id": session_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_round(&self, round_id: &RoundId) -> Option<Round> {
+        let rounds = round_collection(self);
+        let result = rounds.find_one(
+            doc! {
+                "_id": round_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_venue(&self, venue_id: &VenueId) -> Option<Venue> {
+        let venues = venue_collection(self);
+        let result = venues.find_one(
+            doc! {
+                "_id": venue_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_match(&self, match_id: &MatchId) -> Option<Match> {
+        let matches = match_collection(self);
+        let result = matches.find_one(
+            doc! {
+                "_id": match_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_participant(&self, participant_id: &ParticipantId) -> Option<Participant> {
+        let participants = participants_collection(self);
+        let result = participants.find_one(
+            doc! {
+                "_id": participant_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_group_stage(&self, group_stage_id: &GroupStageId) -> Option<GroupStage> {
+        let group_stages = group_stages_collection(self);
+        let result = group_stages.find_one(
+            doc! {
+                "_id": group_stage_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_tie(&self, tie_id: &TieId) -> Option<Tie> {
+        let ties = ties_collection(self);
+        let result = ties.find_one(
+            doc! {
+                "_id": tie_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
+    fn get_challenge(&self, challenge_id: &ChallengeId) -> Option<Challenge> {
+        let challenges = challenges_collection(self);
+        let result = challenges.find_one(
+            doc! {
+                "_id": challenge_id
+            },
+            None,
+        );
+        result.unwrap()
+    }
+
     fn list_leagues(&self) -> Vec<League> {
         let collection = leagues_collection(self);
         let result = collection.find(None, None);
@@ -223,6 +630,46 @@ impl WriteableStore for MongoStore {
         }
     }
 
+    fn list_challenges_for_league(&self, league_id: &LeagueId) -> Vec<Challenge> {
+        let collection = challenges_collection(self);
+        let options = FindOptions::builder().sort(doc! { "issued": 1 }).build();
+        let result = collection.find(
+            doc! {
+                "league_id": league_id
+            },
+            options,
+        );
+
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::warn!(
+                    "Error finding challenges for league '{:?}', {:?}",
+                    league_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn list_venues(&self) -> Vec<Venue> {
+        let collection = venue_collection(self);
+        let result = collection.find(None, None);
+
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding venues, {:?}", error);
+                Vec::new()
+            }
+        }
+    }
+
     fn list_sessions(&self) -> Vec<Session> {
         let collection = sessions_collection(self);
         let result = collection.find(None, None);
@@ -261,6 +708,172 @@ impl WriteableStore for MongoStore {
             }
         }
     }
+    fn list_rounds_for_season(&self, season_id: &SeasonId) -> Vec<Round> {
+        let session_ids: Vec<SessionId> = self
+            .list_sessions_for_season(season_id)
+            .into_iter()
+            .map(|session| session.id)
+            .collect();
+
+        let result = round_collection(self).find(
+            doc! {
+                "session_id": { "$in": session_ids }
+            },
+            None,
+        );
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!(
+                    "Error finding rounds for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn list_matches_for_season(&self, season_id: &SeasonId) -> Vec<Match> {
+        let round_ids: Vec<RoundId> = self
+            .list_rounds_for_season(season_id)
+            .into_iter()
+            .map(|round| round.id)
+            .collect();
+        if round_ids.is_empty() {
+            return Vec::new();
+        }
+
+        let result = match_collection(self).find(
+            doc! {
+                "round_id": { "$in": round_ids }
+            },
+            None,
+        );
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!(
+                    "Error finding matches for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn list_ties_for_season(&self, season_id: &SeasonId) -> Vec<Tie> {
+        let round_ids: Vec<RoundId> = self
+            .list_rounds_for_season(season_id)
+            .into_iter()
+            .map(|round| round.id)
+            .collect();
+
+        let result = ties_collection(self).find(
+            doc! {
+                "round_id": { "$in": round_ids }
+            },
+            None,
+        );
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!(
+                    "Error finding ties for season '{:?}', {:?}",
+                    season_id,
+                    error
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn list_match_history(&self) -> Vec<PlayedMatch> {
+        let session_dates: HashMap<SessionId, DateTime<Utc>> = self
+            .list_sessions()
+            .into_iter()
+            .map(|session| (session.id, session.date))
+            .collect();
+
+        let round_dates: HashMap<RoundId, DateTime<Utc>> =
+            match round_collection(self).find(None, None) {
+                Ok(cursor) => cursor
+                    .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                    .filter_map(|round| {
+                        let date = session_dates.get(&round.session_id)?;
+                        Some((round.id, *date))
+                    })
+                    .collect(),
+                Err(error) => {
+                    tracing::error!("Error finding rounds, {:?}", error);
+                    return Vec::new();
+                }
+            };
+
+        let result = match_collection(self).find(
+            doc! {
+                "confirmed": true
+            },
+            None,
+        );
+
+        let mut history: Vec<PlayedMatch> = match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .filter_map(|a_match| {
+                    let date = round_dates.get(&a_match.round_id)?;
+                    Some(PlayedMatch {
+                        date: *date,
+                        a_match,
+                    })
+                })
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding confirmed matches, {:?}", error);
+                Vec::new()
+            }
+        };
+
+        history.sort_by_key(|played| played.date);
+        history
+    }
+
+    fn list_ratings(&self) -> Vec<ParticipantRating> {
+        let collection = ratings_collection(self);
+        let result = collection.find(None, None);
+
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding ratings, {:?}", error);
+                Vec::new()
+            }
+        }
+    }
+
+    fn list_participants(&self) -> Vec<Participant> {
+        let collection = participants_collection(self);
+        let result = collection.find(None, None);
+
+        match result {
+            Ok(cursor) => cursor
+                .filter_map(|x| x.ok()) // TODO: log out 'broken' docs
+                .collect(),
+            Err(error) => {
+                tracing::error!("Error finding participants, {:?}", error);
+                Vec::new()
+            }
+        }
+    }
 }
 
 impl Drop for MongoStore {
@@ -306,3 +919,39 @@ fn venue_collection(store: &MongoStore) -> Collection<Venue> {
     let db = store.client.database(DB_NAME);
     db.collection::<Venue>(COLLECTION_VENUES)
 }
+
+/// Return a handle to the MongoDB Participants Collection
+fn participants_collection(store: &MongoStore) -> Collection<Participant> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<Participant>(COLLECTION_PARTICIPANTS)
+}
+
+/// Return a handle to the MongoDB Ratings Collection
+fn ratings_collection(store: &MongoStore) -> Collection<ParticipantRating> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<ParticipantRating>(COLLECTION_RATINGS)
+}
+
+/// Return a handle to the MongoDB Brackets Collection
+fn brackets_collection(store: &MongoStore) -> Collection<Bracket> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<Bracket>(COLLECTION_BRACKETS)
+}
+
+/// Return a handle to the MongoDB Group Stages Collection
+fn group_stages_collection(store: &MongoStore) -> Collection<GroupStage> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<GroupStage>(COLLECTION_GROUP_STAGES)
+}
+
+/// Return a handle to the MongoDB Challenges Collection
+fn challenges_collection(store: &MongoStore) -> Collection<Challenge> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<Challenge>(COLLECTION_CHALLENGES)
+}
+
+/// Return a handle to the MongoDB Ties Collection
+fn ties_collection(store: &MongoStore) -> Collection<Tie> {
+    let db = store.client.database(DB_NAME);
+    db.collection::<Tie>(COLLECTION_TIES)
+}
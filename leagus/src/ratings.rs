@@ -0,0 +1,64 @@
+pub mod elo;
+pub mod glicko2;
+pub mod trueskill;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{Match, ParticipantId, ParticipantRating, RatingSystem};
+
+use self::elo::Elo;
+use self::glicko2::Glicko2;
+use self::trueskill::TrueSkill;
+
+/// A source of skill estimates for participants, such as the ratings from one
+/// of the rating systems. Used to seed and balance matches.
+pub trait SkillRatings {
+    /// The estimated skill of a participant, or `None` if they are unrated.
+    fn skill(&self, participant_id: &ParticipantId) -> Option<f64>;
+}
+
+/// A confirmed match along with when it was played.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlayedMatch {
+    pub date: DateTime<Utc>,
+    pub a_match: Match,
+}
+
+/// Calculate every participant's ratings from scratch from the history of
+/// confirmed matches, across all leagues.
+pub fn recalculate(history: &[PlayedMatch]) -> Vec<ParticipantRating> {
+    let elo = Elo::default().rate(history);
+    let mut glicko = Glicko2::default().rate(history);
+    let mut trueskill = TrueSkill::default().rate(history);
+
+    // Every system rates everyone who has played a decided match.
+    let mut ratings: Vec<ParticipantRating> = elo
+        .into_iter()
+        .map(|(participant_id, elo)| ParticipantRating {
+            participant_id,
+            elo,
+            glicko: glicko.remove(&participant_id).unwrap_or_default(),
+            trueskill: trueskill.remove(&participant_id).unwrap_or_default(),
+        })
+        .collect();
+
+    ratings.sort_by(|a, b| b.elo.rating.total_cmp(&a.elo.rating));
+    ratings
+}
+
+/// The skill of each participant in one of the rating systems, from their
+/// stored ratings.
+pub fn skills(ratings: &[ParticipantRating], system: RatingSystem) -> HashMap<ParticipantId, f64> {
+    ratings
+        .iter()
+        .map(|rating| (rating.participant_id, rating.skill(system)))
+        .collect()
+}
+
+impl SkillRatings for HashMap<ParticipantId, f64> {
+    fn skill(&self, participant_id: &ParticipantId) -> Option<f64> {
+        self.get(participant_id).copied()
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use crate::models::{EloRating, MatchOutcome, ParticipantId, Side};
+
+use super::{PlayedMatch, SkillRatings};
+
+/// The Elo rating system.
+///
+/// Ratings move after each match by `k_factor` times the difference between
+/// the actual and expected result. In doubles each pair is rated as the
+/// average of its members and both members move by the pair's change.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Elo {
+    pub k_factor: f64,
+    pub initial_rating: f64,
+}
+
+impl Default for Elo {
+    fn default() -> Self {
+        Elo {
+            k_factor: 32.0,
+            initial_rating: 1500.0,
+        }
+    }
+}
+
+impl Elo {
+    /// The expected score, between 0 and 1, of a side with `rating` against a
+    /// side with `opponent_rating`.
+    pub fn expected_score(rating: f64, opponent_rating: f64) -> f64 {
+        1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+    }
+
+    /// Rate every participant by processing the confirmed, decided matches in
+    /// chronological order. Matches played at the same time are processed in
+    /// the order given.
+    pub fn rate(&self, history: &[PlayedMatch]) -> HashMap<ParticipantId, EloRating> {
+        let mut ratings: HashMap<ParticipantId, EloRating> = HashMap::new();
+
+        let mut history: Vec<&PlayedMatch> = history
+            .iter()
+            .filter(|played| played.a_match.confirmed)
+            .collect();
+        history.sort_by_key(|played| played.date);
+
+        for played in history {
+            let details = &played.a_match.details;
+            let (Some((home, away)), Some(outcome)) = (details.sides(), details.outcome()) else {
+                continue;
+            };
+
+            let home_rating = self.team_rating(&ratings, &home);
+            let away_rating = self.team_rating(&ratings, &away);
+
+            let home_score = match outcome {
+                MatchOutcome::Win(Side::Home) => 1.0,
+                MatchOutcome::Win(Side::Away) => 0.0,
+                MatchOutcome::Draw => 0.5,
+            };
+            let delta =
+                self.k_factor * (home_score - Elo::expected_score(home_rating, away_rating));
+
+            for (team, delta) in [(home, delta), (away, -delta)] {
+                for participant_id in team {
+                    ratings
+                        .entry(participant_id)
+                        .or_insert_with(|| EloRating::new(self.initial_rating))
+                        .apply(played.a_match.id, played.date, delta);
+                }
+            }
+        }
+
+        ratings
+    }
+
+    /// The average rating of the members of a team.
+    fn team_rating(
+        &self,
+        ratings: &HashMap<ParticipantId, EloRating>,
+        team: &[ParticipantId],
+    ) -> f64 {
+        let total: f64 = team
+            .iter()
+            .map(|id| {
+                ratings
+                    .get(id)
+                    .map_or(self.initial_rating, |rating| rating.rating)
+            })
+            .sum();
+        total / team.len() as f64
+    }
+}
+
+impl SkillRatings for HashMap<ParticipantId, EloRating> {
+    fn skill(&self, participant_id: &ParticipantId) -> Option<f64> {
+        self.get(participant_id).map(|rating| rating.rating)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+
+    use crate::models::{Match, MatchDetails, RoundId, VenueId};
+
+    fn day(n: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap() + TimeDelta::days(n)
+    }
+
+    fn played(date: DateTime<Utc>, details: MatchDetails) -> PlayedMatch {
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = details;
+        a_match.confirmed = true;
+        PlayedMatch { date, a_match }
+    }
+
+    fn singles(home: ParticipantId, away: ParticipantId, scores: Vec<(u8, u8)>) -> MatchDetails {
+        MatchDetails::TennisSingles {
+            players: (home, away),
+            scores,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {expected} but was {actual}"
+        );
+    }
+
+    #[test]
+    fn expected_scores() {
+        assert_close(Elo::expected_score(1500.0, 1500.0), 0.5);
+        assert_close(Elo::expected_score(1900.0, 1500.0), 10.0 / 11.0);
+    }
+
+    #[test]
+    fn win_between_equals() {
+        let (ann, bob) = (ParticipantId::new(), ParticipantId::new());
+        let history = vec![played(day(0), singles(ann, bob, vec![(6, 3), (6, 3)]))];
+
+        let ratings = Elo::default().rate(&history);
+
+        assert_close(ratings[&ann].rating, 1516.0);
+        assert_close(ratings[&bob].rating, 1484.0);
+        assert_eq!(ratings[&ann].history.len(), 1);
+        assert_eq!(ratings[&ann].history[0].match_id, history[0].a_match.id);
+    }
+
+    #[test]
+    fn draw_between_equals() {
+        let (ann, bob) = (ParticipantId::new(), ParticipantId::new());
+        let details = MatchDetails::Chess {
+            players: (ann, bob),
+            result: Some(MatchOutcome::Draw),
+        };
+
+        let ratings = Elo::default().rate(&[played(day(0), details)]);
+
+        assert_close(ratings[&ann].rating, 1500.0);
+        assert_close(ratings[&bob].rating, 1500.0);
+    }
+
+    #[test]
+    fn matches_are_processed_chronologically() {
+        let (ann, bob, cat) = (
+            ParticipantId::new(),
+            ParticipantId::new(),
+            ParticipantId::new(),
+        );
+
+        // Given out of order, Ann beats Bob before she beats Cat.
+        let history = vec![
+            played(day(1), singles(ann, cat, vec![(6, 0), (6, 0)])),
+            played(day(0), singles(ann, bob, vec![(6, 0), (6, 0)])),
+        ];
+
+        let ratings = Elo::default().rate(&history);
+
+        assert_close(ratings[&ann].history[0].after, 1516.0);
+        assert_eq!(ratings[&ann].history[0].match_id, history[1].a_match.id);
+        let expected = 1516.0 + 32.0 * (1.0 - Elo::expected_score(1516.0, 1500.0));
+        assert_close(ratings[&ann].rating, expected);
+    }
+
+    #[test]
+    fn unconfirmed_and_undecided_matches_are_ignored() {
+        let (ann, bob) = (ParticipantId::new(), ParticipantId::new());
+        let mut unconfirmed = played(day(0), singles(ann, bob, vec![(6, 0), (6, 0)]));
+        unconfirmed.a_match.confirmed = false;
+        let undecided = played(day(1), singles(ann, bob, vec![(6, 0)]));
+
+        let ratings = Elo::default().rate(&[unconfirmed, undecided]);
+
+        assert!(ratings.is_empty());
+    }
+
+    #[test]
+    fn doubles_use_team_average() {
+        let ids: Vec<ParticipantId> = (0..5).map(|_| ParticipantId::new()).collect();
+
+        // The first match lifts ids[0] above everyone else.
+        let history = vec![
+            played(day(0), singles(ids[0], ids[4], vec![(6, 0), (6, 0)])),
+            played(
+                day(1),
+                MatchDetails::TennisDoubles {
+                    players: ((ids[0], ids[1]), (ids[2], ids[3])),
+                    scores: vec![(6, 4), (6, 4)],
+                },
+            ),
+        ];
+
+        let ratings = Elo::default().rate(&history);
+
+        let home = (1516.0 + 1500.0) / 2.0;
+        let delta = 32.0 * (1.0 - Elo::expected_score(home, 1500.0));
+        assert_close(ratings[&ids[0]].rating, 1516.0 + delta);
+        assert_close(ratings[&ids[1]].rating, 1500.0 + delta);
+        assert_close(ratings[&ids[2]].rating, 1500.0 - delta);
+        assert_close(ratings[&ids[3]].rating, 1500.0 - delta);
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use chrono::NaiveDate;
+
+use crate::models::{Glicko2Rating, MatchOutcome, ParticipantId, Side};
+
+use super::{PlayedMatch, SkillRatings};
+
+/// The factor converting between the Glicko and Glicko-2 scales.
+const SCALE: f64 = 173.7178;
+
+/// The convergence tolerance when solving for the new volatility.
+const TOLERANCE: f64 = 0.000001;
+
+/// The Glicko-2 rating system, as described by Mark Glickman in "Example of
+/// the Glicko-2 system".
+///
+/// Every session date is a rating period: all the matches played on the same
+/// day are rated together and participants who did not play see their rating
+/// deviation grow. In doubles each participant is rated against a composite
+/// opponent with the average rating and deviation of the opposing pair.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Glicko2 {
+    /// Constrains the change in volatility over time, smaller values prevent
+    /// large changes. Reasonable values are between 0.3 and 1.2.
+    pub tau: f64,
+    pub initial: Glicko2Rating,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Glicko2 {
+            tau: 0.5,
+            initial: Glicko2Rating::default(),
+        }
+    }
+}
+
+impl Glicko2 {
+    /// Rate every participant by processing the confirmed, decided matches one
+    /// rating period at a time.
+    pub fn rate(&self, history: &[PlayedMatch]) -> HashMap<ParticipantId, Glicko2Rating> {
+        let mut ratings: HashMap<ParticipantId, Glicko2Rating> = HashMap::new();
+
+        let mut periods: Vec<(NaiveDate, Vec<&PlayedMatch>)> = Vec::new();
+        let mut history: Vec<&PlayedMatch> = history
+            .iter()
+            .filter(|played| played.a_match.confirmed)
+            .collect();
+        history.sort_by_key(|played| played.date);
+        for played in history {
+            let day = played.date.date_naive();
+            match periods.last_mut() {
+                Some((period, matches)) if *period == day => matches.push(played),
+                _ => periods.push((day, vec![played])),
+            }
+        }
+
+        for (_, matches) in periods {
+            let mut results: HashMap<ParticipantId, Vec<(Glicko2Rating, f64)>> = HashMap::new();
+
+            for played in matches {
+                let details = &played.a_match.details;
+                let (Some((home, away)), Some(outcome)) = (details.sides(), details.outcome())
+                else {
+                    continue;
+                };
+
+                let home_score = match outcome {
+                    MatchOutcome::Win(Side::Home) => 1.0,
+                    MatchOutcome::Win(Side::Away) => 0.0,
+                    MatchOutcome::Draw => 0.5,
+                };
+
+                // Everyone is rated against their opponents' ratings from
+                // before the period.
+                let home_opponent = self.composite(&ratings, &away);
+                let away_opponent = self.composite(&ratings, &home);
+
+                for (team, opponent, score) in [
+                    (home, home_opponent, home_score),
+                    (away, away_opponent, 1.0 - home_score),
+                ] {
+                    for participant_id in team {
+                        results
+                            .entry(participant_id)
+                            .or_default()
+                            .push((opponent.clone(), score));
+                    }
+                }
+            }
+
+            for participant_id in results.keys() {
+                ratings
+                    .entry(*participant_id)
+                    .or_insert_with(|| self.initial.clone());
+            }
+
+            for (participant_id, rating) in ratings.iter_mut() {
+                let games = results
+                    .get(participant_id)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                *rating = self.update(rating, games);
+            }
+        }
+
+        ratings
+    }
+
+    /// Update a rating with the results of a rating period, given as each
+    /// opponent's rating and the score against them: 1 for a win, 0.5 for a
+    /// draw and 0 for a loss.
+    pub fn update(&self, rating: &Glicko2Rating, games: &[(Glicko2Rating, f64)]) -> Glicko2Rating {
+        let mu = (rating.rating - 1500.0) / SCALE;
+        let phi = rating.deviation / SCALE;
+        let sigma = rating.volatility;
+
+        if games.is_empty() {
+            // Only the deviation changes, growing with the volatility.
+            return Glicko2Rating {
+                deviation: (phi * phi + sigma * sigma).sqrt() * SCALE,
+                ..rating.clone()
+            };
+        }
+
+        let mut variance_inverse = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in games {
+            let opponent_mu = (opponent.rating - 1500.0) / SCALE;
+            let opponent_g = g(opponent.deviation / SCALE);
+            let expected = expected(mu, opponent_mu, opponent_g);
+
+            variance_inverse += opponent_g * opponent_g * expected * (1.0 - expected);
+            improvement += opponent_g * (score - expected);
+        }
+        let variance = 1.0 / variance_inverse;
+        let delta = variance * improvement;
+
+        let new_sigma = self.volatility(phi, sigma, variance, delta);
+        let phi_star = (phi * phi + new_sigma * new_sigma).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+
+        Glicko2Rating {
+            rating: new_mu * SCALE + 1500.0,
+            deviation: new_phi * SCALE,
+            volatility: new_sigma,
+        }
+    }
+
+    /// Solve for the new volatility using the Illinois algorithm.
+    fn volatility(&self, phi: f64, sigma: f64, variance: f64, delta: f64) -> f64 {
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let d = phi * phi + variance + ex;
+            ex * (delta * delta - d) / (2.0 * d * d) - (x - a) / (self.tau * self.tau)
+        };
+
+        let mut lower = a;
+        let mut upper = if delta * delta > phi * phi + variance {
+            (delta * delta - phi * phi - variance).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * self.tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * self.tau
+        };
+
+        let mut f_lower = f(lower);
+        let mut f_upper = f(upper);
+        while (upper - lower).abs() > TOLERANCE {
+            let next = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_next = f(next);
+
+            if f_next * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = next;
+            f_upper = f_next;
+        }
+
+        (lower / 2.0).exp()
+    }
+
+    /// The average rating and deviation of a team, from before the period.
+    fn composite(
+        &self,
+        ratings: &HashMap<ParticipantId, Glicko2Rating>,
+        team: &[ParticipantId],
+    ) -> Glicko2Rating {
+        let members: Vec<&Glicko2Rating> = team
+            .iter()
+            .map(|id| ratings.get(id).unwrap_or(&self.initial))
+            .collect();
+        let count = members.len() as f64;
+
+        Glicko2Rating {
+            rating: members.iter().map(|member| member.rating).sum::<f64>() / count,
+            deviation: (members
+                .iter()
+                .map(|member| member.deviation * member.deviation)
+                .sum::<f64>()
+                / count)
+                .sqrt(),
+            volatility: members.iter().map(|member| member.volatility).sum::<f64>() / count,
+        }
+    }
+}
+
+/// Reduces the impact of a game based on the opponent's deviation.
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+/// The expected score against an opponent.
+fn expected(mu: f64, opponent_mu: f64, opponent_g: f64) -> f64 {
+    1.0 / (1.0 + (-opponent_g * (mu - opponent_mu)).exp())
+}
+
+impl SkillRatings for HashMap<ParticipantId, Glicko2Rating> {
+    fn skill(&self, participant_id: &ParticipantId) -> Option<f64> {
+        self.get(participant_id).map(|rating| rating.rating)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+
+    use crate::models::{Match, MatchDetails, RoundId, VenueId};
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "expected {expected} but was {actual}"
+        );
+    }
+
+    fn rating(rating: f64, deviation: f64) -> Glicko2Rating {
+        Glicko2Rating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        }
+    }
+
+    fn played(date: DateTime<Utc>, home: ParticipantId, away: ParticipantId) -> PlayedMatch {
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::TennisSingles {
+            players: (home, away),
+            scores: vec![(6, 0), (6, 0)],
+        };
+        a_match.confirmed = true;
+        PlayedMatch { date, a_match }
+    }
+
+    #[test]
+    fn glickman_example() {
+        // The worked example from Glickman's "Example of the Glicko-2 system".
+        let player = rating(1500.0, 200.0);
+        let games = [
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ];
+
+        let updated = Glicko2::default().update(&player, &games);
+
+        assert_close(updated.rating, 1464.06, 0.01);
+        assert_close(updated.deviation, 151.52, 0.01);
+        assert_close(updated.volatility, 0.05999, 0.00001);
+    }
+
+    #[test]
+    fn inactive_deviation_grows() {
+        let player = rating(1500.0, 200.0);
+
+        let updated = Glicko2::default().update(&player, &[]);
+
+        assert_close(updated.rating, 1500.0, 1e-9);
+        assert_close(updated.deviation, 200.2714, 0.0001);
+    }
+
+    #[test]
+    fn sessions_are_rating_periods() {
+        let (ann, bob, cat) = (
+            ParticipantId::new(),
+            ParticipantId::new(),
+            ParticipantId::new(),
+        );
+        let day = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+
+        let history = vec![
+            played(day, ann, bob),
+            played(day + TimeDelta::hours(1), cat, bob),
+            played(day + TimeDelta::days(7), ann, cat),
+        ];
+
+        let ratings = Glicko2::default().rate(&history);
+
+        // Bob only played in the first period so was rated against
+        // unrated opponents and then left to drift.
+        let first = Glicko2::default();
+        let initial = first.initial.clone();
+        let bob_first = first.update(&initial, &[(initial.clone(), 0.0), (initial.clone(), 0.0)]);
+        let bob_second = first.update(&bob_first, &[]);
+        assert_close(ratings[&bob].rating, bob_second.rating, 1e-9);
+        assert_close(ratings[&bob].deviation, bob_second.deviation, 1e-9);
+
+        assert!(ratings[&ann].rating > ratings[&cat].rating);
+        assert!(ratings[&cat].rating > ratings[&bob].rating);
+    }
+}
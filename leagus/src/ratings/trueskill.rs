@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+
+use crate::models::{MatchOutcome, ParticipantId, Side, TrueSkillRating};
+
+use super::{PlayedMatch, SkillRatings};
+
+/// The TrueSkill rating system for two teams of any size.
+///
+/// Each participant's skill is a Gaussian belief with a mean `mu` and
+/// uncertainty `sigma`. A team's performance is the sum of its members', so
+/// after a match each member is updated in proportion to their own
+/// uncertainty. This models doubles partners individually rather than
+/// averaging them, which matters when partners rotate.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrueSkill {
+    pub initial: TrueSkillRating,
+    /// The distance in skill which gives roughly a 76% chance of winning.
+    pub beta: f64,
+    /// The uncertainty added before every match so ratings can keep moving.
+    pub tau: f64,
+    /// The chance of a draw between two evenly matched teams.
+    pub draw_probability: f64,
+}
+
+impl Default for TrueSkill {
+    /// The standard parameters, with a mean of 25 and sigma of 25/3.
+    fn default() -> Self {
+        let initial = TrueSkillRating::default();
+        TrueSkill {
+            beta: initial.sigma / 2.0,
+            tau: initial.sigma / 100.0,
+            draw_probability: 0.1,
+            initial,
+        }
+    }
+}
+
+impl TrueSkill {
+    /// Rate every participant by processing the confirmed, decided matches in
+    /// chronological order. Matches played at the same time are processed in
+    /// the order given.
+    pub fn rate(&self, history: &[PlayedMatch]) -> HashMap<ParticipantId, TrueSkillRating> {
+        let mut ratings: HashMap<ParticipantId, TrueSkillRating> = HashMap::new();
+
+        let mut history: Vec<&PlayedMatch> = history
+            .iter()
+            .filter(|played| played.a_match.confirmed)
+            .collect();
+        history.sort_by_key(|played| played.date);
+
+        for played in history {
+            let details = &played.a_match.details;
+            let (Some((home, away)), Some(outcome)) = (details.sides(), details.outcome()) else {
+                continue;
+            };
+
+            let rating_of = |id: &ParticipantId| {
+                ratings
+                    .get(id)
+                    .cloned()
+                    .unwrap_or_else(|| self.initial.clone())
+            };
+            let home_ratings: Vec<TrueSkillRating> = home.iter().map(rating_of).collect();
+            let away_ratings: Vec<TrueSkillRating> = away.iter().map(rating_of).collect();
+
+            let (home_ratings, away_ratings) = self.update(&home_ratings, &away_ratings, outcome);
+
+            for (id, rating) in home.into_iter().zip(home_ratings) {
+                ratings.insert(id, rating);
+            }
+            for (id, rating) in away.into_iter().zip(away_ratings) {
+                ratings.insert(id, rating);
+            }
+        }
+
+        ratings
+    }
+
+    /// Update the ratings of the members of the home and away teams after a
+    /// match between them.
+    pub fn update(
+        &self,
+        home: &[TrueSkillRating],
+        away: &[TrueSkillRating],
+        outcome: MatchOutcome,
+    ) -> (Vec<TrueSkillRating>, Vec<TrueSkillRating>) {
+        // Add the dynamics factor before the match.
+        let prior = |team: &[TrueSkillRating]| -> Vec<TrueSkillRating> {
+            team.iter()
+                .map(|rating| TrueSkillRating {
+                    mu: rating.mu,
+                    sigma: (rating.sigma * rating.sigma + self.tau * self.tau).sqrt(),
+                })
+                .collect()
+        };
+        let home = prior(home);
+        let away = prior(away);
+
+        let players = (home.len() + away.len()) as f64;
+        let variance: f64 = home
+            .iter()
+            .chain(away.iter())
+            .map(|rating| rating.sigma * rating.sigma)
+            .sum();
+        let c = (variance + players * self.beta * self.beta).sqrt();
+
+        let draw_margin = ppf((self.draw_probability + 1.0) / 2.0) * players.sqrt() * self.beta;
+        let margin = draw_margin / c;
+
+        let team_mu = |team: &[TrueSkillRating]| team.iter().map(|rating| rating.mu).sum::<f64>();
+
+        // Always work from the winner's perspective, or the home side's for a
+        // draw, and move the other team the opposite way.
+        let (first, second, draw) = match outcome {
+            MatchOutcome::Win(Side::Home) | MatchOutcome::Draw => {
+                (home, away, outcome == MatchOutcome::Draw)
+            }
+            MatchOutcome::Win(Side::Away) => (away, home, false),
+        };
+
+        let difference = (team_mu(&first) - team_mu(&second)) / c;
+        let (v, w) = if draw {
+            (v_draw(difference, margin), w_draw(difference, margin))
+        } else {
+            (v_win(difference, margin), w_win(difference, margin))
+        };
+
+        let apply = |team: Vec<TrueSkillRating>, direction: f64| -> Vec<TrueSkillRating> {
+            team.into_iter()
+                .map(|rating| {
+                    let variance = rating.sigma * rating.sigma;
+                    TrueSkillRating {
+                        mu: rating.mu + direction * variance / c * v,
+                        sigma: (variance * (1.0 - variance / (c * c) * w)).sqrt(),
+                    }
+                })
+                .collect()
+        };
+        let first = apply(first, 1.0);
+        let second = apply(second, -1.0);
+
+        match outcome {
+            MatchOutcome::Win(Side::Away) => (second, first),
+            _ => (first, second),
+        }
+    }
+}
+
+/// The mean additive correction for a win.
+fn v_win(difference: f64, margin: f64) -> f64 {
+    let x = difference - margin;
+    let denominator = cdf(x);
+    if denominator > 0.0 {
+        pdf(x) / denominator
+    } else {
+        -x
+    }
+}
+
+/// The variance multiplicative correction for a win.
+fn w_win(difference: f64, margin: f64) -> f64 {
+    let v = v_win(difference, margin);
+    v * (v + difference - margin)
+}
+
+/// The mean additive correction for a draw.
+fn v_draw(difference: f64, margin: f64) -> f64 {
+    let absolute = difference.abs();
+    let (a, b) = (margin - absolute, -margin - absolute);
+    let denominator = cdf(a) - cdf(b);
+    let v = if denominator > 0.0 {
+        (pdf(b) - pdf(a)) / denominator
+    } else {
+        a
+    };
+
+    if difference < 0.0 {
+        -v
+    } else {
+        v
+    }
+}
+
+/// The variance multiplicative correction for a draw.
+fn w_draw(difference: f64, margin: f64) -> f64 {
+    let absolute = difference.abs();
+    let (a, b) = (margin - absolute, -margin - absolute);
+    let denominator = cdf(a) - cdf(b);
+    let v = v_draw(absolute, margin);
+    v * v + (a * pdf(a) - b * pdf(b)) / denominator
+}
+
+/// The standard normal probability density function.
+fn pdf(x: f64) -> f64 {
+    (-x * x / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+/// The standard normal cumulative distribution function.
+fn cdf(x: f64) -> f64 {
+    0.5 * erfc(-x / SQRT_2)
+}
+
+/// The inverse of the standard normal cumulative distribution function.
+fn ppf(p: f64) -> f64 {
+    -SQRT_2 * inverse_erfc(2.0 * p)
+}
+
+/// The complementary error function, using the Chebyshev approximation from
+/// Numerical Recipes which has a fractional error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + z / 2.0);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+
+    if x < 0.0 {
+        2.0 - r
+    } else {
+        r
+    }
+}
+
+/// The inverse of the complementary error function, refined with Newton's
+/// method.
+fn inverse_erfc(y: f64) -> f64 {
+    if y >= 2.0 {
+        return -100.0;
+    }
+    if y <= 0.0 {
+        return 100.0;
+    }
+
+    let below_one = y < 1.0;
+    let y = if below_one { y } else { 2.0 - y };
+
+    let t = (-2.0 * (y / 2.0).ln()).sqrt();
+    let mut x =
+        -FRAC_1_SQRT_2 * ((2.30753 + t * 0.27061) / (1.0 + t * (0.99229 + t * 0.04481)) - t);
+    for _ in 0..2 {
+        let error = erfc(x) - y;
+        x += error / (2.0 / PI.sqrt() * (-x * x).exp() - x * error);
+    }
+
+    if below_one {
+        x
+    } else {
+        -x
+    }
+}
+
+impl SkillRatings for HashMap<ParticipantId, TrueSkillRating> {
+    fn skill(&self, participant_id: &ParticipantId) -> Option<f64> {
+        self.get(participant_id).map(|rating| rating.mu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    use crate::models::{Match, MatchDetails, RoundId, VenueId};
+
+    fn assert_rating(rating: &TrueSkillRating, mu: f64, sigma: f64) {
+        assert!(
+            (rating.mu - mu).abs() < 0.001 && (rating.sigma - sigma).abs() < 0.001,
+            "expected ({mu}, {sigma}) but was ({}, {})",
+            rating.mu,
+            rating.sigma
+        );
+    }
+
+    #[test]
+    fn one_on_one_win() {
+        // Reference values from the TrueSkill documentation.
+        let (home, away) = TrueSkill::default().update(
+            &[TrueSkillRating::default()],
+            &[TrueSkillRating::default()],
+            MatchOutcome::Win(Side::Home),
+        );
+
+        assert_rating(&home[0], 29.396, 7.171);
+        assert_rating(&away[0], 20.604, 7.171);
+    }
+
+    #[test]
+    fn one_on_one_draw() {
+        let (home, away) = TrueSkill::default().update(
+            &[TrueSkillRating::default()],
+            &[TrueSkillRating::default()],
+            MatchOutcome::Draw,
+        );
+
+        assert_rating(&home[0], 25.0, 6.458);
+        assert_rating(&away[0], 25.0, 6.458);
+    }
+
+    #[test]
+    fn two_on_two_win() {
+        let initial = TrueSkillRating::default();
+        let team = [initial.clone(), initial];
+        let (home, away) = TrueSkill::default().update(&team, &team, MatchOutcome::Win(Side::Away));
+
+        for rating in &home {
+            assert_rating(rating, 21.892, 7.774);
+        }
+        for rating in &away {
+            assert_rating(rating, 28.108, 7.774);
+        }
+    }
+
+    #[test]
+    fn uncertain_partner_moves_more() {
+        let settled = TrueSkillRating {
+            mu: 25.0,
+            sigma: 3.0,
+        };
+        let newcomer = TrueSkillRating::default();
+        let opponents = [TrueSkillRating::default(), TrueSkillRating::default()];
+
+        let (home, _) = TrueSkill::default().update(
+            &[settled, newcomer],
+            &opponents,
+            MatchOutcome::Win(Side::Home),
+        );
+
+        assert!(home[1].mu - 25.0 > home[0].mu - 25.0);
+    }
+
+    #[test]
+    fn rate_doubles_history() {
+        let ids: Vec<ParticipantId> = (0..4).map(|_| ParticipantId::new()).collect();
+        let mut a_match = Match::new(RoundId::new(), VenueId::new());
+        a_match.details = MatchDetails::TennisDoubles {
+            players: ((ids[0], ids[1]), (ids[2], ids[3])),
+            scores: vec![(6, 2), (6, 2)],
+        };
+        a_match.confirmed = true;
+        let date = Utc.with_ymd_and_hms(2024, 1, 1, 12, 00, 00).unwrap();
+
+        let ratings = TrueSkill::default().rate(&[PlayedMatch { date, a_match }]);
+
+        assert_rating(&ratings[&ids[0]], 28.108, 7.774);
+        assert_rating(&ratings[&ids[1]], 28.108, 7.774);
+        assert_rating(&ratings[&ids[2]], 21.892, 7.774);
+        assert_rating(&ratings[&ids[3]], 21.892, 7.774);
+    }
+}